@@ -0,0 +1,61 @@
+//! Micro-benchmarks of the attack generators. Run with `cargo bench --bench attacks`; every
+//! benchmark computes the attacks from all 64 squares against a set of blocker patterns and
+//! prints the median time per call.
+
+use shax::attacks::{
+    bishop_attacks, king_attacks, king_attacks_from, knight_attacks, knight_attacks_from,
+    pawn_advances, pawn_attacks, queen_attacks, rook_attacks,
+};
+use shax::notation::{Color, Square};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 9;
+const ITERATIONS: usize = 2_000;
+
+/// Blocker patterns taken from typical middlegame and endgame occupancies.
+const BLOCKERS: [u64; 4] = [
+    0xffff00000000ffff,
+    0x91ad0a5c1a26a1bd,
+    0x0020100804000000,
+    0x8148004a008aa02b,
+];
+
+fn bench(name: &str, f: impl Fn(Square, u64) -> u64) {
+    let squares: Vec<Square> = (0..64).filter_map(Square::from_repr).collect();
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                for &blockers in &BLOCKERS {
+                    for &square in &squares {
+                        black_box(f(black_box(square), black_box(blockers)));
+                    }
+                }
+            }
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    let calls = ITERATIONS * BLOCKERS.len() * squares.len();
+    println!(
+        "{name:<16} {:>6.2} ns/call",
+        times[RUNS / 2].as_nanos() as f64 / calls as f64
+    );
+}
+
+fn main() {
+    bench("rook", rook_attacks);
+    bench("bishop", bishop_attacks);
+    bench("queen", queen_attacks);
+    bench("knight", |square, _| knight_attacks(1 << square as usize));
+    bench("knight table", |square, _| knight_attacks_from(square));
+    bench("king", |square, _| king_attacks(1 << square as usize));
+    bench("king table", |square, _| king_attacks_from(square));
+    bench("pawn attacks", |square, _| {
+        pawn_attacks(1 << square as usize, Color::White)
+    });
+    bench("pawn advances", |square, blockers| {
+        pawn_advances(square, Color::White, blockers)
+    });
+}
@@ -0,0 +1,52 @@
+//! Benchmarks of the bitboard-heavy inner loops: move generation (through perft) and
+//! evaluation. Run with `cargo bench`; every benchmark prints the median of several runs.
+
+use shax::board::Board;
+use shax::engine::meta;
+use shax::eval::evaluate;
+use shax::search::search;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 7;
+
+fn bench<T>(name: &str, iterations: u64, mut f: impl FnMut() -> T) {
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                black_box(f());
+            }
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    let median = times[RUNS / 2];
+    println!(
+        "{name:<24} {:>10.1} ns/iter",
+        median.as_nanos() as f64 / iterations as f64
+    );
+}
+
+fn main() {
+    let positions: Vec<Board> = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    ]
+    .iter()
+    .map(|fen| Board::from_fen(fen).unwrap())
+    .collect();
+
+    bench("perft kiwipete 3", 5, || positions[1].perft(3));
+    bench("perft startpos 4", 3, || positions[0].perft(4));
+    bench("evaluate", 100_000, || {
+        positions.iter().map(|board| evaluate(board)).sum::<i32>()
+    });
+
+    // The node count of fixed-depth searches changes with any change in search behavior
+    let nodes: u64 = positions.iter().map(|board| search(board, 4).nodes).sum();
+    println!("{}", meta::bench_signature(nodes));
+}
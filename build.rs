@@ -0,0 +1,49 @@
+//! Passes the git commit of the build to the crate as `SHAX_GIT_HASH`, so that builds of the
+//! same version can be told apart. Nothing is set when building outside a git checkout of
+//! shax itself, e.g. from crates.io or from a copy vendored inside another repository.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn main() {
+    let root = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    if !root.join(".git").exists() {
+        return;
+    }
+    for path in [".git/HEAD", ".git/index"] {
+        if root.join(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+
+    // The crate must be the root of the repository, not a directory inside another one
+    let toplevel = git(&root, &["rev-parse", "--show-toplevel"]);
+    if !toplevel.is_some_and(|toplevel| same_path(Path::new(&toplevel), &root)) {
+        return;
+    }
+    if let Some(hash) = git(&root, &["rev-parse", "--short=8", "HEAD"]) {
+        println!("cargo:rustc-env=SHAX_GIT_HASH={hash}");
+    }
+}
+
+/// Runs git in `dir` and returns its trimmed output, [`None`] if it failed.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|output| output.trim().to_owned())
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,63 @@
+//! Answers legal move queries line by line, as a rules server for a GUI would.
+//!
+//! Each input line is a FEN, optionally followed by `moves` and moves in LAN to play from it.
+//! The reply lists the legal moves of the resulting position with their SAN, and the size of
+//! the same state encoded for the wire.
+//!
+//! ```sh
+//! echo "startpos moves e2e4 e7e5" | cargo run --example legal_move_server
+//! ```
+
+use shax::board::Board;
+use shax::notation::Move;
+use shax::pgn::san;
+use shax::wire;
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+
+/// Sets up the position of a query: `<fen | startpos> [moves <lan>...]`.
+fn position(query: &str) -> Result<Board, Box<dyn Error>> {
+    let (fen, moves) = match query.split_once(" moves") {
+        Some((fen, moves)) => (fen.trim(), moves),
+        None => (query.trim(), ""),
+    };
+    let mut board = match fen {
+        "startpos" => Board::default(),
+        fen => Board::from_fen(fen)?,
+    };
+    for lan in moves.split_whitespace() {
+        board.make_move(Move::from_lan(lan)?)?;
+    }
+    Ok(board)
+}
+
+fn answer(query: &str, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let board = position(query)?;
+    writeln!(out, "fen {}", board.to_fen())?;
+    writeln!(out, "status {:?}", board.status())?;
+
+    let mut moves: Vec<Move> = board.color_moves(board.active()).collect();
+    moves.sort();
+    for mov in moves {
+        writeln!(out, "move {} {}", mov.to_lan(), san(&board, mov)?)?;
+    }
+    writeln!(out, "wire {} bytes", wire::encode(&board).len())?;
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Err(err) = answer(&line, &mut out) {
+            writeln!(out, "error {err}")?;
+        }
+        writeln!(out, "end")?;
+        out.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! Validates move generation against the known perft node counts of the standard test
+//! positions, and exits with an error if any count differs.
+//!
+//! ```sh
+//! cargo run --release --example perft_validator -- 4
+//! ```
+//!
+//! With the `rayon` feature the root moves are counted in parallel.
+
+use shax::positions;
+use std::process::ExitCode;
+use std::time::Instant;
+
+fn main() -> ExitCode {
+    let max_depth = match std::env::args().nth(1).map(|arg| arg.parse::<usize>()) {
+        None => 3,
+        Some(Ok(depth)) if depth > 0 => depth,
+        Some(_) => {
+            eprintln!("usage: perft_validator [max depth]");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut failures = 0;
+    for position in positions::ALL {
+        let board = position.board();
+        for (depth, &expected) in position.perft.iter().enumerate().take(max_depth) {
+            let depth = depth + 1;
+            let start = Instant::now();
+            #[cfg(feature = "rayon")]
+            let nodes = board.perft_parallel(depth);
+            #[cfg(not(feature = "rayon"))]
+            let nodes = board.perft(depth);
+            let elapsed = start.elapsed();
+            let verdict = if nodes == expected { "ok" } else { "FAILED" };
+            println!(
+                "{:<12} depth {depth}  {nodes:>12}  expected {expected:>12}  {:>8.1?}  {verdict}",
+                position.name, elapsed
+            );
+            if nodes != expected {
+                failures += 1;
+            }
+        }
+    }
+
+    if failures == 0 {
+        println!("all counts match");
+        ExitCode::SUCCESS
+    } else {
+        println!("{failures} counts differ");
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,131 @@
+//! Reads games from a PGN file and flags the moves that lose the most against the engine's
+//! choice, like the blunder check of an analysis board.
+//!
+//! ```sh
+//! cargo run --release --example pgn_analyzer -- games.pgn 4
+//! ```
+//!
+//! Without arguments a short sample game is analyzed.
+
+use shax::board::Board;
+use shax::notation::Color;
+use shax::pgn::{parse_san, san, SanMode};
+use shax::search::{search, Score};
+use std::error::Error;
+
+/// Loss in centipawns from which a move is reported as a blunder.
+const BLUNDER: i32 = 200;
+
+const SAMPLE: &str = r#"[Event "Sample"]
+[White "White"]
+[Black "Black"]
+[Result "0-1"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bc4 Nd4 4. Nxe5 Qg5 5. Nxf7 Qxg2 6. Rf1 Qxe4+ 7. Be2 Nf3# 0-1
+"#;
+
+/// Splits PGN text into games, each given by its tags and its moves in SAN.
+fn games(pgn: &str) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut games = Vec::new();
+    let (mut tags, mut moves) = (Vec::new(), Vec::new());
+    let mut in_comment = false;
+    for line in pgn.lines() {
+        let line = line.trim();
+        if line.starts_with('[') && !in_comment {
+            if !moves.is_empty() {
+                games.push((std::mem::take(&mut tags), std::mem::take(&mut moves)));
+            }
+            tags.push(line.to_string());
+            continue;
+        }
+        for token in line.split_whitespace() {
+            if in_comment || token.starts_with('{') {
+                in_comment = !token.ends_with('}');
+            } else if token.starts_with('$') || token.ends_with('.') {
+                // Numeric annotation glyph or move number
+            } else if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                games.push((std::mem::take(&mut tags), std::mem::take(&mut moves)));
+            } else {
+                let token = token.rsplit('.').next().unwrap_or(token);
+                moves.push(token.to_string());
+            }
+        }
+    }
+    if !moves.is_empty() {
+        games.push((tags, moves));
+    }
+    games
+}
+
+/// Converts a score to centipawns for comparing moves, with mates beyond any evaluation.
+fn centipawns(score: Score) -> i32 {
+    match score {
+        Score::Centipawns(cp) => cp,
+        Score::Mate(moves) if moves > 0 => 100_000 - moves,
+        Score::Mate(moves) => -100_000 - moves,
+    }
+}
+
+fn analyze(moves: &[String], depth: usize) -> Result<(), Box<dyn Error>> {
+    let mut board = Board::default();
+    for text in moves {
+        let mov = parse_san(&board, text, SanMode::Lenient)?;
+        let best = search(&board, depth);
+        let mut after = board.clone();
+        after.make_move(mov)?;
+
+        // The score after the move is from the opponent's side, and 0 if the game has ended
+        // without a mate.
+        let played = if after.is_checkmate() {
+            100_000
+        } else if after.winner().is_some() {
+            0
+        } else {
+            -centipawns(search(&after, depth.saturating_sub(1).max(1)).score)
+        };
+        let loss = centipawns(best.score) - played;
+        if loss >= BLUNDER && Some(mov) != best.best_move {
+            let better = best.best_move.map(|best| san(&board, best)).transpose()?;
+            println!(
+                "  {}{} {} loses {} (best {}, {})",
+                board.fullmove_number(),
+                if board.active() == Color::White {
+                    "."
+                } else {
+                    "..."
+                },
+                text,
+                loss.min(9999),
+                better.unwrap_or_default(),
+                best.score
+            );
+        }
+        board = after;
+    }
+    println!("  final position: {}", board.to_fen());
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let pgn = match args.next() {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => SAMPLE.to_string(),
+    };
+    let depth = args
+        .next()
+        .map(|depth| depth.parse())
+        .transpose()?
+        .unwrap_or(3);
+
+    for (index, (tags, moves)) in games(&pgn).iter().enumerate() {
+        println!("game {}: {} moves", index + 1, moves.len());
+        for tag in tags {
+            println!("  {tag}");
+        }
+        if let Err(err) = analyze(moves, depth) {
+            println!("  stopped: {err}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Plays a game against itself: a few random opening moves for variety, then timed searches
+//! with a small clock for both sides. Prints each move with its score and the final PGN.
+//!
+//! ```sh
+//! cargo run --release --example simple_bot -- 7
+//! ```
+
+use shax::board::Board;
+use shax::engine::meta;
+use shax::game::Game;
+use shax::notation::Color;
+use shax::opening::OpeningRandomizer;
+use shax::pgn::san;
+use shax::search::{search_timed, TimeControl};
+use shax::stop::StopToken;
+use std::error::Error;
+use std::time::Duration;
+
+/// Longest game played before it is adjudicated as unfinished.
+const MAX_PLIES: usize = 300;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let seed = match std::env::args().nth(1) {
+        Some(seed) => seed.parse()?,
+        None => 1,
+    };
+
+    let mut start = Board::default();
+    let opening = OpeningRandomizer::new(4, seed).play(&mut start);
+    println!("opening from seed {seed}: {} plies", opening.len());
+    let mut game = Game::from_board(start).with_auto_claim_draws(true);
+
+    let mut clock = TimeControl {
+        wtime: Some(Duration::from_secs(10)),
+        btime: Some(Duration::from_secs(10)),
+        winc: Some(Duration::from_millis(100)),
+        binc: Some(Duration::from_millis(100)),
+        ..TimeControl::default()
+    };
+    let stop = StopToken::new();
+
+    while !game.is_over() && game.moves().len() < MAX_PLIES {
+        let board = game.board().clone();
+        let result = search_timed(&board, 64, &clock, &stop);
+        let Some(mov) = result.best_move else { break };
+
+        let (remaining, increment) = match board.active() {
+            Color::White => (&mut clock.wtime, clock.winc),
+            Color::Black => (&mut clock.btime, clock.binc),
+        };
+        *remaining = remaining.map(|time| time.saturating_sub(result.time) + increment.unwrap());
+
+        println!(
+            "{:>3}. {:<8} {:<10} depth {:>2}  {:>8} nodes",
+            board.fullmove_number(),
+            san(&board, mov)?,
+            result.score.to_string(),
+            result.depth,
+            result.nodes
+        );
+        game.make_move(mov)?;
+    }
+
+    let mut pgn = game.pgn();
+    meta::annotate(&mut pgn);
+    println!("\n{}", pgn.write()?);
+    Ok(())
+}
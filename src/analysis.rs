@@ -0,0 +1,240 @@
+//! Position analysis for applications built on top of the engine, such as hints and move
+//! explanations for human players.
+
+use crate::board::{Board, MoveError};
+use crate::notation::Move;
+use crate::search::{search, search_with_limits, Score, SearchLimits, SearchResult, TimeControl};
+use crate::stop::StopToken;
+use std::time::Duration;
+
+/// How strong a move [`hint`] suggests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HintLevel {
+    /// Looks one move ahead: avoids hanging pieces and takes free material, but misses
+    /// combinations.
+    #[default]
+    Beginner,
+    /// Looks two moves ahead, which finds simple tactics and mates in one.
+    Club,
+    /// Looks four moves ahead.
+    Master,
+}
+
+impl HintLevel {
+    /// Search depth in plies used for the level.
+    pub fn depth(&self) -> usize {
+        match self {
+            HintLevel::Beginner => 1,
+            HintLevel::Club => 2,
+            HintLevel::Master => 4,
+        }
+    }
+}
+
+/// Suggests a move for the side to move at the given strength, or [`None`] if the game is
+/// over. The strength is capped by searching to the depth of the level, so hints are quick and
+/// do not give away deep engine lines.
+///
+/// ```
+/// use shax::analysis::{hint, HintLevel};
+/// use shax::board::Board;
+/// use shax::notation::Move;
+///
+/// // Back rank mate
+/// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+/// assert_eq!(hint(&board, HintLevel::Club), Some(Move::from_lan("a1a8").unwrap()));
+///
+/// let mated = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+/// assert_eq!(hint(&mated, HintLevel::Master), None);
+/// ```
+pub fn hint(board: &Board, level: HintLevel) -> Option<Move> {
+    search(board, level.depth()).best_move
+}
+
+/// Answer of [`why_not`]: how a move compares with the best one, from the perspective of the
+/// side playing it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// Search of the move asked about alone: its score, and the line expected after it.
+    pub played: SearchResult,
+    /// Search of every move: the best move, its score and line.
+    pub best: SearchResult,
+    /// Reply of the opponent to the move asked about, [`None`] if the move ends the game.
+    pub refutation: Option<Move>,
+}
+
+impl Explanation {
+    /// Returns whether the move asked about is the best move found.
+    pub fn is_best(&self) -> bool {
+        self.played.best_move == self.best.best_move
+    }
+}
+
+/// Explains why `mov` is worse than the best move: searches the move alone and every move,
+/// each within the `limits`, and returns both lines with the opponent's answer to `mov`. The
+/// scores of the two searches are comparable, both being from the perspective of the side to
+/// move.
+///
+/// The root moves and pondering of the limits are ignored. Returns [`MoveError`] if `mov` is
+/// not legal.
+///
+/// ```
+/// use shax::analysis::why_not;
+/// use shax::board::Board;
+/// use shax::notation::Move;
+///
+/// // Moving the king leaves the rook without defense instead of taking the queen
+/// let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+/// let limits = "depth 3".parse().unwrap();
+/// let explanation = why_not(&board, Move::from_lan("e1f1").unwrap(), &limits).unwrap();
+/// assert_eq!(explanation.best.best_move, Some(Move::from_lan("d2d5").unwrap()));
+/// assert_eq!(explanation.refutation, Some(Move::from_lan("d5d2").unwrap()));
+/// assert!(!explanation.is_best());
+/// ```
+pub fn why_not(board: &Board, mov: Move, limits: &SearchLimits) -> Result<Explanation, MoveError> {
+    let mov = board.validate_move(board.active(), mov)?;
+    let limits = SearchLimits {
+        root_moves: Vec::new(),
+        ponder: false,
+        ..limits.clone()
+    };
+    let best = search_with_limits(board, &limits, &StopToken::new());
+    let only = SearchLimits {
+        root_moves: vec![mov],
+        ..limits
+    };
+    let played = search_with_limits(board, &only, &StopToken::new());
+    Ok(Explanation {
+        refutation: played.pv.get(1).copied(),
+        played,
+        best,
+    })
+}
+
+/// Coarse assessment of a position for the side to move, as [`quick_verdict`] gives it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verdict {
+    Losing,
+    Worse,
+    Equal,
+    Better,
+    Winning,
+}
+
+impl Verdict {
+    /// Least advantage in centipawns for [`Verdict::Better`], about a pawn.
+    pub const BETTER: i32 = 100;
+    /// Least advantage in centipawns for [`Verdict::Winning`], about a minor piece.
+    pub const WINNING: i32 = 300;
+
+    /// Classifies a score from the perspective of the side to move. Any mate is decisive.
+    pub fn from_score(score: Score) -> Self {
+        match score {
+            Score::Mate(moves) if moves > 0 => Verdict::Winning,
+            Score::Mate(_) => Verdict::Losing,
+            Score::Centipawns(cp) if cp >= Self::WINNING => Verdict::Winning,
+            Score::Centipawns(cp) if cp >= Self::BETTER => Verdict::Better,
+            Score::Centipawns(cp) if cp > -Self::BETTER => Verdict::Equal,
+            Score::Centipawns(cp) if cp > -Self::WINNING => Verdict::Worse,
+            Score::Centipawns(_) => Verdict::Losing,
+        }
+    }
+}
+
+/// Assesses the position for the side to move with a search of at most `millis`
+/// milliseconds, for callers with a strict latency budget. The search returns as soon as the
+/// time is up, give or take the time to search a thousand nodes, with the assessment of the
+/// deepest iteration completed. A game already over is [`Verdict::Losing`] when checkmated and
+/// [`Verdict::Equal`] when drawn by stalemate.
+///
+/// ```
+/// use shax::analysis::{quick_verdict, Verdict};
+/// use shax::board::Board;
+///
+/// let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+/// assert_eq!(quick_verdict(&board, 50), Verdict::Winning);
+///
+/// let board = Board::from_fen("4k3/8/8/8/8/8/3Q4/4K3 b - - 0 1").unwrap();
+/// assert_eq!(quick_verdict(&board, 50), Verdict::Losing);
+/// ```
+pub fn quick_verdict(board: &Board, millis: u64) -> Verdict {
+    let limits = SearchLimits {
+        time: TimeControl {
+            movetime: Some(Duration::from_millis(millis)),
+            ..TimeControl::default()
+        },
+        ..SearchLimits::default()
+    };
+    Verdict::from_score(search_with_limits(board, &limits, &StopToken::new()).score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_hint_takes_free_queen() {
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        for level in [HintLevel::Beginner, HintLevel::Club, HintLevel::Master] {
+            assert_eq!(hint(&board, level), Some(Move::from_lan("d2d5").unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_hint_is_legal() {
+        let board = Board::default();
+        let mov = hint(&board, HintLevel::Master).unwrap();
+        assert!(board.clone().make_move(mov).is_ok());
+    }
+
+    #[test]
+    fn test_why_not() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let limits = "depth 3".parse().unwrap();
+        let explanation = why_not(&board, Move::from_lan("a1a8").unwrap(), &limits).unwrap();
+        assert!(explanation.is_best());
+        assert_eq!(explanation.played.score, explanation.best.score);
+        assert_eq!(explanation.refutation, None); // Mate
+
+        let explanation = why_not(&board, Move::from_lan("a1a2").unwrap(), &limits).unwrap();
+        assert_eq!(
+            explanation.played.best_move,
+            Some(Move::from_lan("a1a2").unwrap())
+        );
+        assert_eq!(
+            explanation.played.pv.get(1),
+            explanation.refutation.as_ref()
+        );
+        assert_eq!(
+            why_not(&board, Move::from_lan("a1b3").unwrap(), &limits),
+            Err(MoveError::IllegalMove)
+        );
+    }
+
+    #[test]
+    fn test_quick_verdict() {
+        let start = Instant::now();
+        let mated = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(quick_verdict(&mated, 20), Verdict::Losing);
+        let mating = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(quick_verdict(&mating, 20), Verdict::Winning);
+        let black = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 b - - 0 1").unwrap();
+        assert_eq!(quick_verdict(&black, 20), Verdict::Winning);
+        assert_eq!(quick_verdict(&Board::default(), 0), Verdict::Equal);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_verdict_from_score() {
+        assert_eq!(
+            Verdict::from_score(Score::Centipawns(-300)),
+            Verdict::Losing
+        );
+        assert_eq!(Verdict::from_score(Score::Centipawns(-299)), Verdict::Worse);
+        assert_eq!(Verdict::from_score(Score::Centipawns(-99)), Verdict::Equal);
+        assert_eq!(Verdict::from_score(Score::Centipawns(100)), Verdict::Better);
+        assert_eq!(Verdict::from_score(Score::Mate(-3)), Verdict::Losing);
+        assert!(Verdict::Winning > Verdict::Better);
+    }
+}
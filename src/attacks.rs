@@ -1,13 +1,42 @@
+//! Attack generators for every piece type. Square A1 is bit 0 and H8 is bit 63.
+//!
+//! Sliders take the occupancy of the board as blockers; the first blocker in each direction is
+//! included in the attacks, whatever its color.
+//!
+//! ```
+//! use shax::attacks::{knight_attacks_from, rook_attacks};
+//! use shax::masks::{FILE_A, RANK_1};
+//! use shax::notation::Square;
+//!
+//! assert_eq!(knight_attacks_from(Square::A1).count_ones(), 2);
+//! assert_eq!(rook_attacks(Square::A1, 0), (FILE_A | RANK_1) & !1);
+//! ```
+
+use crate::magic::get_magics_cache;
+use crate::masks::{FILE_A, FILE_B, FILE_G, FILE_H, RANK_2, RANK_3, RANK_6, RANK_7};
 use crate::notation::{Color, Square};
 use crate::rays::get_rays_cache;
 use crate::{bitscan_forward, bitscan_reverse};
-use crate::{FILE_A, FILE_B, FILE_G, FILE_H, RANK_2, RANK_7};
 
+/// Returns the squares attacked by a queen on the square.
 pub fn queen_attacks(square: Square, blockers: u64) -> u64 {
     rook_attacks(square, blockers) | bishop_attacks(square, blockers)
 }
 
+/// Returns the squares attacked by a rook on the square, looked up in the magic tables.
+#[inline(always)]
 pub fn rook_attacks(square: Square, blockers: u64) -> u64 {
+    get_magics_cache().rook_attacks(square, blockers)
+}
+
+/// Returns the squares attacked by a bishop on the square, looked up in the magic tables.
+#[inline(always)]
+pub fn bishop_attacks(square: Square, blockers: u64) -> u64 {
+    get_magics_cache().bishop_attacks(square, blockers)
+}
+
+/// Ray-scan rook attacks, used to fill the magic bitboard tables.
+pub(crate) fn classical_rook_attacks(square: Square, blockers: u64) -> u64 {
     let rays = get_rays_cache();
     let mut bb = 0;
     let square = square as usize;
@@ -30,7 +59,8 @@ pub fn rook_attacks(square: Square, blockers: u64) -> u64 {
     bb
 }
 
-pub fn bishop_attacks(square: Square, blockers: u64) -> u64 {
+/// Ray-scan bishop attacks, used to fill the magic bitboard tables.
+pub(crate) fn classical_bishop_attacks(square: Square, blockers: u64) -> u64 {
     let rays = get_rays_cache();
     let mut bb = 0;
     let square = square as usize;
@@ -53,8 +83,35 @@ pub fn bishop_attacks(square: Square, blockers: u64) -> u64 {
     bb
 }
 
+/// Returns the squares attacked by a king on the square, looked up in a precomputed table.
+#[inline(always)]
+pub fn king_attacks_from(square: Square) -> u64 {
+    KING_ATTACKS[square as usize]
+}
+
+/// Returns the squares attacked by a knight on the square, looked up in a precomputed table.
+#[inline(always)]
+pub fn knight_attacks_from(square: Square) -> u64 {
+    KNIGHT_ATTACKS[square as usize]
+}
+
+static KING_ATTACKS: [u64; 64] = step_attack_tables().0;
+static KNIGHT_ATTACKS: [u64; 64] = step_attack_tables().1;
+
+const fn step_attack_tables() -> ([u64; 64], [u64; 64]) {
+    let (mut king, mut knight) = ([0; 64], [0; 64]);
+    let mut square = 0;
+    while square < 64 {
+        king[square] = king_attacks(1 << square);
+        knight[square] = knight_attacks(1 << square);
+        square += 1;
+    }
+    (king, knight)
+}
+
+/// Returns the squares attacked by kings on any of the squares of the bitboard.
 #[inline(always)]
-pub fn king_attacks(bb: u64) -> u64 {
+pub const fn king_attacks(bb: u64) -> u64 {
     (bb << 8)
         | (bb >> 8)
         | ((bb << 1) & !FILE_A)
@@ -65,6 +122,7 @@ pub fn king_attacks(bb: u64) -> u64 {
         | ((bb >> 9) & !FILE_H)
 }
 
+/// Returns the squares attacked by pawns of the color on any of the squares of the bitboard.
 #[inline(always)]
 pub fn pawn_attacks(bb: u64, color: Color) -> u64 {
     match color {
@@ -73,6 +131,7 @@ pub fn pawn_attacks(bb: u64, color: Color) -> u64 {
     }
 }
 
+/// Returns the squares pawns of the color on the bitboard would advance to on an empty board.
 #[inline(always)]
 pub fn pseudo_pawn_advances(bb: u64, color: Color) -> u64 {
     match color {
@@ -81,25 +140,27 @@ pub fn pseudo_pawn_advances(bb: u64, color: Color) -> u64 {
     }
 }
 
+/// Returns the squares a pawn on the square can advance to: one square ahead if it is empty,
+/// and two squares from the starting rank if both are empty.
+#[inline(always)]
 pub fn pawn_advances(square: Square, color: Color, blockers: u64) -> u64 {
-    let rays = get_rays_cache();
-
-    let bb = 1 << square as usize;
-    let advances = pseudo_pawn_advances(bb, color);
+    let bb = 1u64 << square as usize;
+    let empty = !blockers;
     match color {
-        Color::White => match rays.get(bitscan_forward(advances & blockers)) {
-            Some(ray) => advances & !blockers & !ray.north,
-            None => advances & !blockers,
-        },
-        Color::Black => match rays.get(bitscan_reverse(advances & blockers)) {
-            Some(ray) => advances & !blockers & !ray.south,
-            None => advances & !blockers,
-        },
+        Color::White => {
+            let single = (bb << 8) & empty;
+            single | ((single & RANK_3) << 8) & empty
+        }
+        Color::Black => {
+            let single = (bb >> 8) & empty;
+            single | ((single & RANK_6) >> 8) & empty
+        }
     }
 }
 
+/// Returns the squares attacked by knights on any of the squares of the bitboard.
 #[inline(always)]
-pub fn knight_attacks(bb: u64) -> u64 {
+pub const fn knight_attacks(bb: u64) -> u64 {
     ((bb << 6) & !(FILE_G | FILE_H))
         | ((bb << 15) & !FILE_H)
         | ((bb >> 6) & !(FILE_A | FILE_B))
@@ -181,6 +242,31 @@ mod tests {
         assert_eq!(knight_attacks(0x800000000000020), 0x22140000508800);
     }
 
+    #[test]
+    fn test_step_attack_tables() {
+        for square in 0..64 {
+            let sq = Square::from_index(square).unwrap();
+            assert_eq!(knight_attacks_from(sq), knight_attacks(1 << square));
+            assert_eq!(king_attacks_from(sq), king_attacks(1 << square));
+        }
+    }
+
+    #[test]
+    fn test_pawn_advances() {
+        assert_eq!(pawn_advances(E2, White, 0), 0x0000000010100000);
+        assert_eq!(
+            pawn_advances(E2, White, 0x0000000010000000),
+            0x0000000000100000
+        );
+        assert_eq!(pawn_advances(E2, White, 0x0000000000100000), 0);
+        assert_eq!(pawn_advances(E3, White, 0), 0x0000000010000000);
+        assert_eq!(pawn_advances(D7, Black, 0), 0x0000080800000000);
+        assert_eq!(
+            pawn_advances(D7, Black, 0x0000000800000000),
+            0x0000080000000000
+        );
+    }
+
     #[test]
     fn test_king_attacks() {
         assert_eq!(king_attacks(0x0800008100000008), 0x141cc342c3001c14);
@@ -0,0 +1,218 @@
+//! Typed sets of squares.
+
+use crate::bitscan_forward;
+use crate::notation::Square;
+use core::fmt;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+/// Set of squares, one bit per square from a1 (bit 0) to h8 (bit 63).
+///
+/// Converts from and into the raw `u64` masks used by the [`attacks`](crate::attacks) and
+/// [`masks`](crate::masks) modules. Iterating yields the squares from a1 to h8.
+///
+/// ```
+/// use shax::bitboard::Bitboard;
+/// use shax::masks::RANK_1;
+/// use shax::notation::Square;
+///
+/// let mut squares = Bitboard::from(Square::E4) | Square::D5;
+/// assert!(squares.contains(Square::D5));
+/// assert_eq!(squares.len(), 2);
+/// assert_eq!(squares.pop(), Some(Square::E4));
+/// assert_eq!(squares.collect::<Vec<_>>(), [Square::D5]);
+///
+/// let rank = Bitboard(RANK_1);
+/// assert_eq!(rank & !Bitboard::from(Square::A1), Bitboard(RANK_1 & !1));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const FULL: Bitboard = Bitboard(u64::MAX);
+
+    pub const fn contains(self, square: Square) -> bool {
+        self.0 & 1 << square as u64 != 0
+    }
+
+    pub fn insert(&mut self, square: Square) {
+        self.0 |= 1 << square as u64;
+    }
+
+    pub fn remove(&mut self, square: Square) {
+        self.0 &= !(1 << square as u64);
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Number of squares in the set.
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Lowest square of the set, closest to a1, without removing it.
+    pub fn first(self) -> Option<Square> {
+        Square::from_index(bitscan_forward(self.0))
+    }
+
+    /// Removes and returns the lowest square of the set, closest to a1.
+    pub fn pop(&mut self) -> Option<Square> {
+        let square = self.first()?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+impl Iterator for Bitboard {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        self.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Bitboard {}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(squares: I) -> Self {
+        let mut bitboard = Bitboard::EMPTY;
+        for square in squares {
+            bitboard.insert(square);
+        }
+        bitboard
+    }
+}
+
+impl From<u64> for Bitboard {
+    fn from(mask: u64) -> Self {
+        Bitboard(mask)
+    }
+}
+
+impl From<Bitboard> for u64 {
+    fn from(bitboard: Bitboard) -> Self {
+        bitboard.0
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Self {
+        Bitboard(1 << square as u64)
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $method:ident, $assign:ident, $assign_method:ident) => {
+        impl<T: Into<Bitboard>> $op<T> for Bitboard {
+            type Output = Bitboard;
+
+            fn $method(self, rhs: T) -> Bitboard {
+                Bitboard(self.0.$method(rhs.into().0))
+            }
+        }
+
+        impl<T: Into<Bitboard>> $assign<T> for Bitboard {
+            fn $assign_method(&mut self, rhs: T) {
+                self.0.$assign_method(rhs.into().0)
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+/// Prints the set as an 8x8 grid seen from White, rank 8 first, with `X` for the squares in
+/// the set and `.` for the others. Format the inner `u64` for the raw mask.
+///
+/// ```
+/// use shax::bitboard::Bitboard;
+/// use shax::masks::{FILE_A, RANK_8};
+///
+/// let grid = format!("{:?}", Bitboard(FILE_A | RANK_8));
+/// assert_eq!(grid.lines().next(), Some("X X X X X X X X"));
+/// assert_eq!(grid.lines().nth(1), Some("X . . . . . . ."));
+/// assert_eq!(grid.lines().count(), 8);
+/// ```
+impl fmt::Debug for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                let square = Square::new(file, rank).unwrap();
+                let cell = if self.contains(square) { 'X' } else { '.' };
+                if file > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{cell}")?;
+            }
+            if rank > 0 {
+                f.write_str("\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::masks::{FILE_H, LIGHT_SQUARES};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_iterates_every_square_in_order() {
+        let squares: Vec<Square> = Bitboard::FULL.collect();
+        assert_eq!(squares, Square::iter().collect::<Vec<_>>());
+        assert_eq!(squares.into_iter().collect::<Bitboard>(), Bitboard::FULL);
+        assert_eq!(Bitboard(LIGHT_SQUARES).len(), 32);
+        assert_eq!(Bitboard::EMPTY.first(), None);
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut bitboard = Bitboard(FILE_H);
+        bitboard &= !Bitboard::from(Square::H1);
+        bitboard ^= Square::H2;
+        bitboard |= 1;
+        assert_eq!(u64::from(bitboard), FILE_H & !0x8080 | 1);
+
+        bitboard.remove(Square::A1);
+        bitboard.insert(Square::B2);
+        assert!(bitboard.contains(Square::B2));
+        assert!(!bitboard.contains(Square::A1));
+        assert_eq!(bitboard.first(), Some(Square::B2));
+    }
+
+    #[test]
+    fn test_debug_grid() {
+        let bitboard = Bitboard::from(Square::A1) | Square::H8 | Square::E4;
+        assert_eq!(
+            format!("{bitboard:?}"),
+            "\
+. . . . . . . X
+. . . . . . . .
+. . . . . . . .
+. . . . . . . .
+. . . . X . . .
+. . . . . . . .
+. . . . . . . .
+X . . . . . . ."
+        );
+    }
+}
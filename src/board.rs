@@ -1,10 +1,24 @@
 use crate::attacks;
-use crate::bitscan_forward;
+use crate::bitboard::Bitboard;
+use crate::cuckoo;
+use crate::masks::{LIGHT_SQUARES, RANK_2, RANK_7};
+use crate::movelist::MoveList;
 use crate::notation::{
-    CastlingMove, CastlingRights, Color, Move, Piece, PromotionMove, RegularMove, Square, Winner,
+    CastlingMove, CastlingRights, CastlingSide, Color, File, GameStatus, Move, Piece,
+    PromotionMove, PromotionPiece, Rank, RegularMove, Square, Winner,
 };
-use std::error;
-use std::fmt;
+use crate::positions::DEFAULT_BITBOARDS;
+use crate::rays::between;
+use crate::stop::{StopToken, Stopped};
+use crate::zobrist;
+use crate::{bitscan_forward, bitscan_reverse};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{Deref, DerefMut};
 use strum::IntoEnumIterator;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,27 +42,577 @@ impl fmt::Display for MoveError {
 
 impl error::Error for MoveError {}
 
-#[derive(Clone)]
-pub struct Board {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFenError {
+    WrongFieldCount(usize),
+    WrongRankCount(usize),
+    BadRankLength(usize),
+    BadPiece(char),
+    BadActiveColor(String),
+    BadCastling(char),
+    BadEnPassant(String),
+    BadHalfmoveClock(String),
+    BadFullmoveNumber(String),
+}
+
+impl fmt::Display for ParseFenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongFieldCount(n) => write!(f, "expected 4 or 6 fields, got {n}"),
+            Self::WrongRankCount(n) => write!(f, "expected 8 ranks, got {n}"),
+            Self::BadRankLength(rank) => write!(f, "rank {} does not have 8 squares", rank + 1),
+            Self::BadPiece(c) => {
+                write!(f, "expected piece to be one of 'PRNBQKprnbqk', got {c:?}")
+            }
+            Self::BadActiveColor(s) => write!(f, "expected active color 'w' or 'b', got {s:?}"),
+            Self::BadCastling(c) => write!(
+                f,
+                "expected castling to be '-', one of 'KQkq' or a rook file next to a king, got {c:?}"
+            ),
+            Self::BadEnPassant(s) => write!(
+                f,
+                "expected en passant square behind a pawn of the side not to move, got {s:?}"
+            ),
+            Self::BadHalfmoveClock(s) => write!(f, "bad halfmove clock {s:?}"),
+            Self::BadFullmoveNumber(s) => write!(f, "bad fullmove number {s:?}"),
+        }
+    }
+}
+
+impl error::Error for ParseFenError {}
+
+/// Legal moves of a single selected piece, created by [`Board::selection`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Zobrist key of the position the moves are legal in.
+    key: u64,
+    square: Square,
+    piece: Option<(Color, Piece)>,
+    moves: Vec<Move>,
+    destinations: u64,
+    promotions: u64,
+    castling: CastlingRights,
+}
+
+impl Selection {
+    /// Returns the selected square.
+    pub fn square(&self) -> Square {
+        self.square
+    }
+
+    /// Returns the (color, piece) pair on the selected square, if any.
+    pub fn piece(&self) -> Option<(Color, Piece)> {
+        self.piece
+    }
+
+    /// Returns all legal moves of the selected piece.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Returns an iterator over all squares the selected piece can legally move to.
+    pub fn destinations(&self) -> impl Iterator<Item = Square> + '_ {
+        (0..64).filter_map(move |dst| {
+            if (self.destinations & (1 << dst)) != 0 {
+                Square::from_index(dst)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns [`true`] if the selected piece can legally move to the given square.
+    pub fn can_move_to(&self, dst: Square) -> bool {
+        self.destinations & (1 << dst as usize) != 0
+    }
+
+    /// Returns [`true`] if moving to the given square requires choosing a promotion piece.
+    pub fn is_promotion(&self, dst: Square) -> bool {
+        self.promotions & (1 << dst as usize) != 0
+    }
+
+    /// Returns castling rights that the selected king can use right now.
+    pub fn castling(&self) -> CastlingRights {
+        self.castling
+    }
+
+    /// Returns [`true`] if the selection describes the position, and [`false`] once a move has
+    /// been made since it was created.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{Move, Square};
+    ///
+    /// let mut board = Board::default();
+    /// let selection = board.selection(Square::G1).clone();
+    /// assert!(selection.is_current(&board));
+    /// board.make_move(Move::from_lan("g1f3").unwrap()).unwrap();
+    /// assert!(!selection.is_current(&board));
+    /// ```
+    pub fn is_current(&self, board: &Position) -> bool {
+        self.key == board.zobrist()
+    }
+}
+
+/// Which draws end the game automatically.
+///
+/// ```
+/// use shax::board::{Board, RulesProfile};
+/// use shax::notation::{GameStatus, Move};
+///
+/// let mut fide = Board::default();
+/// let mut practical = Board::default().with_rules(RulesProfile::Practical);
+/// for mov in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"] {
+///     fide.make_move(Move::from_lan(mov).unwrap()).unwrap();
+///     practical.make_move(Move::from_lan(mov).unwrap()).unwrap();
+/// }
+/// assert_eq!(fide.status(), GameStatus::Ongoing);
+/// assert!(fide.can_claim_draw());
+/// assert_eq!(practical.status(), GameStatus::ThreefoldRepetition);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RulesProfile {
+    /// The FIDE Laws as an arbiter applies them: fivefold repetition and the 75-move rule end
+    /// the game, while threefold repetition and the 50-move rule only allow a player to claim
+    /// a draw, see [`Board::can_claim_draw`]. Dead positions end the game under both
+    /// profiles, as the Laws leave nothing to claim there.
+    #[default]
+    StrictFide,
+
+    /// Draws that could be claimed are applied at once, as most online servers and engine
+    /// matches do: threefold repetition and the 50-move rule end the game.
+    Practical,
+}
+
+/// A chess position: piece placement, side to move, castling and en passant rights and the
+/// move counters.
+///
+/// Positions are [`Copy`], so a search can keep one per ply instead of taking moves back. A
+/// [`Board`] adds the history of the game, which the repetition rules need, and gives access
+/// to every method of its position through [`Deref`].
+#[derive(Clone, Copy)]
+pub struct Position {
     bitboards: [[u64; 6]; 2],
-    en_passant: Option<u64>,
+    en_passant: Option<Square>,
     active: Color,
-    winner: Option<Winner>,
+    status: GameStatus,
     castling: CastlingRights,
 
-    /// History for detecting fivefold repetition (FIDE 9.6.1).
+    /// Counter for the 75-move rule (FIDE 9.6.2).
+    ///
+    /// The game is drawn when this reaches 150 (75 moves by each player). According to
+    /// [FIDE rules](https://handbook.fide.com/chapter/E012023), checkmate takes precedence
+    /// if achieved on the move that would otherwise trigger this draw condition.
+    halfclock: u16,
+
+    /// Number of the full move, starting at 1 and incremented after each move of Black.
+    fullmove: u16,
+
+    /// Zobrist key of the position, see [`Position::zobrist`].
+    zobrist: u64,
+
+    /// Starting squares of the castling rooks, indexed by [`Color`] and then kingside (0) or
+    /// queenside (1). They are the corners unless the game is Chess960.
+    castling_rooks: [[Square; 2]; 2],
+    chess960: bool,
+    rules: RulesProfile,
+
+    /// Move that led to the position, see [`Position::last_move`].
+    last_move: Option<Move>,
+}
+
+/// Positions are equal when they have the same pieces on the same squares, the same side to
+/// move, the same castling rights and the same en passant capture available, as in the
+/// repetition rules (FIDE 9.2.2). Move counters, the last move and the rules profile are
+/// ignored.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::notation::Move;
+///
+/// let mut board = Board::default();
+/// for mov in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+///     board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+/// }
+/// assert_eq!(board.position(), Board::default().position());
+///
+/// // Without a black pawn to capture it, the en passant square makes no difference
+/// let after = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+/// let before = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+/// assert_eq!(after.position(), before.position());
+/// ```
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.bitboards == other.bitboards
+            && self.active == other.active
+            && self.castling == other.castling
+            && self.held_castling_rooks() == other.held_castling_rooks()
+            && self.capturable_en_passant() == other.capturable_en_passant()
+    }
+}
+
+impl Eq for Position {}
+
+/// Hashes the Zobrist key, which equal positions share.
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.zobrist);
+    }
+}
+
+/// A game in progress: the current [`Position`] with the history of earlier positions and
+/// the moves played.
+pub struct Board {
+    position: Position,
+
+    /// Zobrist keys of earlier positions for detecting fivefold repetition (FIDE 9.6.1).
     ///
     /// Per [FIDE rules](https://handbook.fide.com/chapter/E012023), the game is drawn
     /// if the same position occurs five times. The history is cleared after pawn moves
     /// and captures because these moves make it impossible to return to previous positions.
-    history: Vec<[[u64; 6]; 2]>,
+    history: Vec<u64>,
 
-    /// Counter for the 75-move rule (FIDE 9.6.2).
+    /// Moves made with [`Board::make_move`] since the position was created.
+    played: Vec<Move>,
+
+    /// Last selection returned by [`Board::selection`], cleared by [`Board::make_move`].
+    selected: Option<Selection>,
+}
+
+impl Clone for Board {
+    fn clone(&self) -> Self {
+        Board {
+            position: self.position,
+            history: self.history.clone(),
+            played: self.played.clone(),
+            selected: None,
+        }
+    }
+
+    /// Copies `source` into the board, reusing the memory of the board's history, so that
+    /// two boards can be copied back and forth without allocating once both have grown.
+    fn clone_from(&mut self, source: &Self) {
+        self.position = source.position;
+        self.history.clone_from(&source.history);
+        self.played.clone_from(&source.played);
+        self.selected = None;
+    }
+}
+
+impl Deref for Board {
+    type Target = Position;
+
+    fn deref(&self) -> &Position {
+        &self.position
+    }
+}
+
+impl DerefMut for Board {
+    fn deref_mut(&mut self) -> &mut Position {
+        &mut self.position
+    }
+}
+
+/// Boards are equal when their current positions are, whatever the moves that led there, so
+/// that they can key position caches and opening explorers.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.position.hash(state);
+    }
+}
+
+/// Starts a game from the position, with an empty history.
+impl From<Position> for Board {
+    fn from(position: Position) -> Self {
+        Board {
+            position,
+            history: Vec::new(),
+            played: Vec::new(),
+            selected: None,
+        }
+    }
+}
+
+/// Options for the text diagram of a board, see [`Position::display`].
+///
+/// The alternate [`Debug`](fmt::Debug) output `{board:#?}` uses the default options, and
+/// `{board:+#?}` adds coordinates and the FEN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Labels ranks on the left and files below the board.
+    pub coordinates: bool,
+
+    /// Appends the FEN of the position on a line after the board.
+    pub fen: bool,
+
+    /// Draws pieces with Unicode chess symbols instead of FEN letters.
+    pub unicode: bool,
+
+    /// Side the board is viewed from, which is drawn at the bottom.
+    pub perspective: Color,
+
+    /// Colors the background of highlighted squares with ANSI escape codes for terminals,
+    /// instead of marking them with a character after the piece.
+    pub ansi: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions {
+            coordinates: false,
+            fen: false,
+            unicode: false,
+            perspective: Color::White,
+            ansi: false,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Returns the column and row at which the square is drawn, counted from the top left
+    /// corner of the board as seen from the [`perspective`](DisplayOptions::perspective).
+    /// Renderers use it to place highlights and arrows.
     ///
-    /// The game is drawn when this reaches 150 (75 moves by each player). According to
-    /// [FIDE rules](https://handbook.fide.com/chapter/E012023), checkmate takes precedence
-    /// if achieved on the move that would otherwise trigger this draw condition.
-    halfclock: usize,
+    /// ```
+    /// use shax::board::DisplayOptions;
+    /// use shax::notation::{Color, Square};
+    ///
+    /// let options = DisplayOptions::default();
+    /// assert_eq!(options.cell(Square::A8), (0, 0));
+    /// assert_eq!(options.cell(Square::E2), (4, 6));
+    ///
+    /// let flipped = DisplayOptions { perspective: Color::Black, ..options };
+    /// assert_eq!(flipped.cell(Square::E2), (3, 1));
+    /// ```
+    pub fn cell(&self, square: Square) -> (usize, usize) {
+        match self.perspective {
+            Color::White => (square.file() as usize, 7 - square.rank() as usize),
+            Color::Black => (7 - square.file() as usize, square.rank() as usize),
+        }
+    }
+}
+
+/// Squares to emphasize on a diagram of the board, see [`BoardDisplay::with_highlights`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Highlights {
+    /// Source and destination squares of the move that led to the position.
+    pub last_move: Option<(Square, Square)>,
+
+    /// Square of a king in check.
+    pub check: Option<Square>,
+
+    /// Any other squares to mark, as a bitboard.
+    pub marked: u64,
+
+    /// Arrows from one square to another, such as suggested moves. Text diagrams cannot draw
+    /// them and leave them to the renderers of GUIs.
+    pub arrows: Vec<(Square, Square)>,
+}
+
+impl Highlights {
+    /// Returns the highlights GUIs usually show: the last move, and the king of the side to
+    /// move if it is in check.
+    ///
+    /// ```
+    /// use shax::board::{Board, Highlights};
+    /// use shax::notation::{Move, Square};
+    ///
+    /// let mut board = Board::default();
+    /// for mov in ["e2e4", "f7f6", "d1h5"] {
+    ///     board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+    /// }
+    /// let highlights = Highlights::of(&board);
+    /// assert_eq!(highlights.last_move, Some((Square::D1, Square::H5)));
+    /// assert_eq!(highlights.check, Some(Square::E8));
+    /// ```
+    pub fn of(position: &Position) -> Self {
+        let color = position.active();
+        let check = if position.is_check(color) {
+            Square::from_index(bitscan_forward(position.bitboard(color, Piece::King)))
+        } else {
+            None
+        };
+        Highlights {
+            last_move: position.last_move().map(endpoints),
+            check,
+            ..Highlights::default()
+        }
+    }
+
+    fn at(&self, square: Square) -> Option<Highlight> {
+        if self.check == Some(square) {
+            Some(Highlight::Check)
+        } else if self
+            .last_move
+            .is_some_and(|(src, dst)| src == square || dst == square)
+        {
+            Some(Highlight::LastMove)
+        } else if self.marked & (1 << square as usize) != 0 {
+            Some(Highlight::Marked)
+        } else {
+            None
+        }
+    }
+}
+
+/// Kind of highlight drawn on a square, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Highlight {
+    Check,
+    LastMove,
+    Marked,
+}
+
+impl Highlight {
+    /// Character written after the piece in plain text diagrams.
+    fn marker(self) -> char {
+        match self {
+            Highlight::Check => '!',
+            Highlight::LastMove => '*',
+            Highlight::Marked => '+',
+        }
+    }
+
+    /// ANSI escape code for the background color of the square.
+    fn ansi(self) -> &'static str {
+        match self {
+            Highlight::Check => "\x1b[41m",
+            Highlight::LastMove => "\x1b[43m",
+            Highlight::Marked => "\x1b[44m",
+        }
+    }
+}
+
+/// Text diagram of a board, created by [`Position::display`].
+pub struct BoardDisplay<'a> {
+    board: &'a Position,
+    options: DisplayOptions,
+    highlights: Highlights,
+}
+
+impl BoardDisplay<'_> {
+    /// Draws the diagram with the given squares highlighted. In plain text a highlighted
+    /// square is followed by `!` for a king in check, `*` for the last move and `+` for
+    /// marked squares instead of a space; with [`DisplayOptions::ansi`] its background is
+    /// colored instead.
+    ///
+    /// ```
+    /// use shax::board::{Board, DisplayOptions, Highlights};
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+    /// let text = board
+    ///     .display(DisplayOptions::default())
+    ///     .with_highlights(Highlights::of(&board))
+    ///     .to_string();
+    /// let lines: Vec<&str> = text.lines().collect();
+    /// assert_eq!(lines[4], ". . . . P*. . . ");
+    /// assert_eq!(lines[6], "P P P P .*P P P ");
+    /// ```
+    pub fn with_highlights(mut self, highlights: Highlights) -> Self {
+        self.highlights = highlights;
+        self
+    }
+}
+
+impl fmt::Display for BoardDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars = if self.options.unicode {
+            ['♙', '♖', '♘', '♗', '♕', '♔', '♟', '♜', '♞', '♝', '♛', '♚']
+        } else {
+            ['P', 'R', 'N', 'B', 'Q', 'K', 'p', 'r', 'n', 'b', 'q', 'k']
+        };
+        let (ranks, files): (Vec<usize>, Vec<usize>) = match self.options.perspective {
+            Color::White => ((0..8).rev().collect(), (0..8).collect()),
+            Color::Black => ((0..8).collect(), (0..8).rev().collect()),
+        };
+
+        let mut ascii = String::new();
+        for (row, &rank) in ranks.iter().enumerate() {
+            if self.options.coordinates {
+                ascii.push((b'1' + rank as u8) as char);
+                ascii.push(' ');
+            }
+            for &file in &files {
+                let square = Square::new(file, rank).unwrap();
+                let char = match self.board.on_square(square) {
+                    Some((color, piece)) => chars[piece as usize + (color as usize * 6)],
+                    None => '.',
+                };
+                match self.highlights.at(square) {
+                    Some(highlight) if self.options.ansi => {
+                        ascii.push_str(highlight.ansi());
+                        ascii.push(char);
+                        ascii.push_str("\x1b[0m ");
+                    }
+                    Some(highlight) => {
+                        ascii.push(char);
+                        ascii.push(highlight.marker());
+                    }
+                    None => {
+                        ascii.push(char);
+                        ascii.push(' ');
+                    }
+                }
+            }
+            if row != 7 {
+                ascii.push('\n');
+            }
+        }
+        if self.options.coordinates {
+            ascii.push_str("\n  ");
+            for &file in &files {
+                ascii.push((b'a' + file as u8) as char);
+                ascii.push(' ');
+            }
+        }
+        if self.options.fen {
+            ascii.push('\n');
+            ascii.push_str(&self.board.to_fen());
+        }
+        f.write_str(&ascii)
+    }
+}
+
+impl fmt::Display for Board {
+    /// Draws the board from White's side with coordinates. Use [`Position::display`] for other
+    /// options.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(
+    ///     board.to_string().lines().collect::<Vec<&str>>(),
+    ///     [
+    ///         "8 r n b q k b n r ",
+    ///         "7 p p p p p p p p ",
+    ///         "6 . . . . . . . . ",
+    ///         "5 . . . . . . . . ",
+    ///         "4 . . . . . . . . ",
+    ///         "3 . . . . . . . . ",
+    ///         "2 P P P P P P P P ",
+    ///         "1 R N B Q K B N R ",
+    ///         "  a b c d e f g h ",
+    ///     ]
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = DisplayOptions {
+            coordinates: true,
+            ..DisplayOptions::default()
+        };
+        write!(f, "{}", self.display(options))
+    }
 }
 
 impl fmt::Debug for Board {
@@ -72,80 +636,748 @@ impl fmt::Debug for Board {
     ///     ]
     /// );
     /// ```
+    ///
+    /// The `+` flag adds coordinates and the FEN, which makes the output self-contained:
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let board = Board::default();
+    /// let lines: Vec<String> = format!("{board:+#?}").lines().map(String::from).collect();
+    /// assert_eq!(lines[0], "8 r n b q k b n r ");
+    /// assert_eq!(lines[8], "  a b c d e f g h ");
+    /// assert_eq!(lines[9], "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// ```
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
-            let chars = ['P', 'R', 'N', 'B', 'Q', 'K', 'p', 'r', 'n', 'b', 'q', 'k'];
-            let mut ascii = String::new();
-            for rank in (0..8).rev() {
-                for file in 0..8 {
-                    let square = Square::from_repr(rank * 8 + file).unwrap();
-                    let char = match self.on_square(square) {
-                        Some((color, piece)) => chars[piece as usize + (color as usize * 6)],
-                        None => '.',
-                    };
-                    ascii.push(char);
-                    ascii.push(' ');
-                }
-                if rank != 0 {
-                    ascii.push('\n');
-                }
-            }
-            f.write_str(&ascii)?;
+            fmt::Debug::fmt(&self.position, f)?;
         } else {
             f.debug_struct("Board")
                 .field("active", &self.active())
-                .field("winner", &self.winner())
+                .field("status", &self.status())
                 .field("en_passant", &self.en_passant())
                 .field("castling", &self.castling())
                 .field("halfclock", &self.halfclock)
+                .field("repetitions", &self.repetition_count())
                 .finish_non_exhaustive()?;
         }
         Ok(())
     }
 }
 
-const DEFAULT: [[u64; 6]; 2] = [
-    [
-        // White
-        0x000000000000ff00, // Pawn
-        0x0000000000000081, // Rook
-        0x0000000000000042, // Knight
-        0x0000000000000024, // Bishop
-        0x0000000000000008, // Queen
-        0x0000000000000010, // King
-    ],
-    [
-        // Black
-        0x00ff000000000000, // Pawn
-        0x8100000000000000, // Rook
-        0x4200000000000000, // Knight
-        0x2400000000000000, // Bishop
-        0x0800000000000000, // Queen
-        0x1000000000000000, // King
-    ],
-];
+/// Formats the position as [`Board`] does, without the repetition count.
+impl fmt::Debug for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            let options = DisplayOptions {
+                coordinates: f.sign_plus(),
+                fen: f.sign_plus(),
+                ..DisplayOptions::default()
+            };
+            write!(f, "{}", self.display(options))
+        } else {
+            f.debug_struct("Position")
+                .field("active", &self.active)
+                .field("status", &self.status)
+                .field("en_passant", &self.en_passant)
+                .field("castling", &self.castling)
+                .field("halfclock", &self.halfclock)
+                .finish_non_exhaustive()
+        }
+    }
+}
+
+const DEFAULT_CASTLING_ROOKS: [[Square; 2]; 2] =
+    [[Square::H1, Square::A1], [Square::H8, Square::A8]];
 
 impl Default for Board {
     fn default() -> Self {
-        Board {
-            bitboards: DEFAULT,
-            en_passant: None,
-            castling: CastlingRights::all(),
-            active: Color::White,
-            winner: None,
-            history: Vec::with_capacity(150),
-            halfclock: 0,
-        }
+        Board::from_parts(
+            DEFAULT_BITBOARDS,
+            Color::White,
+            CastlingRights::all(),
+            None,
+            0,
+            GameStatus::Ongoing,
+        )
     }
 }
 
 impl Board {
+    /// Builds a board from raw state without any validation. The repetition history is empty.
+    pub(crate) fn from_parts(
+        bitboards: [[u64; 6]; 2],
+        active: Color,
+        castling: CastlingRights,
+        en_passant: Option<Square>,
+        halfclock: u16,
+        status: GameStatus,
+    ) -> Self {
+        let mut board = Board::from(Position {
+            bitboards,
+            en_passant,
+            active,
+            status,
+            castling,
+            halfclock,
+            fullmove: 1,
+            zobrist: 0,
+            castling_rooks: DEFAULT_CASTLING_ROOKS,
+            chess960: false,
+            rules: RulesProfile::default(),
+            last_move: None,
+        });
+        board.zobrist = board.compute_zobrist();
+        board
+    }
+
+    /// Builds a board of standard chess from trusted state, computing the game status.
+    pub(crate) fn from_setup(
+        bitboards: [[u64; 6]; 2],
+        active: Color,
+        castling: CastlingRights,
+        en_passant: Option<Square>,
+        halfclock: u16,
+        fullmove: u16,
+    ) -> Self {
+        let mut board = Board::from_parts(
+            bitboards,
+            active,
+            castling,
+            en_passant,
+            halfclock,
+            GameStatus::Ongoing,
+        );
+        board.fullmove = fullmove;
+        board.update_game_state(active.opposite());
+        board
+    }
+
+    /// Parses a position in Forsyth-Edwards Notation (FEN). The halfmove clock and fullmove
+    /// number fields are optional, as in EPD records.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{Color, Square};
+    ///
+    /// let board =
+    ///     Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2").unwrap();
+    /// assert_eq!(board.active(), Color::White);
+    /// assert_eq!(board.en_passant(), Some(Square::E6));
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Self, ParseFenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(ParseFenError::WrongFieldCount(fields.len()));
+        }
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(ParseFenError::WrongRankCount(ranks.len()));
+        }
+
+        let mut bitboards = [[0; 6]; 2];
+        for (rank, pieces) in ranks.iter().rev().enumerate() {
+            let mut file = 0;
+            for c in pieces.chars() {
+                if let Some(skip) = c.to_digit(10).filter(|skip| (1..=8).contains(skip)) {
+                    file += skip as usize;
+                    continue;
+                }
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                let piece = match c.to_ascii_lowercase() {
+                    'p' => Piece::Pawn,
+                    'r' => Piece::Rook,
+                    'n' => Piece::Knight,
+                    'b' => Piece::Bishop,
+                    'q' => Piece::Queen,
+                    'k' => Piece::King,
+                    _ => return Err(ParseFenError::BadPiece(c)),
+                };
+                if file >= 8 {
+                    return Err(ParseFenError::BadRankLength(rank));
+                }
+                bitboards[color as usize][piece as usize] |= 1 << (rank * 8 + file);
+                file += 1;
+            }
+            if file != 8 {
+                return Err(ParseFenError::BadRankLength(rank));
+            }
+        }
+
+        let active = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(ParseFenError::BadActiveColor(other.to_string())),
+        };
+
+        let mut castling = CastlingRights::empty();
+        let mut castling_rooks = DEFAULT_CASTLING_ROOKS;
+        let mut chess960 = false;
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let (color, kingside, rook) = parse_castling_right(&bitboards, c)?;
+                castling |= if kingside {
+                    color.kingside_castling_flag()
+                } else {
+                    color.queenside_castling_flag()
+                };
+                if let Some(rook) = rook {
+                    let side = if kingside { 0 } else { 1 };
+                    castling_rooks[color as usize][side] = rook;
+                }
+                chess960 |= !"KQkq".contains(c);
+            }
+        }
+        chess960 |= castling_rooks != DEFAULT_CASTLING_ROOKS;
+        for color in [Color::White, Color::Black] {
+            let rights = color.kingside_castling_flag() | color.queenside_castling_flag();
+            let home = 1 << Square::at(File::E, color.back_rank()) as usize;
+            chess960 |= castling.intersects(rights)
+                && bitboards[color as usize][Piece::King as usize] != home;
+        }
+
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => {
+                let bad = || ParseFenError::BadEnPassant(square.to_string());
+                let square: Square = square.parse().map_err(|_| bad())?;
+                if !is_valid_en_passant(&bitboards, active, square) {
+                    return Err(bad());
+                }
+                Some(square)
+            }
+        };
+
+        let halfclock = match fields.get(4) {
+            Some(clock) => clock
+                .parse()
+                .map_err(|_| ParseFenError::BadHalfmoveClock(clock.to_string()))?,
+            None => 0,
+        };
+        let fullmove = match fields.get(5) {
+            Some(number) => number
+                .parse::<u16>()
+                .ok()
+                .filter(|&number| number > 0)
+                .ok_or_else(|| ParseFenError::BadFullmoveNumber(number.to_string()))?,
+            None => 1,
+        };
+
+        let mut board = Board::from_parts(
+            bitboards,
+            active,
+            castling,
+            en_passant,
+            halfclock,
+            GameStatus::Ongoing,
+        );
+        board.castling_rooks = castling_rooks;
+        board.chess960 = chess960;
+        board.fullmove = fullmove;
+        board.recompute_castling_rights();
+        board.update_game_state(active.opposite());
+        Ok(board)
+    }
+
+    /// Returns the Chess960 starting position with the given number from 0 to 959, using the
+    /// standard numbering scheme in which 518 is the regular chess starting position.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let board = Board::chess960(0).unwrap();
+    /// assert_eq!(board.to_diagram_lines()[7], "B B Q N N R K R ");
+    /// assert!(board.is_chess960());
+    ///
+    /// let board = Board::chess960(518).unwrap();
+    /// assert_eq!(format!("{board:#?}"), format!("{:#?}", Board::default()));
+    /// assert!(Board::chess960(960).is_none());
+    /// ```
+    pub fn chess960(number: usize) -> Option<Self> {
+        if number >= 960 {
+            return None;
+        }
+
+        // Knight placements on the five files left after the bishops and the queen
+        const KNIGHTS: [(usize, usize); 10] = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ];
+
+        let mut files = [None; 8];
+        files[number % 4 * 2 + 1] = Some(Piece::Bishop);
+        files[number / 4 % 4 * 2] = Some(Piece::Bishop);
+        let empty = |files: &[Option<Piece>; 8]| -> Vec<usize> {
+            (0..8).filter(|&file| files[file].is_none()).collect()
+        };
+        files[empty(&files)[number / 16 % 6]] = Some(Piece::Queen);
+        let (first, second) = KNIGHTS[number / 96];
+        let free = empty(&files);
+        files[free[first]] = Some(Piece::Knight);
+        files[free[second]] = Some(Piece::Knight);
+        let free = empty(&files);
+        for (file, piece) in free
+            .into_iter()
+            .zip([Piece::Rook, Piece::King, Piece::Rook])
+        {
+            files[file] = Some(piece);
+        }
+
+        let mut bitboards = [[0; 6]; 2];
+        bitboards[Color::White as usize][Piece::Pawn as usize] = RANK_2;
+        bitboards[Color::Black as usize][Piece::Pawn as usize] = RANK_7;
+        for (file, piece) in files.into_iter().enumerate() {
+            let piece = piece.unwrap() as usize;
+            bitboards[Color::White as usize][piece] |= 1 << file;
+            bitboards[Color::Black as usize][piece] |= 1 << (56 + file);
+        }
+
+        let rooks = bitboards[Color::White as usize][Piece::Rook as usize];
+        let (queenside, kingside) = (bitscan_forward(rooks), bitscan_reverse(rooks));
+        let mut board = Board::default();
+        board.bitboards = bitboards;
+        board.castling_rooks = [
+            [kingside, queenside].map(|file| Square::from_index(file).unwrap()),
+            [kingside, queenside].map(|file| Square::from_index(56 + file).unwrap()),
+        ];
+        board.chess960 = true;
+        board.zobrist = board.compute_zobrist();
+        Some(board)
+    }
+
+    /// Returns a [`Selection`] with every legal move of the piece standing on the given square.
+    ///
+    /// The selection is cached until the next [`Board::make_move`], so querying the same
+    /// square again, e.g. on every mouse event while the user is dragging a piece in a GUI,
+    /// does not generate the moves again. A clone kept past a move can be checked with
+    /// [`Selection::is_current`].
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Square;
+    ///
+    /// let mut board = Board::default();
+    /// let selection = board.selection(Square::G1);
+    /// assert!(selection.can_move_to(Square::F3));
+    /// assert!(!selection.can_move_to(Square::E2));
+    /// assert_eq!(selection.destinations().count(), 2);
+    /// ```
+    pub fn selection(&mut self, square: Square) -> &Selection {
+        let key = self.zobrist();
+        let cached = self
+            .selected
+            .as_ref()
+            .is_some_and(|selection| selection.square == square && selection.key == key);
+        if !cached {
+            self.selected = Some(self.position.select(square));
+        }
+        self.selected.as_ref().unwrap()
+    }
+
+    /// Returns the moves made on this board since it was created, oldest first.
+    pub fn played_moves(&self) -> &[Move] {
+        &self.played
+    }
+
+    /// Returns [`true`] if the side to move may claim a draw because the position has occurred
+    /// three times (FIDE 9.2) or 50 moves have passed without a capture or pawn move (FIDE 9.3).
+    ///
+    /// Under [`RulesProfile::Practical`] such draws end the game right away instead.
+    pub fn can_claim_draw(&self) -> bool {
+        !self.status.is_over() && (self.repetition_count() >= 3 || self.halfclock >= 100)
+    }
+
+    /// Returns the board with the given rules profile, updating the game status for it.
+    pub fn with_rules(mut self, rules: RulesProfile) -> Self {
+        self.rules = rules;
+        self.update_game_state(self.active.opposite());
+        self
+    }
+
+    /// Returns how many times the current position has occurred since the last capture or
+    /// pawn move, including the current occurrence.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// for mov in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///     board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+    /// }
+    /// assert_eq!(board.repetition_count(), 2);
+    /// ```
+    pub fn repetition_count(&self) -> usize {
+        // The key includes the side to move, so only every second position can match
+        self.history
+            .iter()
+            .rev()
+            .skip(1)
+            .step_by(2)
+            .filter(|&&key| key == self.zobrist)
+            .count()
+            + 1
+    }
+
+    /// Returns [`true`] if the side to move can repeat an earlier position of the game with a
+    /// single move of a piece other than a pawn, without capturing. Searches use this to score
+    /// positions where a repetition is within reach as draws before reaching it.
+    ///
+    /// Such a move is not checked for legality: it may leave the king in check.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// for mov in ["g1f3", "g8f6", "f3g1"] {
+    ///     assert!(!board.has_game_cycle());
+    ///     board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+    /// }
+    /// // Black can play f6g8 back into the starting position
+    /// assert!(board.has_game_cycle());
+    /// ```
+    pub fn has_game_cycle(&self) -> bool {
+        self.game_cycles(&self.history)
+            .any(|(_, color)| color == self.active)
+    }
+
+    /// Returns the Zobrist keys of the earlier positions since the last capture or pawn move,
+    /// oldest first.
+    #[cfg(feature = "std")]
+    pub(crate) fn history(&self) -> &[u64] {
+        &self.history
+    }
+
+    /// Attempts to execute a move on the board.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self), fields(mov = %mov), err(Debug))
+    )]
+    pub fn make_move(&mut self, mov: Move) -> Result<(), MoveError> {
+        if self.status.is_over() {
+            return Err(MoveError::GameEnded);
+        }
+
+        let mov = self.validate_move(self.active, mov)?;
+        let moved = self.active;
+        let before = self.zobrist;
+        self.do_move(mov);
+        self.played.push(mov);
+
+        // Captures and pawn moves make it impossible to return to previous positions
+        if self.halfclock == 0 {
+            self.history.clear();
+        } else {
+            self.history.push(before);
+        }
+        self.update_game_state(moved);
+        self.selected = None;
+
+        Ok(())
+    }
+
+    fn update_game_state(&mut self, moved_color: Color) {
+        let opposite = moved_color.opposite();
+        let repetitions = self.repetition_count();
+
+        self.status = if self.is_checkmated(opposite) {
+            GameStatus::Checkmate {
+                winner: moved_color,
+            }
+        } else if self.is_stalemated(opposite) {
+            GameStatus::Stalemate
+        } else if repetitions >= 5 {
+            GameStatus::FivefoldRepetition
+        } else if self.halfclock >= 150 {
+            GameStatus::SeventyFiveMoves
+        } else if self.rules == RulesProfile::Practical && repetitions >= 3 {
+            GameStatus::ThreefoldRepetition
+        } else if self.rules == RulesProfile::Practical && self.halfclock >= 100 {
+            GameStatus::FiftyMoves
+        } else if self.is_insufficient_material() {
+            GameStatus::InsufficientMaterial
+        } else if self.is_blocked_position() {
+            GameStatus::DeadPosition
+        } else {
+            GameStatus::Ongoing
+        };
+
+        self.active = opposite;
+    }
+
+    /// Returns the current position, without the history of the game.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl Position {
+    /// Returns the Zobrist key of the position: a 64-bit hash of piece placement, active
+    /// color, castling rights and en passant file. Equal positions have equal keys, so the key
+    /// can be used for transposition tables, repetition detection and position caches.
+    ///
+    /// The en passant file is only hashed when a pawn of the active color can capture.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// for mov in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///     board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+    /// }
+    /// assert_eq!(board.zobrist(), Board::default().zobrist());
+    ///
+    /// board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+    /// assert_ne!(board.zobrist(), Board::default().zobrist());
+    /// ```
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    fn compute_zobrist(&self) -> u64 {
+        let keys = &zobrist::KEYS;
+        let mut key = 0;
+
+        for (color, piece, &bb) in self.flat_enumerate() {
+            let mut bb = bb;
+            while bb != 0 {
+                key ^= keys.pieces[color as usize][piece as usize][bitscan_forward(bb)];
+                bb &= bb - 1;
+            }
+        }
+
+        key ^ self.state_key()
+    }
+
+    /// Returns the part of the Zobrist key that does not come from the pieces: the side to
+    /// move, the castling rights and the en passant file. Moves take it out of the key before
+    /// changing any of them and put it back afterwards, while `add_piece` and `remove_piece`
+    /// update the key for the pieces.
+    fn state_key(&self) -> u64 {
+        let keys = &zobrist::KEYS;
+        let mut key = keys.castling[self.castling.bits() as usize];
+
+        if self.active == Color::Black {
+            key ^= keys.black_to_move;
+        }
+
+        if let Some(en_passant) = self.capturable_en_passant() {
+            key ^= keys.en_passant[en_passant.file() as usize];
+        }
+
+        key
+    }
+
+    /// Finds the earlier positions that differ from this one by a single reversible move, given
+    /// the keys of the positions before this one, oldest first. Yields how many plies back
+    /// each position is, always odd, and the color of the piece making the move.
+    ///
+    /// Only positions where the pieces of the side not to move are back on their squares are
+    /// looked up, as only the side to move can then complete the cycle.
+    pub(crate) fn game_cycles<'a>(
+        &'a self,
+        keys: &'a [u64],
+    ) -> impl Iterator<Item = (usize, Color)> + 'a {
+        let side = zobrist::KEYS.black_to_move;
+        let end = keys.len().min(self.halfclock as usize);
+        let key_back = move |plies: usize| match plies {
+            0 => self.zobrist,
+            _ => keys[keys.len() - plies],
+        };
+        // Combined key changes of the moves of the side not to move, which cancel out once
+        // its pieces are back where they were
+        let mut other = if end >= 3 {
+            key_back(0) ^ key_back(1) ^ side
+        } else {
+            0
+        };
+        (3..=end).step_by(2).filter_map(move |plies| {
+            other ^= key_back(plies - 1) ^ key_back(plies) ^ side;
+            if other != 0 {
+                return None;
+            }
+            let (a, b) = cuckoo::lookup(self.zobrist ^ key_back(plies))?;
+            if between(a, b) & self.occupied_mask() != 0 {
+                return None;
+            }
+            let (color, _) = self.on_square(a).or_else(|| self.on_square(b))?;
+            Some((plies, color))
+        })
+    }
+
+    /// Returns the en passant square if a pawn of the active color can capture on it.
+    fn capturable_en_passant(&self) -> Option<Square> {
+        self.en_passant.filter(|&en_passant| {
+            let capturers = attacks::pawn_attacks(1 << en_passant as usize, self.active.opposite());
+            capturers & self.bitboard(self.active, Piece::Pawn) != 0
+        })
+    }
+
+    /// Returns the starting squares of the castling rooks for the rights still held.
+    fn held_castling_rooks(&self) -> [[Option<Square>; 2]; 2] {
+        [Color::White, Color::Black].map(|color| {
+            let flags = [
+                color.kingside_castling_flag(),
+                color.queenside_castling_flag(),
+            ];
+            let rooks = self.castling_rooks[color as usize];
+            [0, 1].map(|side| self.castling.contains(flags[side]).then_some(rooks[side]))
+        })
+    }
+
+    /// Formats the position in Forsyth-Edwards Notation (FEN). Castling rights are written as
+    /// `KQkq` in standard chess and as rook files (Shredder-FEN) in Chess960.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    /// assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+    ///
+    /// let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+    /// assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+    /// ```
+    pub fn to_fen(&self) -> String {
+        let chars = ['P', 'R', 'N', 'B', 'Q', 'K', 'p', 'r', 'n', 'b', 'q', 'k'];
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let square = Square::from_index(rank * 8 + file).unwrap();
+                match self.on_square(square) {
+                    Some((color, piece)) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(chars[piece as usize + (color as usize * 6)]);
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank != 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push_str(match self.active {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+
+        let rights = [
+            (Color::White, 0, CastlingRights::WHITE_KINGSIDE, 'K'),
+            (Color::White, 1, CastlingRights::WHITE_QUEENSIDE, 'Q'),
+            (Color::Black, 0, CastlingRights::BLACK_KINGSIDE, 'k'),
+            (Color::Black, 1, CastlingRights::BLACK_QUEENSIDE, 'q'),
+        ];
+        for (color, side, flag, char) in rights {
+            if !self.castling.contains(flag) {
+                continue;
+            }
+            if self.chess960 {
+                let file = self.castling_rooks[color as usize][side].file().to_char();
+                fen.push(match color {
+                    Color::White => file.to_ascii_uppercase(),
+                    Color::Black => file,
+                });
+            } else {
+                fen.push(char);
+            }
+        }
+        if self.castling.is_empty() {
+            fen.push('-');
+        }
+
+        match self.en_passant() {
+            Some(square) => fen.push_str(&format!(" {square} ")),
+            None => fen.push_str(" - "),
+        }
+        fen.push_str(&format!("{} {}", self.halfclock, self.fullmove));
+        fen
+    }
+
+    /// Returns a text diagram of the board with the given options.
+    ///
+    /// ```
+    /// use shax::board::{Board, DisplayOptions};
+    /// use shax::notation::Color;
+    ///
+    /// let board = Board::default();
+    /// let options = DisplayOptions {
+    ///     unicode: true,
+    ///     perspective: Color::Black,
+    ///     coordinates: true,
+    ///     ..DisplayOptions::default()
+    /// };
+    /// let text = board.display(options).to_string();
+    /// assert!(text.starts_with("1 ♖ ♘ ♗ ♔ ♕ ♗ ♘ ♖ \n2 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙ \n"));
+    /// assert!(text.ends_with("8 ♜ ♞ ♝ ♚ ♛ ♝ ♞ ♜ \n  h g f e d c b a "));
+    /// ```
+    pub fn display(&self, options: DisplayOptions) -> BoardDisplay<'_> {
+        BoardDisplay {
+            board: self,
+            options,
+            highlights: Highlights::default(),
+        }
+    }
+
+    /// Returns the ranks of the board from the 8th to the 1st, drawn as in the alternate
+    /// [`Debug`](fmt::Debug) output: one FEN letter or `.` per square, each followed by a space.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").unwrap();
+    /// let lines = board.to_diagram_lines();
+    /// assert_eq!(lines[0], ". . . . k . . . ");
+    /// assert_eq!(lines[4], ". . . . P . . . ");
+    /// assert_eq!(lines[7], ". . . . K . . . ");
+    /// ```
+    pub fn to_diagram_lines(&self) -> [String; 8] {
+        let chars = ['P', 'R', 'N', 'B', 'Q', 'K', 'p', 'r', 'n', 'b', 'q', 'k'];
+        core::array::from_fn(|row| {
+            let mut line = String::with_capacity(16);
+            for file in 0..8 {
+                let square = Square::new(file, 7 - row).unwrap();
+                line.push(match self.on_square(square) {
+                    Some((color, piece)) => chars[piece as usize + (color as usize * 6)],
+                    None => '.',
+                });
+                line.push(' ');
+            }
+            line
+        })
+    }
+
+    /// Returns [`true`] if the position comes from a Chess960 game, i.e. a castling rook or
+    /// king did not start on its standard square, or the position was created with
+    /// [`Board::chess960`] or a FEN with Shredder-style castling rights such as `HAha`.
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
     /// Returns the en passant target square, if an en passant capture is possible this turn.
     pub fn en_passant(&self) -> Option<Square> {
-        match self.en_passant {
-            Some(bb) => Square::from_repr(bb.trailing_zeros() as usize),
-            None => None,
-        }
+        self.en_passant
     }
 
     /// Returns the color of the currently active player (whose turn it is).
@@ -155,17 +1387,149 @@ impl Board {
 
     /// Returns the game winner if the game has concluded, or [`None`] if still in progress.
     pub fn winner(&self) -> Option<Winner> {
-        self.winner
+        self.status.winner()
+    }
+
+    /// Returns whether the game is still in progress or how it has concluded.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{GameStatus, Move};
+    ///
+    /// let mut board = Board::default();
+    /// assert_eq!(board.status(), GameStatus::Ongoing);
+    ///
+    /// let board = Board::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert_eq!(board.status(), GameStatus::Stalemate);
+    /// ```
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Returns the move that led to the current position, or [`None`] if no move was made on
+    /// this board since it was created (e.g. loaded from FEN). Castling is returned resolved
+    /// as the king taking its own rook, see [`Move::Castling`].
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// assert_eq!(board.last_move(), None);
+    /// board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+    /// board.make_move(Move::from_lan("c7c5").unwrap()).unwrap();
+    /// assert_eq!(board.last_move(), Some(Move::from_lan("c7c5").unwrap()));
+    /// assert_eq!(board.played_moves().len(), 2);
+    /// ```
+    pub fn last_move(&self) -> Option<Move> {
+        self.last_move
+    }
+
+    /// Returns current castling rights for both players.
+    /// These flags track whether kings/rooks have moved or rooks were captured, not directly
+    /// indicating legal moves. Use [`Position::castling_moves`] to calculate actual castling
+    /// possibilities.
+    pub fn castling(&self) -> CastlingRights {
+        self.castling
+    }
+
+    /// Clears the castling rights that cannot be used anymore because the king is not on its
+    /// back rank or the castling rook is missing, and returns the rights that were removed.
+    ///
+    /// Rights are kept consistent while playing moves, and [`Board::from_fen`] calls this for
+    /// loaded positions, so a FEN claiming an impossible right is accepted with the right
+    /// dropped.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::CastlingRights;
+    ///
+    /// // There is no rook on a1 to castle queenside with
+    /// let board = Board::from_fen("r3k2r/8/8/8/8/8/8/4K2R w KQkq - 0 1").unwrap();
+    /// assert_eq!(
+    ///     board.castling(),
+    ///     CastlingRights::WHITE_KINGSIDE | CastlingRights::BLACK_KINGSIDE
+    ///         | CastlingRights::BLACK_QUEENSIDE
+    /// );
+    /// assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/4K2R w Kkq - 0 1");
+    /// ```
+    pub fn recompute_castling_rights(&mut self) -> CastlingRights {
+        let before = self.castling;
+        for color in [Color::White, Color::Black] {
+            let back_rank = color.back_rank().mask().0;
+            let king = self.bitboard(color, Piece::King) & back_rank;
+            let rooks = self.bitboard(color, Piece::Rook);
+            let flags = [
+                color.kingside_castling_flag(),
+                color.queenside_castling_flag(),
+            ];
+            for (kingside, (flag, rook)) in [true, false]
+                .into_iter()
+                .zip(flags.into_iter().zip(self.castling_rooks[color as usize]))
+            {
+                let usable = king != 0
+                    && (self.chess960
+                        || king == 1 << Square::at(File::E, color.back_rank()) as usize)
+                    && rooks & (1 << rook as usize) != 0
+                    && (rook as usize % 8 > bitscan_forward(king) % 8) == kingside;
+                if !usable {
+                    self.castling.remove(flag);
+                }
+            }
+        }
+        let keys = &zobrist::KEYS;
+        self.zobrist ^=
+            keys.castling[before.bits() as usize] ^ keys.castling[self.castling.bits() as usize];
+        debug_assert_eq!(self.zobrist, self.compute_zobrist());
+        before.difference(self.castling)
+    }
+
+    /// Returns how many more halfmoves can be played without a capture or pawn move before
+    /// a draw can be claimed under the 50-move rule (FIDE 9.3).
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// assert_eq!(board.rule50_ply_remaining(), 100);
+    ///
+    /// board.make_move(Move::from_lan("g1f3").unwrap()).unwrap();
+    /// assert_eq!(board.rule50_ply_remaining(), 99);
+    /// ```
+    pub fn rule50_ply_remaining(&self) -> usize {
+        100usize.saturating_sub(self.halfclock as usize)
+    }
+
+    /// Returns the number of halfmoves since the last capture or pawn move, as written in FEN.
+    pub fn halfmove_clock(&self) -> usize {
+        self.halfclock as usize
+    }
+
+    /// Returns the number of the current full move, as written in FEN: it starts at 1 and is
+    /// incremented after each move of Black.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+    /// assert_eq!(board.fullmove_number(), 1);
+    /// board.make_move(Move::from_lan("e7e5").unwrap()).unwrap();
+    /// assert_eq!(board.fullmove_number(), 2);
+    /// assert_eq!(board.halfmove_clock(), 0);
+    /// ```
+    pub fn fullmove_number(&self) -> usize {
+        self.fullmove as usize
     }
 
-    /// Returns current castling rights for both players.
-    /// These flags track whether kings/rooks have moved, not directly indicating legal moves.
-    /// Use [`Board::castling_moves`] to calculate actual castling possibilities.
-    pub fn castling(&self) -> CastlingRights {
-        self.castling
+    /// Returns the rules profile deciding which draws end the game automatically.
+    pub fn rules(&self) -> RulesProfile {
+        self.rules
     }
 
-    fn bitboard(&self, color: Color, piece: Piece) -> u64 {
+    pub(crate) fn bitboard(&self, color: Color, piece: Piece) -> u64 {
         self.bitboards[color as usize][piece as usize]
     }
 
@@ -188,7 +1552,7 @@ impl Board {
         let bitboard = self.bitboard(color, piece);
         (0..64).filter_map(move |dst| {
             if (bitboard & (1 << dst)) != 0 {
-                Square::from_repr(dst)
+                Square::from_index(dst)
             } else {
                 None
             }
@@ -208,44 +1572,116 @@ impl Board {
             .next()
     }
 
-    /// Attempts to execute a move on the board.
-    pub fn make_move(&mut self, mov: Move) -> Result<(), MoveError> {
-        if self.winner.is_some() {
-            return Err(MoveError::GameEnded);
+    /// Checks that the move is legal for the given color. Returns the move with castling
+    /// notation resolved against the piece standing on the source square, since e.g. `e1g1`
+    /// may also be a rook move, and castling rewritten as the king taking its own rook.
+    pub(crate) fn validate_move(&self, color: Color, mov: Move) -> Result<Move, MoveError> {
+        self.validate_move_with(&self.legality(color), color, mov)
+    }
+
+    /// Returns the moves that are legal for the side to move, in their original order and
+    /// notation. The checks and pins of the position are computed once for all of them,
+    /// which makes this cheaper than validating the moves one by one, e.g. for a queue of
+    /// premoves sent by a client. Nothing is legal once the game is over.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// // The knight on e4 is pinned, a1 is empty and the rook on e7 is Black's
+    /// let board = Board::from_fen("4k3/4r3/8/8/4N3/8/8/4K3 w - - 0 1").unwrap();
+    /// let candidates = ["e4f6", "e1d2", "e1e3", "a1a2", "e7e6"]
+    ///     .map(|lan| Move::from_lan(lan).unwrap());
+    /// let legal: Vec<String> = board
+    ///     .filter_legal(candidates)
+    ///     .iter()
+    ///     .map(Move::to_lan)
+    ///     .collect();
+    /// assert_eq!(legal, ["e1d2"]);
+    /// ```
+    pub fn filter_legal(&self, moves: impl IntoIterator<Item = Move>) -> Vec<Move> {
+        if self.status.is_over() {
+            return Vec::new();
+        }
+        let legality = self.legality(self.active);
+        moves
+            .into_iter()
+            .filter(|&mov| self.validate_move_with(&legality, self.active, mov).is_ok())
+            .collect()
+    }
+
+    fn validate_move_with(
+        &self,
+        legality: &Legality,
+        color: Color,
+        mov: Move,
+    ) -> Result<Move, MoveError> {
+        let (src, dst) = endpoints(mov);
+
+        let (owner, piece) = self.on_square(src).ok_or(MoveError::NothingToMove)?;
+        if owner != color {
+            return Err(MoveError::IllegalMove);
         }
 
+        let promotes = piece == Piece::Pawn && dst.rank() == color.promotion_rank();
         match mov {
-            Move::Regular(regular) => {
-                let (_, piece) = self
-                    .on_square(regular.src)
-                    .ok_or(MoveError::NothingToMove)?;
-
-                self.validate_move(self.active, piece, regular.src, regular.dst)?;
-                self.execute_regular_move(self.active, piece, regular);
+            Move::Regular(_) | Move::Castling(_) if piece == Piece::King => {
+                match self.castling_notation(color, src, dst) {
+                    Some(castling) if self.castling_moves(color, src).any(|m| m == castling) => {
+                        Ok(castling)
+                    }
+                    Some(_) => Err(MoveError::IllegalMove),
+                    None => {
+                        self.validate_destination(legality, color, piece, src, dst)?;
+                        Ok(Move::Regular(RegularMove { src, dst }))
+                    }
+                }
             }
-            Move::Promotion(promotion) => {
-                self.validate_move(self.active, Piece::Pawn, promotion.src, promotion.dst)?;
-                self.execute_promotion_move(self.active, promotion);
+            Move::Promotion(_) if !promotes => Err(MoveError::IllegalMove),
+            Move::Regular(_) | Move::Castling(_) if promotes => Err(MoveError::IllegalMove),
+            Move::Promotion(_) => {
+                self.validate_destination(legality, color, piece, src, dst)?;
+                Ok(mov)
             }
-            Move::Castling(castling) => {
-                if self
-                    .castling_moves(self.active, castling.src)
-                    .any(|mov| mov == Move::Castling(CastlingMove { ..castling }))
-                {
-                    self.execute_castling_move(self.active, castling);
-                } else {
-                    return Err(MoveError::IllegalMove);
-                }
+            Move::Regular(_) | Move::Castling(_) => {
+                self.validate_destination(legality, color, piece, src, dst)?;
+                Ok(Move::Regular(RegularMove { src, dst }))
             }
         }
+    }
 
-        self.update_game_state(self.active);
+    /// Interprets a king move as castling if it lands on a castling rook of its own color or
+    /// moves two or more files towards it, which a regular king move never does.
+    fn castling_notation(&self, color: Color, src: Square, dst: Square) -> Option<Move> {
+        if src.rank() != color.back_rank() || dst.rank() != color.back_rank() {
+            return None;
+        }
 
-        Ok(())
+        let kingside = dst.file() > src.file();
+        let (flag, rook) = if kingside {
+            (
+                color.kingside_castling_flag(),
+                self.castling_rooks[color as usize][0],
+            )
+        } else {
+            (
+                color.queenside_castling_flag(),
+                self.castling_rooks[color as usize][1],
+            )
+        };
+        let king_dst = if kingside { File::G } else { File::C };
+
+        // Without the rook the king may be capturing an enemy piece on its square
+        let has_rook = self.bitboard(color, Piece::Rook) & (1 << rook as usize) != 0;
+        let castles = dst == rook
+            || ((src.file() as u8).abs_diff(dst.file() as u8) >= 2 && dst.file() == king_dst);
+        (castles && has_rook && self.castling.contains(flag))
+            .then_some(Move::Castling(CastlingMove { src, dst: rook }))
     }
 
-    fn validate_move(
+    fn validate_destination(
         &self,
+        legality: &Legality,
         color: Color,
         piece: Piece,
         src: Square,
@@ -255,96 +1691,245 @@ impl Board {
             return Err(MoveError::IllegalMove);
         }
 
-        if self.is_move_pinned(color, piece, src, dst) {
+        if !self.is_legal(legality, color, piece, src, dst) {
             return Err(MoveError::PinnedMove);
         }
 
         Ok(())
     }
 
-    fn execute_regular_move(&mut self, color: Color, piece: Piece, mov: RegularMove) {
-        if let Some((blocker_color, blocker_piece)) = self.on_square(mov.dst) {
-            self.reset_draw_conditions();
-            self.remove_piece(blocker_color, blocker_piece, mov.dst);
-        } else if Piece::Pawn == piece {
-            self.reset_draw_conditions();
+    /// Plays a legal move for the active color and returns the information needed to take
+    /// it back with [`Position::undo_move`]. Only the position is updated: the repetition history
+    /// and the game status are left untouched, and nothing is allocated.
+    ///
+    /// The move is not validated, so it must come from [`Position::generate_moves_into`] or
+    /// similar; use [`Board::make_move`] for moves from other sources.
+    ///
+    /// ```
+    /// use shax::{board::Board, movelist::MoveList};
+    ///
+    /// let mut board = Board::default();
+    /// let mut moves = MoveList::new();
+    /// board.generate_moves_into(&mut moves);
+    /// let undo = board.do_move(moves[0]);
+    /// assert_ne!(board.to_fen(), Board::default().to_fen());
+    /// board.undo_move(undo);
+    /// assert_eq!(board.to_fen(), Board::default().to_fen());
+    /// ```
+    pub fn do_move(&mut self, mov: Move) -> Undo {
+        let color = self.active;
+        let (src, dst) = endpoints(mov);
+        let piece = match mov {
+            Move::Castling(_) => Piece::King,
+            _ => self.on_square(src).map_or(Piece::Pawn, |(_, piece)| piece),
+        };
+
+        let mut undo = Undo {
+            mov,
+            piece,
+            captured: None,
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfclock: self.halfclock,
+            fullmove: self.fullmove,
+            zobrist: self.zobrist,
+            last_move: self.last_move,
+        };
+        self.zobrist ^= self.state_key();
+
+        match mov {
+            Move::Regular(_) | Move::Promotion(_) => {
+                undo.captured = self.captured_piece(color, piece, dst);
+                if let Some((captured, square)) = undo.captured {
+                    self.remove_piece(color.opposite(), captured, square);
+                    if captured == Piece::Rook {
+                        self.handle_rook_removal(color.opposite(), square);
+                    }
+                }
+
+                if let Move::Promotion(promotion) = mov {
+                    self.remove_piece(color, Piece::Pawn, src);
+                    self.add_piece(color, promotion.piece.into(), dst);
+                } else {
+                    self.force_move(color, piece, src, dst);
+                }
+
+                self.update_castling(color, piece, src);
+                self.update_en_passant(piece, src, dst);
+            }
+            Move::Castling(castling) => {
+                // The king and rook may swap squares or stay in place in Chess960
+                let (king_dst, rook_dst) = castling_destinations(castling);
+                self.remove_piece(color, Piece::King, src);
+                self.remove_piece(color, Piece::Rook, dst);
+                self.add_piece(color, Piece::King, king_dst);
+                self.add_piece(color, Piece::Rook, rook_dst);
+                self.handle_king_move(color);
+                self.en_passant = None;
+            }
+        }
+
+        if piece == Piece::Pawn || undo.captured.is_some() {
+            self.halfclock = 0;
         } else {
-            self.update_draw_conditions();
+            self.halfclock = self.halfclock.saturating_add(1);
+        }
+        if color == Color::Black {
+            self.fullmove = self.fullmove.saturating_add(1);
         }
 
-        self.force_move(color, piece, mov.src, mov.dst);
-        self.update_castling(color, piece, mov.src);
-        self.update_en_passant(color, piece, mov.src, mov.dst);
+        self.active = color.opposite();
+        self.zobrist ^= self.state_key();
+        debug_assert_eq!(self.zobrist, self.compute_zobrist(), "{mov:?}");
+        self.last_move = Some(mov);
+
+        undo
     }
 
-    fn execute_promotion_move(&mut self, color: Color, mov: PromotionMove) {
-        self.reset_draw_conditions();
+    /// Takes back the last move made with [`Position::do_move`].
+    pub fn undo_move(&mut self, undo: Undo) {
+        let color = self.active.opposite();
+        let (src, dst) = endpoints(undo.mov);
+
+        match undo.mov {
+            Move::Regular(_) => self.force_move(color, undo.piece, dst, src),
+            Move::Promotion(promotion) => {
+                self.remove_piece(color, promotion.piece.into(), dst);
+                self.add_piece(color, Piece::Pawn, src);
+            }
+            Move::Castling(castling) => {
+                let (king_dst, rook_dst) = castling_destinations(castling);
+                self.remove_piece(color, Piece::King, king_dst);
+                self.remove_piece(color, Piece::Rook, rook_dst);
+                self.add_piece(color, Piece::King, src);
+                self.add_piece(color, Piece::Rook, dst);
+            }
+        }
 
-        if let Some((blocker_color, blocker_piece)) = self.on_square(mov.dst) {
-            self.remove_piece(blocker_color, blocker_piece, mov.dst);
+        if let Some((captured, square)) = undo.captured {
+            self.add_piece(color.opposite(), captured, square);
         }
 
-        self.remove_piece(color, Piece::Pawn, mov.src);
-        self.add_piece(color, mov.piece.into(), mov.dst);
+        self.active = color;
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfclock = undo.halfclock;
+        self.fullmove = undo.fullmove;
+        self.zobrist = undo.zobrist;
+        self.last_move = undo.last_move;
     }
 
-    fn execute_castling_move(&mut self, color: Color, mov: CastlingMove) {
-        self.update_draw_conditions();
-        match (mov.src, mov.dst) {
-            (Square::E1, Square::G1) => {
-                self.force_move(color, Piece::Rook, Square::H1, Square::F1);
-                self.force_move(color, Piece::King, Square::E1, Square::G1);
-            }
-            (Square::E8, Square::G8) => {
-                self.force_move(color, Piece::Rook, Square::H8, Square::F8);
-                self.force_move(color, Piece::King, Square::E8, Square::G8);
-            }
-            (Square::E1, Square::C1) => {
-                self.force_move(color, Piece::Rook, Square::A1, Square::D1);
-                self.force_move(color, Piece::King, Square::E1, Square::C1);
+    /// Passes the turn to the opponent without moving, as in null-move pruning, and returns
+    /// the information needed to take it back with [`Position::undo_null_move`]. The en
+    /// passant square is cleared and the clocks advance as after a quiet move. Like
+    /// [`Position::do_move`], only the position is updated.
+    ///
+    /// Returns [`None`] without changing anything if the side to move is in check, since the
+    /// opponent could then capture the king.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{Color, Move};
+    ///
+    /// let mut board = Board::default();
+    /// board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+    /// let undo = board.make_null_move().unwrap();
+    /// assert_eq!(board.active(), Color::White);
+    /// assert_eq!(board.en_passant(), None);
+    /// board.undo_null_move(undo);
+    /// assert_eq!(board.active(), Color::Black);
+    ///
+    /// let mut check = Board::from_fen("4k3/8/8/8/8/8/8/4K2r w - - 0 1").unwrap();
+    /// assert_eq!(check.make_null_move(), None);
+    /// ```
+    pub fn make_null_move(&mut self) -> Option<NullUndo> {
+        if self.is_check(self.active) {
+            return None;
+        }
+
+        let undo = NullUndo {
+            en_passant: self.en_passant,
+            halfclock: self.halfclock,
+            fullmove: self.fullmove,
+            zobrist: self.zobrist,
+            last_move: self.last_move,
+        };
+
+        self.zobrist ^= self.state_key();
+        self.en_passant = None;
+        self.halfclock = self.halfclock.saturating_add(1);
+        if self.active == Color::Black {
+            self.fullmove = self.fullmove.saturating_add(1);
+        }
+        self.active = self.active.opposite();
+        self.zobrist ^= self.state_key();
+        debug_assert_eq!(self.zobrist, self.compute_zobrist());
+        self.last_move = None;
+
+        Some(undo)
+    }
+
+    /// Takes back a null move made with [`Position::make_null_move`].
+    pub fn undo_null_move(&mut self, undo: NullUndo) {
+        self.active = self.active.opposite();
+        self.en_passant = undo.en_passant;
+        self.halfclock = undo.halfclock;
+        self.fullmove = undo.fullmove;
+        self.zobrist = undo.zobrist;
+        self.last_move = undo.last_move;
+    }
+
+    /// Returns the piece of the opponent that a move of the side to move captures, including
+    /// the pawn captured en passant. Castling never captures.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{Move, Piece};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K2r w - d6 0 1").unwrap();
+    /// assert_eq!(board.captured_by(Move::from_lan("e5d6").unwrap()), Some(Piece::Pawn));
+    /// assert_eq!(board.captured_by(Move::from_lan("e1f1").unwrap()), None);
+    /// assert_eq!(board.captured_by(Move::from_lan("e5e6").unwrap()), None);
+    /// ```
+    pub fn captured_by(&self, mov: Move) -> Option<Piece> {
+        if let Move::Castling(_) = mov {
+            return None;
+        }
+        let (src, dst) = endpoints(mov);
+        let (color, piece) = self.on_square(src)?;
+        match self.captured_piece(color, piece, dst) {
+            Some((captured, _)) if self.on_square(dst).is_none_or(|(owner, _)| owner != color) => {
+                Some(captured)
             }
-            (Square::E8, Square::C8) => {
-                self.force_move(color, Piece::Rook, Square::A8, Square::D8);
-                self.force_move(color, Piece::King, Square::E8, Square::C8);
+            _ => None,
+        }
+    }
+
+    /// Returns the piece captured by moving the given piece to the destination square,
+    /// together with the square it is captured on (which differs for en passant).
+    fn captured_piece(&self, color: Color, piece: Piece, dst: Square) -> Option<(Piece, Square)> {
+        match self.on_square(dst) {
+            Some((_, captured)) => Some((captured, dst)),
+            None if piece == Piece::Pawn && self.en_passant == Some(dst) => {
+                Some((Piece::Pawn, en_passant_victim(color, dst)))
             }
-            _ => unreachable!(),
+            None => None,
         }
     }
 
-    fn update_en_passant(&mut self, color: Color, piece: Piece, src: Square, dst: Square) {
+    fn update_en_passant(&mut self, piece: Piece, src: Square, dst: Square) {
         let (src, dst) = (src as usize, dst as usize);
         self.en_passant = if piece == Piece::Pawn && src.abs_diff(dst) == 16 {
-            Some(match color {
-                Color::White => (1 << src) << 8,
-                Color::Black => (1 << src) >> 8,
-            })
+            Square::from_index((src + dst) / 2)
         } else {
             None
         }
     }
 
-    fn update_game_state(&mut self, moved_color: Color) {
-        let opposite = moved_color.opposite();
-
-        if !self.color_has_moves(opposite) {
-            if self.is_king_attacked(opposite) {
-                self.winner = Some(moved_color.into())
-            } else {
-                self.winner = Some(Winner::Draw)
-            }
-        } else if self.is_fivefold_repetition() || self.halfclock >= 150 {
-            self.winner = Some(Winner::Draw)
-        } else {
-            self.winner = None
-        }
-
-        self.active = opposite;
-    }
-
     fn update_castling(&mut self, color: Color, piece: Piece, src: Square) {
         match piece {
             Piece::King => self.handle_king_move(color),
-            Piece::Rook => self.handle_rook_move(color, src),
+            Piece::Rook => self.handle_rook_removal(color, src),
             _ => (),
         }
     }
@@ -354,105 +1939,542 @@ impl Board {
             .remove(color.kingside_castling_flag() | color.queenside_castling_flag());
     }
 
-    fn handle_rook_move(&mut self, color: Color, src: Square) {
-        let (kingside, queenside) = match color {
-            Color::White => (Square::H1, Square::A1),
-            Color::Black => (Square::H8, Square::A8),
+    /// Clears the castling right of a rook leaving `src`, whether it moved or was captured.
+    fn handle_rook_removal(&mut self, color: Color, src: Square) {
+        let [kingside, queenside] = self.castling_rooks[color as usize];
+
+        if src == kingside {
+            self.castling.remove(color.kingside_castling_flag())
+        } else if src == queenside {
+            self.castling.remove(color.queenside_castling_flag())
+        }
+    }
+
+    /// Returns all legal moves for pieces of the specified type and color.
+    pub fn moves(&self, color: Color, piece: Piece) -> impl Iterator<Item = Move> + '_ {
+        self.squares(color, piece)
+            .flat_map(move |src| self.square_moves(color, piece, src))
+    }
+
+    /// Returns all legal moves available for the specified color.
+    pub fn color_moves(&self, color: Color) -> impl Iterator<Item = Move> + '_ {
+        Piece::iter().flat_map(move |piece| self.moves(color, piece))
+    }
+
+    /// Returns legal moves for a specific piece (color and type) originating from a given square.
+    /// Useful for generating moves when a player selects a particular piece on the board.
+    pub fn square_moves(
+        &self,
+        color: Color,
+        piece: Piece,
+        src: Square,
+    ) -> impl Iterator<Item = Move> + '_ {
+        let mask = self.pseudo_moves_mask(color, piece, src).unwrap_or(0);
+        let legality = self.legality(color);
+        let castling: Vec<Move> = if piece == Piece::King {
+            self.castling_moves(color, src).collect()
+        } else {
+            Vec::new()
+        };
+        (0..64)
+            .filter_map(move |dst| {
+                if (mask & (1 << dst)) != 0 {
+                    Square::from_index(dst)
+                } else {
+                    None
+                }
+            })
+            .filter(move |&dst| self.is_legal(&legality, color, piece, src, dst))
+            .flat_map(move |dst| self.generate_moves(color, piece, src, dst))
+            .chain(castling)
+    }
+
+    /// Returns a [`Selection`] with every legal move of the piece standing on the given square,
+    /// which [`Board::selection`] caches.
+    fn select(&self, square: Square) -> Selection {
+        let piece = self.on_square(square);
+        let moves: Vec<Move> = match piece {
+            Some((color, piece)) => self.square_moves(color, piece, square).collect(),
+            None => Vec::new(),
+        };
+
+        let mut destinations = 0;
+        let mut promotions = 0;
+        let mut castling = CastlingRights::empty();
+        for mov in &moves {
+            match mov {
+                Move::Regular(regular) => destinations |= 1 << regular.dst as usize,
+                Move::Promotion(promotion) => {
+                    destinations |= 1 << promotion.dst as usize;
+                    promotions |= 1 << promotion.dst as usize;
+                }
+                Move::Castling(castling_move) => {
+                    let (king_dst, _) = castling_destinations(*castling_move);
+                    destinations |= 1 << castling_move.dst as usize | 1 << king_dst as usize;
+                    if let Some((color, _)) = piece {
+                        castling |= if castling_move.dst.file() > castling_move.src.file() {
+                            color.kingside_castling_flag()
+                        } else {
+                            color.queenside_castling_flag()
+                        };
+                    }
+                }
+            }
+        }
+
+        Selection {
+            key: self.zobrist(),
+            square,
+            piece,
+            moves,
+            destinations,
+            promotions,
+            castling,
+        }
+    }
+
+    /// Returns legal castling moves for the specified color's king. Castling is encoded as the
+    /// king taking its own rook, see [`Move::Castling`].
+    pub fn castling_moves(&self, color: Color, src: Square) -> impl Iterator<Item = Move> {
+        let mut moves = Vec::new();
+        self.for_each_castling_move(color, src, |mov| moves.push(mov));
+        moves.into_iter()
+    }
+
+    /// Returns [`true`] if the king of the color can castle to the given side right now: the
+    /// right is still held, the squares the king and rook pass are empty, and the king is not
+    /// in check and does not pass or land on an attacked square. Whose turn it is is not
+    /// taken into account, as with [`Position::castling_moves`].
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{CastlingSide, Color};
+    ///
+    /// // The rook on f8 covers f1, so White cannot castle kingside yet
+    /// let board = Board::from_fen("r3kr2/8/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+    /// assert!(!board.can_castle(Color::White, CastlingSide::Kingside));
+    /// assert!(board.can_castle(Color::White, CastlingSide::Queenside));
+    /// // Black has lost the kingside right
+    /// assert!(!board.can_castle(Color::Black, CastlingSide::Kingside));
+    /// assert!(board.can_castle(Color::Black, CastlingSide::Queenside));
+    /// ```
+    pub fn can_castle(&self, color: Color, side: CastlingSide) -> bool {
+        let Some(king) = Square::from_index(bitscan_forward(self.bitboard(color, Piece::King)))
+        else {
+            return false;
+        };
+        let rook = self.castling_rooks[color as usize][side as usize];
+        let mut can_castle = false;
+        self.for_each_castling_move(color, king, |mov| {
+            can_castle |= mov
+                == Move::Castling(CastlingMove {
+                    src: king,
+                    dst: rook,
+                });
+        });
+        can_castle
+    }
+
+    fn for_each_castling_move(&self, color: Color, src: Square, mut f: impl FnMut(Move)) {
+        if src.rank() != color.back_rank()
+            || self.bitboard(color, Piece::King) & (1 << src as usize) == 0
+        {
+            return;
+        }
+
+        let flags = [
+            color.kingside_castling_flag(),
+            color.queenside_castling_flag(),
+        ];
+        for (flag, rook) in flags.into_iter().zip(self.castling_rooks[color as usize]) {
+            if !self.castling.contains(flag)
+                || self.bitboard(color, Piece::Rook) & (1 << rook as usize) == 0
+            {
+                continue;
+            }
+
+            let castling = CastlingMove { src, dst: rook };
+            let (king_dst, rook_dst) = castling_destinations(castling);
+
+            // Only the castling king and rook may stand on the squares they pass. Attacks are
+            // checked without them too, since e.g. a rook on b1 may be shielding c1 from an
+            // enemy rook on a1 before castling queenside.
+            let occupied = self.occupied_mask() & !(1 << src as usize) & !(1 << rook as usize);
+            let king_path = span(src, king_dst);
+            if occupied & (king_path | span(rook, rook_dst)) == 0
+                && (0..64)
+                    .filter(|square| king_path & (1 << square) != 0)
+                    .filter_map(Square::from_repr)
+                    .all(|square| !self.is_square_attacked_through(color, square, occupied))
+            {
+                f(Move::Castling(castling));
+            }
+        }
+    }
+
+    /// Writes all legal moves of the active color into the list, replacing its contents. Unlike
+    /// [`Position::color_moves`] this never allocates, which matters in search and perft.
+    ///
+    /// The moves are in the same order as those returned by [`Position::color_moves`].
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn generate_moves_into(&self, moves: &mut MoveList) {
+        moves.clear();
+        let color = self.active;
+        let legality = self.legality(color);
+        for piece in Piece::iter() {
+            let mut pieces = self.bitboard(color, piece);
+            while pieces != 0 {
+                let src = Square::from_index(bitscan_forward(pieces)).unwrap();
+                pieces &= pieces - 1;
+
+                let mut mask = self.pseudo_moves_mask(color, piece, src).unwrap_or(0);
+                while mask != 0 {
+                    let dst = Square::from_index(bitscan_forward(mask)).unwrap();
+                    mask &= mask - 1;
+                    if !self.is_legal(&legality, color, piece, src, dst) {
+                        continue;
+                    }
+                    if piece == Piece::Pawn && dst.rank() == color.promotion_rank() {
+                        for piece in PromotionPiece::iter() {
+                            moves.push(Move::Promotion(PromotionMove { src, dst, piece }));
+                        }
+                    } else {
+                        moves.push(Move::Regular(RegularMove { src, dst }));
+                    }
+                }
+
+                if piece == Piece::King {
+                    self.for_each_castling_move(color, src, |mov| moves.push(mov));
+                }
+            }
+        }
+    }
+
+    /// Counts the leaf nodes of the legal move tree of the given depth, starting with the
+    /// active color. Used to verify the move generator against known values; game-ending
+    /// draws such as repetitions are not taken into account.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(board.perft(1), 20);
+    /// assert_eq!(board.perft(3), 8902);
+    /// ```
+    pub fn perft(&self, depth: usize) -> u64 {
+        let mut position = *self;
+        position
+            .perft_mut(depth, &mut PerftCounter::new(None, &mut |_| ()))
+            .unwrap()
+    }
+
+    /// Same as [`Position::perft`], but can be cancelled through the [`StopToken`].
+    pub fn try_perft(&self, depth: usize, stop: &StopToken) -> Result<u64, Stopped> {
+        let mut position = *self;
+        position.perft_mut(depth, &mut PerftCounter::new(Some(stop), &mut |_| ()))
+    }
+
+    /// Same as [`Position::perft`], but returns the leaf node count under each legal move of the
+    /// active color, which helps to localize move generator bugs.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let board = Board::default();
+    /// let divide = board.perft_divide(2);
+    /// assert_eq!(divide.len(), 20);
+    /// assert!(divide.contains(&(Move::from_lan("e2e4").unwrap(), 20)));
+    /// ```
+    pub fn perft_divide(&self, depth: usize) -> Vec<(Move, u64)> {
+        self.perft_divide_mut(depth, None, &mut |_| ()).unwrap()
+    }
+
+    /// Same as [`Position::perft_divide`], but can be cancelled through the [`StopToken`].
+    pub fn try_perft_divide(
+        &self,
+        depth: usize,
+        stop: &StopToken,
+    ) -> Result<Vec<(Move, u64)>, Stopped> {
+        self.perft_divide_mut(depth, Some(stop), &mut |_| ())
+    }
+
+    /// Same as [`Position::perft`], but counts the root moves in parallel on the global
+    /// [`rayon`] thread pool.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let board = Board::default();
+    /// assert_eq!(board.perft_parallel(3), board.perft(3));
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn perft_parallel(&self, depth: usize) -> u64 {
+        if depth <= 1 {
+            return self.perft(depth);
+        }
+        self.perft_divide_parallel(depth)
+            .into_iter()
+            .map(|(_, nodes)| nodes)
+            .sum()
+    }
+
+    /// Same as [`Position::perft_divide`], but counts the root moves in parallel on the global
+    /// [`rayon`] thread pool. The moves are returned in the same order.
+    #[cfg(feature = "rayon")]
+    pub fn perft_divide_parallel(&self, depth: usize) -> Vec<(Move, u64)> {
+        use rayon::prelude::*;
+
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
+        moves
+            .par_iter()
+            .map(|&mov| {
+                let mut position = *self;
+                position.do_move(mov);
+                (mov, position.perft(depth.saturating_sub(1)))
+            })
+            .collect()
+    }
+
+    /// Same as [`Position::try_perft_divide`], but calls `progress` before each root move and
+    /// regularly while counting it, so that long runs can display a progress bar.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::stop::StopToken;
+    ///
+    /// let board = Board::default();
+    /// let mut reports = Vec::new();
+    /// let divide = board
+    ///     .perft_with_progress(3, &StopToken::new(), |progress| reports.push(*progress))
+    ///     .unwrap();
+    /// assert_eq!(divide.len(), 20);
+    /// assert_eq!(reports.len(), 20);
+    /// assert_eq!(reports[0].estimated_total, None);
+    /// assert_eq!(reports[1].estimated_total, Some(380 * 20));
+    /// assert_eq!(reports[19].root_moves_done, 19);
+    /// ```
+    pub fn perft_with_progress(
+        &self,
+        depth: usize,
+        stop: &StopToken,
+        mut progress: impl FnMut(&PerftProgress),
+    ) -> Result<Vec<(Move, u64)>, Stopped> {
+        self.perft_divide_mut(depth, Some(stop), &mut progress)
+    }
+
+    fn perft_divide_mut(
+        &self,
+        depth: usize,
+        stop: Option<&StopToken>,
+        progress: &mut dyn FnMut(&PerftProgress),
+    ) -> Result<Vec<(Move, u64)>, Stopped> {
+        let mut position = *self;
+        let mut moves = MoveList::new();
+        position.generate_moves_into(&mut moves);
+        let mut divide = Vec::with_capacity(moves.len());
+        let mut done = 0;
+
+        for (index, &mov) in moves.iter().enumerate() {
+            let mut status = PerftProgress {
+                nodes: done,
+                estimated_total: (index > 0).then(|| done * moves.len() as u64 / index as u64),
+                root_move: mov,
+                root_moves_done: index,
+                root_moves: moves.len(),
+            };
+            progress(&status);
+
+            let mut report = |nodes| {
+                status.nodes = done + nodes;
+                progress(&status)
+            };
+            let mut counter = PerftCounter::new(stop, &mut report);
+            let undo = position.do_move(mov);
+            let nodes = position.perft_mut(depth.saturating_sub(1), &mut counter);
+            position.undo_move(undo);
+
+            let nodes = nodes?;
+            done += nodes;
+            divide.push((mov, nodes));
+        }
+
+        Ok(divide)
+    }
+
+    fn perft_mut(&mut self, depth: usize, counter: &mut PerftCounter) -> Result<u64, Stopped> {
+        if depth == 0 {
+            return Ok(1);
+        }
+
+        if let Some(stop) = counter.stop {
+            stop.check()?;
+        }
+
+        let mut moves = MoveList::new();
+        self.generate_moves_into(&mut moves);
+        if depth == 1 {
+            counter.add(moves.len() as u64);
+            return Ok(moves.len() as u64);
+        }
+
+        let mut nodes = 0;
+        for &mov in moves.iter() {
+            let undo = self.do_move(mov);
+            let result = self.perft_mut(depth - 1, counter);
+            self.undo_move(undo);
+            nodes += result?;
+        }
+        Ok(nodes)
+    }
+
+    /// Returns [`true`] if the king of the specified color is attacked.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{Color, Move};
+    ///
+    /// let mut board = Board::default();
+    /// for mov in ["e2e4", "f7f6", "d1h5"] {
+    ///     board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+    /// }
+    /// assert!(board.is_check(Color::Black));
+    /// assert!(!board.is_check(Color::White));
+    /// ```
+    pub fn is_check(&self, color: Color) -> bool {
+        self.is_king_attacked(color)
+    }
+
+    /// Returns [`true`] if the legal move of the active color puts the opponent in check,
+    /// directly or by uncovering an attack of another piece. The board is not modified.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// // Moving the bishop uncovers the rook on e1
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/4B3/K3R3 w - - 0 1").unwrap();
+    /// assert!(board.gives_check(Move::from_lan("e2b5").unwrap()));
+    /// assert!(board.gives_check(Move::from_lan("e2f3").unwrap()));
+    /// assert!(!board.gives_check(Move::from_lan("a1b1").unwrap()));
+    /// ```
+    pub fn gives_check(&self, mov: Move) -> bool {
+        let color = self.active;
+        let (src, dst) = endpoints(mov);
+        let piece = match self.on_square(src) {
+            Some((owner, piece)) if owner == color => piece,
+            _ => return false,
+        };
+        let king = self.bitboard(color.opposite(), Piece::King);
+        let Some(king_square) = Square::from_index(bitscan_forward(king)) else {
+            return false;
+        };
+
+        let mov = match mov {
+            Move::Regular(_) if piece == Piece::King => {
+                self.castling_notation(color, src, dst).unwrap_or(mov)
+            }
+            _ => mov,
         };
 
-        if src == kingside {
-            self.castling.remove(color.kingside_castling_flag())
-        } else if src == queenside {
-            self.castling.remove(color.queenside_castling_flag())
+        // Our pieces and the occupancy as they will be after the move
+        let mut pieces = self.bitboards[color as usize];
+        let mut occupied = self.occupied_mask();
+        let (src_bb, dst_bb) = (1 << src as usize, 1 << dst as usize);
+        match mov {
+            Move::Castling(castling) => {
+                let (king_dst, rook_dst) = castling_destinations(castling);
+                let (king_dst, rook_dst) = (1 << king_dst as usize, 1 << rook_dst as usize);
+                pieces[Piece::Rook as usize] = pieces[Piece::Rook as usize] & !dst_bb | rook_dst;
+                occupied = occupied & !(src_bb | dst_bb) | king_dst | rook_dst;
+            }
+            Move::Regular(_) | Move::Promotion(_) => {
+                if let Some((_, square)) = self.captured_piece(color, piece, dst) {
+                    occupied &= !(1 << square as usize);
+                }
+                let landed = match mov {
+                    Move::Promotion(promotion) => promotion.piece.into(),
+                    _ => piece,
+                };
+                pieces[piece as usize] &= !src_bb;
+                pieces[landed as usize] |= dst_bb;
+                occupied = occupied & !src_bb | dst_bb;
+            }
         }
-    }
 
-    fn reset_draw_conditions(&mut self) {
-        self.history.clear();
-        self.halfclock = 0;
+        let queens = pieces[Piece::Queen as usize];
+        attacks::pawn_attacks(king, color.opposite()) & pieces[Piece::Pawn as usize] != 0
+            || attacks::knight_attacks(king) & pieces[Piece::Knight as usize] != 0
+            || attacks::bishop_attacks(king_square, occupied)
+                & (pieces[Piece::Bishop as usize] | queens)
+                != 0
+            || attacks::rook_attacks(king_square, occupied)
+                & (pieces[Piece::Rook as usize] | queens)
+                != 0
     }
 
-    fn update_draw_conditions(&mut self) {
-        self.history.push(self.bitboards);
-        self.halfclock += 1;
-    }
-
-    /// Returns all legal moves for pieces of the specified type and color.
-    pub fn moves(&self, color: Color, piece: Piece) -> impl Iterator<Item = Move> + '_ {
-        self.squares(color, piece)
-            .flat_map(move |src| self.square_moves(color, piece, src))
+    /// Returns [`true`] if the active color is in check and has no legal moves.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    ///
+    /// let mut board = Board::default();
+    /// for mov in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+    ///     board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+    /// }
+    /// assert!(board.is_checkmate());
+    /// assert!(!board.is_stalemate());
+    /// ```
+    pub fn is_checkmate(&self) -> bool {
+        self.is_checkmated(self.active)
     }
 
-    /// Returns all legal moves available for the specified color.
-    pub fn color_moves(&self, color: Color) -> impl Iterator<Item = Move> + '_ {
-        Piece::iter().flat_map(move |piece| self.moves(color, piece))
+    /// Returns [`true`] if the specified color is in check and has no legal moves, whichever
+    /// color is to move. Useful for positions set up from a FEN, such as compositions, where
+    /// the side to move may not be the one of interest.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Color;
+    ///
+    /// // Black is mated, although the FEN gives White the move
+    /// let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 w - - 0 1").unwrap();
+    /// assert!(board.is_checkmated(Color::Black));
+    /// assert!(!board.is_checkmated(Color::White));
+    /// assert!(!board.is_checkmate());
+    /// ```
+    pub fn is_checkmated(&self, color: Color) -> bool {
+        self.is_king_attacked(color) && !self.color_has_moves(color)
     }
 
-    /// Returns legal moves for a specific piece (color and type) originating from a given square.
-    /// Useful for generating moves when a player selects a particular piece on the board.
-    pub fn square_moves(
-        &self,
-        color: Color,
-        piece: Piece,
-        src: Square,
-    ) -> impl Iterator<Item = Move> + '_ {
-        let mask = self.pseudo_moves_mask(color, piece, src).unwrap_or(0);
-        (0..64)
-            .filter_map(move |dst| {
-                if (mask & (1 << dst)) != 0 {
-                    Square::from_repr(dst)
-                } else {
-                    None
-                }
-            })
-            .filter(move |&dst| !self.is_move_pinned(color, piece, src, dst))
-            .flat_map(move |dst| self.generate_moves(color, piece, src, dst))
+    /// Returns [`true`] if the active color is not in check but has no legal moves.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// let board = Board::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert!(board.is_stalemate());
+    /// assert!(!board.is_checkmate());
+    /// ```
+    pub fn is_stalemate(&self) -> bool {
+        self.is_stalemated(self.active)
     }
 
-    /// Returns legal castling moves for the specified color's king.
-    pub fn castling_moves(&self, color: Color, src: Square) -> impl Iterator<Item = Move> {
-        let mut moves = Vec::new();
-        let empty_mask = !self.occupied_mask();
-
-        if self.castling.contains(color.kingside_castling_flag()) {
-            let path_mask = match color {
-                Color::White => 0x60, // f1 and g1
-                Color::Black => 0x6000000000000000,
-            };
-            let mut path = match color {
-                Color::White => [Square::E1, Square::F1, Square::G1].iter(),
-                Color::Black => [Square::E1, Square::F8, Square::G8].iter(),
-            };
-            if empty_mask & path_mask == path_mask
-                && path.all(|&sq| !self.is_square_attacked(color, sq))
-            {
-                moves.push(Move::Castling(CastlingMove {
-                    src,
-                    dst: Square::from_repr(src as usize + 2).unwrap(),
-                }))
-            }
-        }
-
-        if self.castling.contains(color.queenside_castling_flag()) {
-            let path_mask = match color {
-                Color::White => 0x0E, // b1, c1, d1
-                Color::Black => 0x0E00000000000000,
-            };
-            let mut path = match color {
-                Color::White => [Square::E1, Square::D1, Square::C1, Square::B1].iter(),
-                Color::Black => [Square::E8, Square::D8, Square::C8, Square::B8].iter(),
-            };
-            if empty_mask & path_mask == path_mask
-                && path.all(|&sq| !self.is_square_attacked(color, sq))
-            {
-                moves.push(Move::Castling(CastlingMove {
-                    src,
-                    dst: Square::from_repr(src as usize - 2).unwrap(),
-                }))
-            }
-        }
-
-        moves.into_iter()
+    /// Returns [`true`] if the specified color is not in check but has no legal moves,
+    /// whichever color is to move.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Color;
+    ///
+    /// let board = Board::from_fen("7k/5K2/6Q1/8/8/8/8/8 w - - 0 1").unwrap();
+    /// assert!(board.is_stalemated(Color::Black));
+    /// assert!(!board.is_stalemated(Color::White));
+    /// ```
+    pub fn is_stalemated(&self, color: Color) -> bool {
+        !self.is_king_attacked(color) && !self.color_has_moves(color)
     }
 
     /// Returns [`true`] if the specified color has any legal moves available.
@@ -467,16 +2489,21 @@ impl Board {
         let occupied = friendly | enemy;
         let bb = 1 << src as usize;
 
+        let en_passant = match self.en_passant {
+            Some(square) if color == self.active => 1 << square as usize,
+            _ => 0,
+        };
+
         let moves = match piece {
             Piece::Pawn => {
-                (attacks::pawn_attacks(bb, color) & (enemy | self.en_passant.unwrap_or(0)))
+                (attacks::pawn_attacks(bb, color) & (enemy | en_passant))
                     | attacks::pawn_advances(src, color, occupied)
             }
             Piece::Rook => attacks::rook_attacks(src, occupied),
-            Piece::Knight => attacks::knight_attacks(bb),
+            Piece::Knight => attacks::knight_attacks_from(src),
             Piece::Bishop => attacks::bishop_attacks(src, occupied),
             Piece::Queen => attacks::queen_attacks(src, occupied),
-            Piece::King => attacks::king_attacks(bb),
+            Piece::King => attacks::king_attacks_from(src),
         } & !friendly;
 
         (moves != 0).then_some(moves)
@@ -492,30 +2519,183 @@ impl Board {
         let mut moves = Vec::new();
         if Piece::Pawn == piece && dst.rank() == color.promotion_rank() {
             moves.extend(PromotionMove::all(src, dst))
-        } else if Piece::King == piece {
-            let mut castling_moves = self.castling_moves(color, src).peekable();
-            if castling_moves.peek().is_some() {
-                moves.extend(castling_moves)
-            } else {
-                moves.push(Move::Regular(RegularMove { src, dst }))
-            }
         } else {
             moves.push(Move::Regular(RegularMove { src, dst }))
         }
         moves.into_iter()
     }
 
-    fn is_fivefold_repetition(&self) -> bool {
-        self.history
+    /// Returns [`true`] if neither player can checkmate by any series of legal moves because
+    /// only kings and minor pieces are left: a king against a king with at most one knight or
+    /// bishop, or kings with any number of bishops all standing on squares of one color
+    /// (FIDE 9.6.2).
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// // Bishops on c3 and f6 are both on dark squares
+    /// let board = Board::from_fen("8/8/4kb2/8/8/2BK4/8/8 w - - 0 1").unwrap();
+    /// assert!(board.is_insufficient_material());
+    ///
+    /// let board = Board::from_fen("8/8/4k1b1/8/8/2BK4/8/8 w - - 0 1").unwrap();
+    /// assert!(!board.is_insufficient_material());
+    /// ```
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy = [Piece::Pawn, Piece::Rook, Piece::Queen]
             .iter()
-            .filter(|&bitboards| *bitboards == self.bitboards)
-            .count()
-            >= 5
+            .any(|&piece| {
+                self.bitboard(Color::White, piece) | self.bitboard(Color::Black, piece) != 0
+            });
+        if heavy {
+            return false;
+        }
+
+        let knights =
+            self.bitboard(Color::White, Piece::Knight) | self.bitboard(Color::Black, Piece::Knight);
+        let bishops =
+            self.bitboard(Color::White, Piece::Bishop) | self.bitboard(Color::Black, Piece::Bishop);
+        (knights | bishops).count_ones() <= 1
+            || (knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0))
+    }
+
+    /// Returns [`true`] if only kings and pawns are left and the pawns are locked for good:
+    /// every pawn is blocked by a pawn, no pawn can capture, and neither king can reach a pawn
+    /// it could take, so neither player can checkmate by any series of legal moves (FIDE
+    /// 9.6.2). Positions where a king could break through are not recognized, even if doing so
+    /// leads nowhere.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    ///
+    /// // The pawn chains shut the kings in on their own sides
+    /// let board = Board::from_fen("8/8/1k6/p1p1p1p1/P1P1P1P1/8/5K2/8 w - - 0 1").unwrap();
+    /// assert!(board.is_blocked_position());
+    ///
+    /// // The a-pawns are not protected, so the white king can go after the black one
+    /// let board = Board::from_fen("8/8/1k6/p1p1p1p1/1PP1P1P1/8/5K2/8 w - - 0 1").unwrap();
+    /// assert!(!board.is_blocked_position());
+    /// ```
+    pub fn is_blocked_position(&self) -> bool {
+        let pieces = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+            .iter()
+            .any(|&piece| {
+                self.bitboard(Color::White, piece) | self.bitboard(Color::Black, piece) != 0
+            });
+        if pieces || self.en_passant.is_some() {
+            return false;
+        }
+
+        let pawns =
+            self.bitboard(Color::White, Piece::Pawn) | self.bitboard(Color::Black, Piece::Pawn);
+        Color::iter().all(|color| {
+            let own = self.bitboard(color, Piece::Pawn);
+            let enemy = self.bitboard(color.opposite(), Piece::Pawn);
+            // Pawns only stay blocked by pawns, and never capture
+            let ahead = match color {
+                Color::White => own << 8,
+                Color::Black => own >> 8,
+            };
+            if ahead & !pawns != 0 || attacks::pawn_attacks(own, color) & enemy != 0 {
+                return false;
+            }
+
+            // Squares the king can reach without walking into a pawn attack
+            let free = !(own | attacks::pawn_attacks(enemy, color.opposite()));
+            let mut reach = self.bitboard(color, Piece::King);
+            loop {
+                let next = reach | attacks::king_attacks(reach) & free;
+                if next == reach {
+                    break;
+                }
+                reach = next;
+            }
+            reach & enemy == 0
+        })
+    }
+
+    /// Returns the pieces of `color` attacking `square` with the current occupancy, whether or
+    /// not they could legally move there. Sliders are blocked by pieces of both colors.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{Color, Square};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/3p4/8/2N5/8/3RK3 w - - 0 1").unwrap();
+    /// let attackers = board.attackers(Square::D5, Color::White);
+    /// assert_eq!(attackers.collect::<Vec<_>>(), [Square::D1, Square::C3]);
+    /// assert_eq!(board.attackers(Square::E4, Color::Black).first(), Some(Square::D5));
+    /// assert!(board.attackers(Square::A8, Color::White).is_empty());
+    /// ```
+    pub fn attackers(&self, square: Square, color: Color) -> Bitboard {
+        let occupied = self.occupied_mask();
+        let pawns = self.bitboard(color, Piece::Pawn);
+        let knights = self.bitboard(color, Piece::Knight);
+        let bishops = self.bitboard(color, Piece::Bishop);
+        let rooks = self.bitboard(color, Piece::Rook);
+        let queens = self.bitboard(color, Piece::Queen);
+        let kings = self.bitboard(color, Piece::King);
+
+        // A pawn of `color` attacks the square if a pawn of the other color on it would attack
+        // the pawn
+        Bitboard(
+            attacks::pawn_attacks(1 << square as usize, color.opposite()) & pawns
+                | attacks::knight_attacks_from(square) & knights
+                | attacks::king_attacks_from(square) & kings
+                | attacks::bishop_attacks(square, occupied) & (bishops | queens)
+                | attacks::rook_attacks(square, occupied) & (rooks | queens),
+        )
+    }
+
+    /// Returns every square attacked by `color` with the current occupancy, including squares
+    /// holding its own pieces, which it defends.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::{Color, Square};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    /// let attacked = board.attacked_squares(Color::White);
+    /// assert!(attacked.contains(Square::A8));
+    /// assert!(attacked.contains(Square::E1)); // The king is defended by the rook
+    /// assert!(!attacked.contains(Square::G1)); // The rook is blocked by the king
+    /// assert_eq!(attacked.len(), 11 + 4);
+    /// ```
+    pub fn attacked_squares(&self, color: Color) -> Bitboard {
+        Bitboard(self.attacked_squares_through(color, self.occupied_mask()))
     }
 
+    /// Same as [`Position::attacked_squares`], but sliders are blocked by the given occupancy.
+    fn attacked_squares_through(&self, color: Color, occupied: u64) -> u64 {
+        let queens = self.bitboard(color, Piece::Queen);
+        let mut attacked = attacks::pawn_attacks(self.bitboard(color, Piece::Pawn), color)
+            | attacks::knight_attacks(self.bitboard(color, Piece::Knight))
+            | attacks::king_attacks(self.bitboard(color, Piece::King));
+
+        let mut bishops = self.bitboard(color, Piece::Bishop) | queens;
+        while bishops != 0 {
+            let square = Square::from_index(bitscan_forward(bishops)).unwrap();
+            bishops &= bishops - 1;
+            attacked |= attacks::bishop_attacks(square, occupied);
+        }
+        let mut rooks = self.bitboard(color, Piece::Rook) | queens;
+        while rooks != 0 {
+            let square = Square::from_index(bitscan_forward(rooks)).unwrap();
+            rooks &= rooks - 1;
+            attacked |= attacks::rook_attacks(square, occupied);
+        }
+        attacked
+    }
+
+    /// Returns [`true`] if `square` is attacked by the opponent of `color`.
     fn is_square_attacked(&self, color: Color, square: Square) -> bool {
+        self.is_square_attacked_through(color, square, self.occupied_mask())
+    }
+
+    /// Same as [`Position::is_square_attacked`], but sliders are blocked by the given
+    /// occupancy. Unlike [`Position::attackers`], it stops at the first attacker found, which
+    /// matters in move generation.
+    fn is_square_attacked_through(&self, color: Color, square: Square, occupied: u64) -> bool {
         let opponent = color.opposite();
-        let occupied = self.occupied_mask();
 
         let pawns = self.bitboard(opponent, Piece::Pawn);
         if attacks::pawn_attacks(1 << square as usize, color) & pawns != 0 {
@@ -523,12 +2703,12 @@ impl Board {
         }
 
         let knights = self.bitboard(opponent, Piece::Knight);
-        if attacks::knight_attacks(1 << square as usize) & knights != 0 {
+        if attacks::knight_attacks_from(square) & knights != 0 {
             return true;
         }
 
         let kings = self.bitboard(opponent, Piece::King);
-        if attacks::king_attacks(1 << square as usize) & kings != 0 {
+        if attacks::king_attacks_from(square) & kings != 0 {
             return true;
         }
 
@@ -540,9 +2720,9 @@ impl Board {
             || (attacks::rook_attacks(square, occupied) & (rooks | queens) != 0)
     }
 
-    fn is_king_attacked(&self, color: Color) -> bool {
+    pub(crate) fn is_king_attacked(&self, color: Color) -> bool {
         let king = self.bitboard(color, Piece::King);
-        match Square::from_repr(bitscan_forward(king)) {
+        match Square::from_index(bitscan_forward(king)) {
             Some(sq) => self.is_square_attacked(color, sq),
             None => false,
         }
@@ -553,14 +2733,96 @@ impl Board {
         moves_mask.is_some_and(|mask| mask & (1 << dst as usize) != 0)
     }
 
-    fn is_move_pinned(&self, color: Color, piece: Piece, src: Square, dst: Square) -> bool {
-        let mut board = self.clone();
-        board.force_move(color, piece, src, dst);
-        if let Some(blocker) = self.on_square(dst) {
-            let (color, piece) = blocker;
-            board.remove_piece(color, piece, dst);
+    fn legality(&self, color: Color) -> Legality {
+        let mut legality = Legality {
+            king: self.bitboard(color, Piece::King),
+            checkers: 0,
+            evasions: u64::MAX,
+            king_danger: 0,
+            pins: [(0, 0); 8],
+            pin_count: 0,
+        };
+        let Some(king) = Square::from_index(bitscan_forward(legality.king)) else {
+            return legality;
+        };
+
+        let opponent = color.opposite();
+        let occupied = self.occupied_mask();
+        let queens = self.bitboard(opponent, Piece::Queen);
+        let rooks = self.bitboard(opponent, Piece::Rook) | queens;
+        let bishops = self.bitboard(opponent, Piece::Bishop) | queens;
+
+        // The king does not block the sliders attacking it, so it cannot step back along them
+        legality.king_danger = self.attacked_squares_through(opponent, occupied & !legality.king);
+        legality.checkers = (attacks::pawn_attacks(legality.king, color)
+            & self.bitboard(opponent, Piece::Pawn))
+            | (attacks::knight_attacks(legality.king) & self.bitboard(opponent, Piece::Knight))
+            | (attacks::rook_attacks(king, occupied) & rooks)
+            | (attacks::bishop_attacks(king, occupied) & bishops);
+        if legality.checkers.count_ones() == 1 {
+            let checker = Square::from_index(bitscan_forward(legality.checkers)).unwrap();
+            legality.evasions = legality.checkers | between(king, checker);
+        } else if legality.checkers != 0 {
+            legality.evasions = 0;
+        }
+
+        // Enemy sliders seen from the king through our own pieces pin the only piece between
+        let enemies = self.occupied_by_color_mask(opponent);
+        let mut snipers = (attacks::rook_attacks(king, enemies) & rooks)
+            | (attacks::bishop_attacks(king, enemies) & bishops);
+        while snipers != 0 {
+            let sniper = Square::from_index(bitscan_forward(snipers)).unwrap();
+            snipers &= snipers - 1;
+            let ray = between(king, sniper);
+            let blockers = ray & occupied;
+            if blockers.count_ones() == 1 {
+                legality.pins[legality.pin_count] = (blockers, ray | 1 << sniper as usize);
+                legality.pin_count += 1;
+            }
+        }
+
+        legality
+    }
+
+    /// Returns [`true`] if the pseudo-legal move does not leave the king of the moving color
+    /// in check.
+    fn is_legal(
+        &self,
+        legality: &Legality,
+        color: Color,
+        piece: Piece,
+        src: Square,
+        dst: Square,
+    ) -> bool {
+        let (src_bb, dst_bb) = (1 << src as usize, 1 << dst as usize);
+        if piece == Piece::King {
+            return legality.king_danger & dst_bb == 0;
+        }
+        if legality.checkers.count_ones() > 1 {
+            return false;
+        }
+
+        // En passant removes a second piece from the board, which may expose the king along
+        // the rank, so it is checked by playing it out
+        if piece == Piece::Pawn
+            && color == self.active
+            && self.en_passant == Some(dst)
+            && src.file() != dst.file()
+        {
+            return !self.leaves_king_attacked(color, piece, src, dst);
+        }
+
+        legality.evasions & dst_bb != 0
+            && legality.pin_ray(src_bb).is_none_or(|ray| ray & dst_bb != 0)
+    }
+
+    fn leaves_king_attacked(&self, color: Color, piece: Piece, src: Square, dst: Square) -> bool {
+        let mut position = *self;
+        if let Some((captured, square)) = position.captured_piece(color, piece, dst) {
+            position.remove_piece(color.opposite(), captured, square);
         }
-        board.is_king_attacked(color)
+        position.force_move(color, piece, src, dst);
+        position.is_king_attacked(color)
     }
 
     fn flat_enumerate(&self) -> impl Iterator<Item = (Color, Piece, &u64)> {
@@ -570,8 +2832,8 @@ impl Board {
             .flat_map(|(color, pieces)| {
                 pieces.iter().enumerate().map(move |(piece, bb)| {
                     (
-                        Color::from_repr(color).unwrap(),
-                        Piece::from_repr(piece).unwrap(),
+                        Color::from_repr(color as u8).unwrap(),
+                        Piece::from_repr(piece as u8).unwrap(),
                         bb,
                     )
                 })
@@ -584,10 +2846,581 @@ impl Board {
     }
 
     fn remove_piece(&mut self, color: Color, piece: Piece, square: Square) {
-        *self.bitboard_mut(color, piece) &= !(1 << square as usize)
+        *self.bitboard_mut(color, piece) &= !(1 << square as usize);
+        self.zobrist ^= zobrist::KEYS.pieces[color as usize][piece as usize][square as usize];
     }
 
     fn add_piece(&mut self, color: Color, piece: Piece, square: Square) {
-        *self.bitboard_mut(color, piece) |= 1 << square as usize
+        *self.bitboard_mut(color, piece) |= 1 << square as usize;
+        self.zobrist ^= zobrist::KEYS.pieces[color as usize][piece as usize][square as usize];
+    }
+}
+
+/// Parses a single castling right of a FEN, returning the color, side (kingside if [`true`])
+/// and the castling rook if it was found. Accepts both `KQkq` (X-FEN, the outermost rook)
+/// and Shredder-FEN rook files such as `HAha` used in Chess960.
+fn parse_castling_right(
+    bitboards: &[[u64; 6]; 2],
+    c: char,
+) -> Result<(Color, bool, Option<Square>), ParseFenError> {
+    let color = if c.is_ascii_uppercase() {
+        Color::White
+    } else {
+        Color::Black
+    };
+    let back_rank = color.back_rank().mask().0;
+    let king = bitboards[color as usize][Piece::King as usize] & back_rank;
+    let rooks = bitboards[color as usize][Piece::Rook as usize] & back_rank;
+    let king_file = (king != 0).then(|| bitscan_forward(king) % 8);
+    let rook_on = |file: &usize| rooks & (1 << (color.back_rank() as usize * 8 + file)) != 0;
+
+    let (kingside, rook_file) = match (c.to_ascii_lowercase(), king_file) {
+        ('k', Some(king_file)) => (true, (king_file + 1..8).rev().find(rook_on)),
+        ('q', Some(king_file)) => (false, (0..king_file).find(rook_on)),
+        ('k', None) => (true, None),
+        ('q', None) => (false, None),
+        (file @ 'a'..='h', Some(king_file)) => {
+            let file = file as usize - 'a' as usize;
+            if file == king_file || !rook_on(&file) {
+                return Err(ParseFenError::BadCastling(c));
+            }
+            (file > king_file, Some(file))
+        }
+        _ => return Err(ParseFenError::BadCastling(c)),
+    };
+
+    let rook = rook_file.map(|file| Square::new(file, color.back_rank() as usize).unwrap());
+    Ok((color, kingside, rook))
+}
+
+/// Returns the square of the pawn captured en passant by a pawn of the given color
+/// landing on the target square.
+fn en_passant_victim(color: Color, target: Square) -> Square {
+    match color {
+        Color::White => Square::from_index(target as usize - 8).unwrap(),
+        Color::Black => Square::from_index(target as usize + 8).unwrap(),
+    }
+}
+
+/// Returns whether the en passant target square can be left by a double push of the color not
+/// to move: on the rank passed over, empty, and with the pushed pawn in front of it. Other
+/// squares would let the active color capture a pawn that is not there.
+pub(crate) fn is_valid_en_passant(
+    bitboards: &[[u64; 6]; 2],
+    active: Color,
+    target: Square,
+) -> bool {
+    let rank = match active {
+        Color::White => Rank::R6,
+        Color::Black => Rank::R3,
+    };
+    let occupied = bitboards.iter().flatten().fold(0, |acc, bb| acc | bb);
+    let pawns = bitboards[active.opposite() as usize][Piece::Pawn as usize];
+    target.rank() == rank
+        && occupied & (1 << target as usize) == 0
+        && pawns & (1 << en_passant_victim(active, target) as usize) != 0
+}
+
+/// Progress of a perft run, reported by [`Position::perft_with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerftProgress {
+    /// Leaf nodes counted so far.
+    pub nodes: u64,
+    /// Total leaf nodes extrapolated from the finished root moves, or [`None`] until the
+    /// first root move is finished.
+    pub estimated_total: Option<u64>,
+    /// Root move being counted.
+    pub root_move: Move,
+    /// Number of finished root moves.
+    pub root_moves_done: usize,
+    /// Number of legal root moves.
+    pub root_moves: usize,
+}
+
+/// Leaf nodes counted between two progress reports within a single root move.
+const PERFT_REPORT_INTERVAL: u64 = 1 << 20;
+
+/// Leaf node counter threaded through a perft traversal.
+struct PerftCounter<'a> {
+    stop: Option<&'a StopToken>,
+    nodes: u64,
+    next_report: u64,
+    report: &'a mut dyn FnMut(u64),
+}
+
+impl<'a> PerftCounter<'a> {
+    fn new(stop: Option<&'a StopToken>, report: &'a mut dyn FnMut(u64)) -> Self {
+        PerftCounter {
+            stop,
+            nodes: 0,
+            next_report: PERFT_REPORT_INTERVAL,
+            report,
+        }
+    }
+
+    fn add(&mut self, nodes: u64) {
+        self.nodes += nodes;
+        if self.nodes >= self.next_report {
+            self.next_report = self.nodes + PERFT_REPORT_INTERVAL;
+            (self.report)(self.nodes);
+        }
+    }
+}
+
+/// Information needed to take back a move made with [`Position::do_move`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    mov: Move,
+    piece: Piece,
+    captured: Option<(Piece, Square)>,
+    castling: CastlingRights,
+    en_passant: Option<Square>,
+    halfclock: u16,
+    fullmove: u16,
+    zobrist: u64,
+    last_move: Option<Move>,
+}
+
+/// Information needed to take back a null move made with [`Position::make_null_move`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullUndo {
+    en_passant: Option<Square>,
+    halfclock: u16,
+    fullmove: u16,
+    zobrist: u64,
+    last_move: Option<Move>,
+}
+
+/// Checks and pins against the king of one color, computed once per position so that the
+/// legality of each pseudo-legal move comes down to a few mask tests.
+struct Legality {
+    king: u64,
+    checkers: u64,
+    /// Squares a piece other than the king must move to: the checker and the squares between
+    /// it and the king. Every square when not in check.
+    evasions: u64,
+    /// Squares attacked by the opponent, which the king may not move to.
+    king_danger: u64,
+    /// Pinned pieces with the squares they may move to: along the pin up to the pinner.
+    pins: [(u64, u64); 8],
+    pin_count: usize,
+}
+
+impl Legality {
+    fn pin_ray(&self, src: u64) -> Option<u64> {
+        self.pins[..self.pin_count]
+            .iter()
+            .find(|(pinned, _)| pinned & src != 0)
+            .map(|&(_, ray)| ray)
+    }
+}
+
+pub(crate) fn endpoints(mov: Move) -> (Square, Square) {
+    match mov {
+        Move::Regular(regular) => (regular.src, regular.dst),
+        Move::Promotion(promotion) => (promotion.src, promotion.dst),
+        Move::Castling(castling) => (castling.src, castling.dst),
+    }
+}
+
+/// Returns the squares from `a` to `b` inclusive, which must lie on the same rank.
+fn span(a: Square, b: Square) -> u64 {
+    let (a, b) = (a as usize, b as usize);
+    let (low, high) = (a.min(b), b.max(a));
+    (u64::MAX >> (63 - high)) & (u64::MAX << low)
+}
+
+/// Returns the destination squares of the king and the rook taking part in castling.
+fn castling_destinations(mov: CastlingMove) -> (Square, Square) {
+    let rank = mov.src.rank();
+    let (king, rook) = if mov.dst.file() > mov.src.file() {
+        (File::G, File::F)
+    } else {
+        (File::C, File::D)
+    };
+    (Square::at(king, rank), Square::at(rook, rank))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gives_check_matches_make_move() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "1r3kr1/pppppppp/8/8/8/8/PPPPPPPP/1R3KR1 w GBgb - 0 1",
+            "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+            "8/8/8/1k6/3Pp3/8/8/4KQ2 b - d3 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let replies: Vec<Move> = board.color_moves(board.active).collect();
+            for reply in replies {
+                let mut position = board.position();
+                position.do_move(reply);
+                assert_eq!(
+                    board.gives_check(reply),
+                    position.is_king_attacked(position.active),
+                    "{fen} {reply:?}"
+                );
+                for mov in position.color_moves(position.active) {
+                    let mut after = position;
+                    after.do_move(mov);
+                    assert_eq!(
+                        position.gives_check(mov),
+                        after.is_king_attacked(after.active),
+                        "{fen} {reply:?} {mov:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_moves_into_matches_color_moves() {
+        let mut moves = MoveList::new();
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "1r3kr1/pppppppp/8/8/8/8/PPPPPPPP/1R3KR1 w GBgb - 0 1",
+            "R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            board.generate_moves_into(&mut moves);
+            let expected: Vec<Move> = board.color_moves(board.active).collect();
+            assert_eq!(&moves[..], &expected[..], "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_legality_matches_playing_out() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "8/8/8/KPp4r/8/8/8/7k w - c6 0 1",
+            "4k3/8/8/8/1b6/8/3P4/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/4r3/R3K2q w - - 0 1",
+            "4k3/8/5n2/8/8/8/4r3/4K3 w - - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let legality = board.legality(board.active);
+            for piece in Piece::iter() {
+                for src in board.squares(board.active, piece) {
+                    let mask = board
+                        .pseudo_moves_mask(board.active, piece, src)
+                        .unwrap_or(0);
+                    for dst in (0..64).filter(|dst| mask & 1 << dst != 0) {
+                        let dst = Square::from_index(dst).unwrap();
+                        assert_eq!(
+                            board.is_legal(&legality, board.active, piece, src, dst),
+                            !board.leaves_king_attacked(board.active, piece, src, dst),
+                            "{fen} {src}{dst}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_selection_cache() {
+        let mut board = Board::default();
+        let first = board.selection(Square::E2).clone();
+        assert_eq!(board.selection(Square::E2), &first);
+        assert_eq!(board.selected.as_ref(), Some(&first));
+
+        board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+        assert!(board.selected.is_none());
+        assert!(!first.is_current(&board));
+        assert_eq!(board.selection(Square::E2).moves(), []);
+
+        // Moves made on the position directly are caught by the key
+        let knight = board.selection(Square::G8).clone();
+        board.do_move(Move::from_lan("g8f6").unwrap());
+        assert!(!knight.is_current(&board));
+        assert_eq!(board.selection(Square::G8).moves(), []);
+    }
+
+    #[test]
+    fn test_fen_roundtrip() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 12 37",
+            "1r3kr1/pppppppp/8/8/8/8/PPPPPPPP/1R3KR1 w GBgb - 0 1",
+            "1r3kr1/pppppppp/8/8/8/8/PPPPPPPP/1R3KR1 b Gb - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.to_fen(), fen);
+            let reparsed = Board::from_fen(&board.to_fen()).unwrap();
+            assert_eq!(reparsed.zobrist, board.zobrist);
+            assert_eq!(reparsed.castling_rooks, board.castling_rooks);
+            assert_eq!(reparsed.chess960, board.chess960);
+        }
+    }
+
+    #[test]
+    fn test_diagram_lines_match_debug() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let debug = format!("{board:#?}");
+        assert_eq!(
+            board.to_diagram_lines().to_vec(),
+            debug.lines().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_filter_legal_matches_make_move() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let candidates: Vec<Move> = Square::iter()
+                .flat_map(|src| Square::iter().map(move |dst| (src, dst)))
+                .flat_map(|(src, dst)| {
+                    let mut moves = PromotionMove::all(src, dst);
+                    moves.push(Move::Regular(RegularMove { src, dst }));
+                    moves
+                })
+                .collect();
+            let expected: Vec<Move> = candidates
+                .iter()
+                .copied()
+                .filter(|&mov| board.clone().make_move(mov).is_ok())
+                .collect();
+            assert_eq!(board.filter_legal(candidates), expected, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_move_counters() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 7 40").unwrap();
+        assert_eq!(board.halfmove_clock(), 7);
+        assert_eq!(board.fullmove_number(), 40);
+
+        board.make_move(Move::from_lan("e8d7").unwrap()).unwrap();
+        assert_eq!(board.halfmove_clock(), 8);
+        assert_eq!(board.fullmove_number(), 41);
+        board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+        assert_eq!(board.fullmove_number(), 41);
+        assert_eq!(board.to_fen(), "8/3k4/8/8/4P3/8/8/4K3 b - e3 0 41");
+
+        // Both counters are optional in FEN
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - -").unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+        assert_eq!(board.fullmove_number(), 1);
+    }
+
+    #[test]
+    fn test_equal_positions_hash_alike() {
+        extern crate std;
+        use std::collections::HashSet;
+
+        let mut seen = HashSet::new();
+        for line in [["e2e4", "e7e5", "g1f3"], ["g1f3", "e7e5", "e2e4"]] {
+            let mut board = Board::default();
+            for mov in line {
+                board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+            }
+            seen.insert(board);
+        }
+        assert_eq!(seen.len(), 1);
+
+        // Castling rights with different rooks are different positions in Chess960
+        let a_rook = Board::from_fen("4k3/8/8/8/8/8/8/RR2K3 w A - 0 1").unwrap();
+        let b_rook = Board::from_fen("4k3/8/8/8/8/8/8/RR2K3 w B - 0 1").unwrap();
+        assert_eq!(a_rook.castling, b_rook.castling);
+        assert_ne!(a_rook, b_rook);
+        assert_ne!(Board::default(), a_rook);
+    }
+
+    #[test]
+    fn test_null_move() {
+        let mut board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 3 20").unwrap();
+        let before = board.clone();
+        let undo = board.make_null_move().unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/4P3/8/8/4K3 w - - 4 21");
+        assert_eq!(board.zobrist, board.compute_zobrist());
+        assert_eq!(board.last_move(), None);
+
+        board.undo_null_move(undo);
+        assert_eq!(board.to_fen(), before.to_fen());
+        assert_eq!(board.zobrist, before.zobrist);
+    }
+
+    #[test]
+    fn test_do_undo_move_restores_position() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+        ] {
+            let mut board = Board::from_fen(fen).unwrap();
+            let moves: Vec<Move> = board.color_moves(board.active).collect();
+            for mov in moves {
+                let before = board.clone();
+                let undo = board.do_move(mov);
+                assert_eq!(board.zobrist, board.compute_zobrist(), "{mov:?}");
+                board.undo_move(undo);
+                assert_eq!(board.bitboards, before.bitboards, "{mov:?}");
+                assert_eq!(board.active, before.active);
+                assert_eq!(board.castling, before.castling);
+                assert_eq!(board.en_passant, before.en_passant);
+                assert_eq!(board.halfclock, before.halfclock);
+                assert_eq!(board.fullmove, before.fullmove);
+                assert_eq!(board.zobrist, before.zobrist);
+                assert_eq!(board.last_move, before.last_move);
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_highlights() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+        let highlights = Highlights {
+            last_move: Some((Square::A1, Square::E1)),
+            check: Some(Square::E1),
+            marked: 1 << Square::E8 as usize,
+            arrows: Vec::new(),
+        };
+        let lines = |options| {
+            let text = board
+                .display(options)
+                .with_highlights(highlights.clone())
+                .to_string();
+            text.lines().map(String::from).collect::<Vec<String>>()
+        };
+
+        // The check takes precedence over the last move on the same square
+        let plain = lines(DisplayOptions::default());
+        assert_eq!(plain[0], ". . . . k+. . . ");
+        assert_eq!(plain[7], "R*. . . K!. . . ");
+
+        let ansi = lines(DisplayOptions {
+            ansi: true,
+            ..DisplayOptions::default()
+        });
+        assert_eq!(ansi[0], ". . . . \x1b[44mk\x1b[0m . . . ");
+        assert_eq!(ansi[7], "\x1b[43mR\x1b[0m . . . \x1b[41mK\x1b[0m . . . ");
+        assert_eq!(ansi[3], plain[3]);
+    }
+
+    #[test]
+    fn test_position_size() {
+        // Small enough to copy a position per ply, and copies leave the board untouched
+        assert!(core::mem::size_of::<Position>() <= 128);
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let mut position = board.position();
+        position.do_move(Move::from_lan("h1h8").unwrap());
+        assert!(position.is_check(Color::Black));
+        assert!(!board.is_check(Color::Black));
+        assert_eq!(format!("{:#?}", board.position()), format!("{board:#?}"));
+    }
+
+    #[test]
+    fn test_last_move_is_resolved() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        board.make_move(Move::from_lan("e1g1").unwrap()).unwrap();
+        let castling = Move::Castling(CastlingMove {
+            src: Square::E1,
+            dst: Square::H1,
+        });
+        assert_eq!(board.last_move(), Some(castling));
+        assert_eq!(board.played_moves(), [castling]);
+
+        // A board started from the position keeps the last move but not the list
+        let started = Board::from(board.position());
+        assert_eq!(started.last_move(), Some(castling));
+        assert!(started.played_moves().is_empty());
+    }
+
+    #[test]
+    fn test_capturing_castling_rook_clears_right() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        board.make_move(Move::from_lan("a8a1").unwrap()).unwrap();
+        assert_eq!(
+            board.castling(),
+            CastlingRights::WHITE_KINGSIDE | CastlingRights::BLACK_KINGSIDE
+        );
+        assert_eq!(board.to_fen(), "4k2r/8/8/8/8/8/8/r3K2R w Kk - 0 2");
+
+        // The position is the same as if it had been loaded with the correct rights
+        let reloaded = Board::from_fen(&board.to_fen()).unwrap();
+        assert_eq!(board.zobrist, reloaded.zobrist);
+    }
+
+    #[test]
+    fn test_recompute_castling_rights() {
+        let mut board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        board.remove_piece(Color::White, Piece::Rook, Square::H1);
+        board.remove_piece(Color::Black, Piece::King, Square::E8);
+        board.add_piece(Color::Black, Piece::King, Square::E7);
+        assert_eq!(
+            board.recompute_castling_rights(),
+            CastlingRights::WHITE_KINGSIDE
+                | CastlingRights::BLACK_KINGSIDE
+                | CastlingRights::BLACK_QUEENSIDE
+        );
+        assert_eq!(board.castling(), CastlingRights::WHITE_QUEENSIDE);
+        assert_eq!(board.zobrist, board.compute_zobrist());
+        assert_eq!(board.recompute_castling_rights(), CastlingRights::empty());
+
+        // Rights without a king on its back rank are dropped when loading
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/4K3/R6R w KQkq - 0 1").unwrap();
+        assert_eq!(
+            board.castling(),
+            CastlingRights::BLACK_KINGSIDE | CastlingRights::BLACK_QUEENSIDE
+        );
+    }
+
+    #[test]
+    fn test_attackers_match_is_square_attacked() {
+        for position in crate::positions::ALL {
+            let board = position.board();
+            for square in Square::iter() {
+                for color in Color::iter() {
+                    let attackers = board.attackers(square, color);
+                    assert_eq!(
+                        !attackers.is_empty(),
+                        board.is_square_attacked(color.opposite(), square),
+                        "{} {square:?} {color:?}",
+                        position.fen
+                    );
+                    let own = board.bitboard(color, Piece::Pawn)
+                        | board.bitboard(color, Piece::Knight)
+                        | board.bitboard(color, Piece::Bishop)
+                        | board.bitboard(color, Piece::Rook)
+                        | board.bitboard(color, Piece::Queen)
+                        | board.bitboard(color, Piece::King);
+                    assert!((attackers & !own).is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_attacked_squares_match_attackers() {
+        for position in crate::positions::ALL {
+            let board = position.board();
+            for color in Color::iter() {
+                let expected: Bitboard = Square::iter()
+                    .filter(|&square| !board.attackers(square, color).is_empty())
+                    .collect();
+                assert_eq!(board.attacked_squares(color), expected, "{}", position.fen);
+            }
+        }
     }
 }
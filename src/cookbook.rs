@@ -0,0 +1,41 @@
+//! Complete programs built on the crate, from the `examples` directory of the repository.
+//!
+//! Each recipe runs with `cargo run --example <name>` and is compiled by `cargo test`, both as
+//! an example and as the doctest below, so the recipes stay in sync with the API.
+//!
+//! | Recipe                 | Shows                                                    |
+//! |------------------------|----------------------------------------------------------|
+//! | [`legal_move_server`]  | FEN and LAN input, legal moves, SAN and the wire format  |
+//! | [`pgn_analyzer`]       | SAN parsing, searching every position of a game          |
+//! | [`perft_validator`]    | Perft on the standard [`positions`](crate::positions)    |
+//! | [`simple_bot`]         | Opening randomization, timed search and [`Game`] to PGN  |
+//!
+//! [`legal_move_server`]: self#legal_move_server
+//! [`pgn_analyzer`]: self#pgn_analyzer
+//! [`perft_validator`]: self#perft_validator
+//! [`simple_bot`]: self#simple_bot
+//! [`Game`]: crate::game::Game
+//!
+//! ## `legal_move_server`
+//!
+//! ```no_run
+#![doc = include_str!("../examples/legal_move_server.rs")]
+//! ```
+//!
+//! ## `pgn_analyzer`
+//!
+//! ```no_run
+#![doc = include_str!("../examples/pgn_analyzer.rs")]
+//! ```
+//!
+//! ## `perft_validator`
+//!
+//! ```no_run
+#![doc = include_str!("../examples/perft_validator.rs")]
+//! ```
+//!
+//! ## `simple_bot`
+//!
+//! ```no_run
+#![doc = include_str!("../examples/simple_bot.rs")]
+//! ```
@@ -0,0 +1,108 @@
+//! Correction history for the search: how far the static evaluation turned out to be from the
+//! search results in positions with the same pawn structure. The evaluation misjudges some
+//! structures consistently, e.g. passed pawns it does not know about, and the search corrects
+//! its static evaluations by what it learnt.
+
+use crate::board::Position;
+use crate::notation::{Color, Piece};
+use crate::zobrist::KEYS;
+
+/// Number of pawn structures per color, a power of two.
+const SIZE: usize = 1 << 14;
+
+/// Corrections are kept in 1/GRAIN centipawns, so that the moving averages stay precise.
+const GRAIN: i32 = 256;
+
+/// Largest correction, in centipawns.
+const MAX_CORRECTION: i32 = 256;
+
+/// Weights of the moving averages are fractions of this.
+const WEIGHT_SCALE: i32 = 256;
+
+/// Average error of the static evaluation by side to move and pawn structure.
+pub(crate) struct CorrectionHistory {
+    corrections: [[i32; SIZE]; 2],
+}
+
+impl CorrectionHistory {
+    pub(crate) fn new() -> Self {
+        CorrectionHistory {
+            corrections: [[0; SIZE]; 2],
+        }
+    }
+
+    /// Returns the static evaluation `eval` of the position corrected by the history.
+    pub(crate) fn correct(&self, board: &Position, eval: i32) -> i32 {
+        eval + *self.entry(board) / GRAIN
+    }
+
+    /// Learns that a search `depth` plies deep scored the position `score`, against a static
+    /// evaluation of `eval` before correction. Deeper searches weigh more.
+    pub(crate) fn update(&mut self, board: &Position, depth: usize, eval: i32, score: i32) {
+        let limit = MAX_CORRECTION * GRAIN;
+        let error = ((score - eval) * GRAIN).clamp(-limit, limit);
+        let weight = (depth as i32 + 1).min(16);
+        let entry = self.entry_mut(board);
+        *entry = (*entry * (WEIGHT_SCALE - weight) + error * weight) / WEIGHT_SCALE;
+    }
+
+    fn entry(&self, board: &Position) -> &i32 {
+        &self.corrections[board.active() as usize][pawn_key(board) as usize % SIZE]
+    }
+
+    fn entry_mut(&mut self, board: &Position) -> &mut i32 {
+        &mut self.corrections[board.active() as usize][pawn_key(board) as usize % SIZE]
+    }
+}
+
+/// Zobrist key of the pawns of both colors alone.
+fn pawn_key(board: &Position) -> u64 {
+    let mut key = 0;
+    for color in [Color::White, Color::Black] {
+        let mut pawns = board.bitboard(color, Piece::Pawn);
+        while pawns != 0 {
+            let square = pawns.trailing_zeros() as usize;
+            key ^= KEYS.pieces[color as usize][Piece::Pawn as usize][square];
+            pawns &= pawns - 1;
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn test_corrections() {
+        let board = Board::from_fen("4k3/8/8/3p4/3P4/8/8/4K3 w - - 0 1").unwrap();
+        let mut history = CorrectionHistory::new();
+        assert_eq!(history.correct(&board, 10), 10);
+
+        // The error is learnt gradually, and only for the side to move
+        for _ in 0..100 {
+            history.update(&board, 10, 10, 60);
+        }
+        let corrected = history.correct(&board, 10);
+        assert!(corrected > 50 && corrected <= 60, "{corrected}");
+        let black = Board::from_fen("4k3/8/8/3p4/3P4/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(history.correct(&black, 10), 10);
+
+        // Pieces do not matter, the pawns do
+        let rook = Board::from_fen("4k3/8/8/3p4/3P4/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(history.correct(&rook, 10), corrected);
+        let pawns = Board::from_fen("4k3/8/8/3p4/2P5/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(history.correct(&pawns, 10), 10);
+    }
+
+    #[test]
+    fn test_corrections_are_bounded() {
+        let board = Board::default();
+        let mut history = CorrectionHistory::new();
+        for _ in 0..1000 {
+            history.update(&board, 20, 0, 30_000);
+        }
+        assert!(history.correct(&board, 0) <= MAX_CORRECTION);
+    }
+}
@@ -0,0 +1,138 @@
+//! Cuckoo tables of the reversible moves, for detecting that a position can be repeated with a
+//! single move (Marcel van Kervinck, "The design of Rookie 3").
+//!
+//! Every move of a knight, bishop, rook, queen or king between two squares is stored under
+//! the difference it makes to the Zobrist key, which includes the change of side to move. If
+//! the keys of the current position and an earlier one differ by such a move, and nothing
+//! stands between its squares, the earlier position is one move away.
+
+use crate::notation::Square;
+use crate::zobrist::KEYS;
+
+const SIZE: usize = 8192;
+
+/// Number of reversible moves: the same squares joined by the same piece of the same color
+/// count once, whichever way the piece moves.
+#[cfg(test)]
+const MOVES: usize = 3668;
+
+struct Cuckoo {
+    keys: [u64; SIZE],
+    /// Squares of the move stored under the key, as indices.
+    moves: [(u8, u8); SIZE],
+}
+
+static CUCKOO: Cuckoo = generate();
+
+/// Returns the squares of the reversible move that changes a Zobrist key by `key`, if any.
+pub(crate) fn lookup(key: u64) -> Option<(Square, Square)> {
+    for slot in [first_slot(key), second_slot(key)] {
+        if CUCKOO.keys[slot] == key {
+            let (a, b) = CUCKOO.moves[slot];
+            return Some((
+                Square::from_index(a as usize)?,
+                Square::from_index(b as usize)?,
+            ));
+        }
+    }
+    None
+}
+
+const fn first_slot(key: u64) -> usize {
+    key as usize & (SIZE - 1)
+}
+
+const fn second_slot(key: u64) -> usize {
+    (key >> 16) as usize & (SIZE - 1)
+}
+
+/// Returns whether the piece, indexed as in [`Piece`](crate::notation::Piece), moves between
+/// the two squares on an empty board. Pawns never do, as their moves are irreversible.
+const fn reaches(piece: usize, a: usize, b: usize) -> bool {
+    let files = (a % 8).abs_diff(b % 8);
+    let ranks = (a / 8).abs_diff(b / 8);
+    let straight = files == 0 || ranks == 0;
+    let diagonal = files == ranks;
+    match piece {
+        1 => straight,
+        2 => (files == 1 && ranks == 2) || (files == 2 && ranks == 1),
+        3 => diagonal,
+        4 => straight || diagonal,
+        5 => files <= 1 && ranks <= 1,
+        _ => false,
+    }
+}
+
+const fn generate() -> Cuckoo {
+    let mut cuckoo = Cuckoo {
+        keys: [0; SIZE],
+        moves: [(0, 0); SIZE],
+    };
+
+    let mut color = 0;
+    while color < 2 {
+        let mut piece = 1;
+        while piece < 6 {
+            let mut a = 0;
+            while a < 64 {
+                let mut b = a + 1;
+                while b < 64 {
+                    if reaches(piece, a, b) {
+                        let keys = &KEYS.pieces[color][piece];
+                        let key = keys[a] ^ keys[b] ^ KEYS.black_to_move;
+                        insert(&mut cuckoo, key, (a as u8, b as u8));
+                    }
+                    b += 1;
+                }
+                a += 1;
+            }
+            piece += 1;
+        }
+        color += 1;
+    }
+
+    cuckoo
+}
+
+/// Inserts the move in its first slot, moving the entry already there to its other slot and
+/// so on until an empty slot is found.
+const fn insert(cuckoo: &mut Cuckoo, mut key: u64, mut squares: (u8, u8)) {
+    let mut slot = first_slot(key);
+    let mut evictions = 0;
+    while evictions < SIZE {
+        let evicted = (cuckoo.keys[slot], cuckoo.moves[slot]);
+        cuckoo.keys[slot] = key;
+        cuckoo.moves[slot] = squares;
+        if evicted.0 == 0 {
+            return;
+        }
+        (key, squares) = evicted;
+        slot = if slot == first_slot(key) {
+            second_slot(key)
+        } else {
+            first_slot(key)
+        };
+        evictions += 1;
+    }
+    panic!("the cuckoo table is too small for the reversible moves");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::{Color, Piece};
+
+    #[test]
+    fn test_every_reversible_move_is_found() {
+        let stored = CUCKOO.keys.iter().filter(|&&key| key != 0).count();
+        assert_eq!(stored, MOVES);
+
+        let knight = KEYS.pieces[Color::Black as usize][Piece::Knight as usize];
+        let key = knight[Square::G8 as usize] ^ knight[Square::F6 as usize] ^ KEYS.black_to_move;
+        assert_eq!(lookup(key), Some((Square::F6, Square::G8)));
+
+        let bishop = KEYS.pieces[Color::White as usize][Piece::Bishop as usize];
+        let key = bishop[Square::C1 as usize] ^ bishop[Square::C3 as usize] ^ KEYS.black_to_move;
+        assert_eq!(lookup(key), None);
+    }
+}
@@ -0,0 +1,303 @@
+//! Facilities for running shax as an engine behind a frontend, such as a GUI or a
+//! tournament manager.
+
+pub mod meta;
+pub mod options;
+
+#[cfg(feature = "std")]
+use crate::board::Board;
+#[cfg(feature = "std")]
+use crate::notation::Move;
+#[cfg(feature = "std")]
+use crate::search::{Score, SearchLimits, SearchResult, SearchState};
+#[cfg(feature = "std")]
+use crate::stop::StopToken;
+#[cfg(feature = "std")]
+use options::{OptionError, Options};
+
+/// Engine playing one game after another from a position it is given, the simplest way to get
+/// a move out of shax:
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::engine::Engine;
+/// use shax::search::Score;
+///
+/// let mut engine = Engine::new();
+/// engine.set_position(Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap());
+/// let (mov, score) = engine.best_move(&"depth 3".parse().unwrap()).unwrap();
+/// assert_eq!(mov.to_lan(), "a1a8");
+/// assert_eq!(score, Score::Mate(1));
+/// ```
+///
+/// Searches keep what they learn for the next moves of the game: the transposition table, the
+/// killer moves and history scores of the move ordering, and the correction history of the
+/// static evaluation. [`Engine::new_game`] forgets it all between games, so that the results
+/// of a game do not depend on the games played before.
+///
+/// ```
+/// use shax::engine::Engine;
+///
+/// let mut engine = Engine::new();
+/// let limits = "depth 4".parse().unwrap();
+/// let first = engine.analyze(&limits, |_, _| {});
+///
+/// // ucinewgame
+/// engine.new_game();
+/// let again = engine.analyze(&limits, |_, _| {});
+/// assert_eq!((&again[0].pv, again[0].nodes), (&first[0].pv, first[0].nodes));
+/// ```
+#[cfg(feature = "std")]
+pub struct Engine {
+    options: Options,
+    board: Board,
+    state: SearchState,
+    stop: StopToken,
+}
+
+#[cfg(feature = "std")]
+impl Engine {
+    /// Creates an engine with the built-in options at their defaults, at the starting
+    /// position.
+    pub fn new() -> Self {
+        Self::with_options(Options::new())
+    }
+
+    /// Creates an engine with the given options. Searches use a transposition table of `Hash`
+    /// megabytes, `Threads` threads, search `MultiPV` lines, and keep `Move Overhead` on the
+    /// clock unless the time control sets its own. With `Keep Hash` off, each search starts
+    /// from an empty transposition table.
+    pub fn with_options(options: Options) -> Self {
+        Engine {
+            state: SearchState::new(options.hash()),
+            options,
+            board: Board::default(),
+            stop: StopToken::new(),
+        }
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Sets an option from its textual value, as [`Options::set`] does. Changing `Hash`
+    /// replaces the transposition table with an empty one of the new size.
+    ///
+    /// ```
+    /// use shax::engine::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_option("Threads", "2").unwrap();
+    /// engine.set_option("Hash", "1").unwrap();
+    /// assert_eq!(engine.options().threads(), 2);
+    /// assert!(engine.set_option("Hash", "0").is_err());
+    /// ```
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        self.options.set(name, value)?;
+        self.state.resize(self.options.hash());
+        Ok(())
+    }
+
+    /// Handles a UCI `setoption name <id> [value <x>]` command, as [`Options::setoption`]
+    /// does. Changing `Hash` replaces the transposition table as [`Engine::set_option`] does.
+    pub fn setoption(&mut self, command: &str) -> Result<(), OptionError> {
+        self.options.setoption(command)?;
+        self.state.resize(self.options.hash());
+        Ok(())
+    }
+
+    /// Returns the position searched by the engine.
+    pub fn position(&self) -> &Board {
+        &self.board
+    }
+
+    /// Sets the position to search, the equivalent of the UCI `position` command. The moves
+    /// played to reach it are kept by the board, for the search to see repetitions.
+    pub fn set_position(&mut self, board: Board) {
+        self.board = board;
+    }
+
+    /// Returns a token stopping the searches of the engine, for stopping one from another
+    /// thread or passing on a `ponderhit` with [`StopToken::ponderhit`]. Each search clears it
+    /// before starting.
+    pub fn stop_token(&self) -> StopToken {
+        self.stop.clone()
+    }
+
+    /// Prepares the engine for a new game from the starting position, the equivalent of the UCI
+    /// `ucinewgame` command: the transposition table, killer moves, history scores and
+    /// correction history are cleared.
+    pub fn new_game(&mut self) {
+        self.board = Board::default();
+        self.state.clear();
+    }
+
+    /// Searches the position within the limits and returns the best move with its score,
+    /// [`None`] if the game is over.
+    pub fn best_move(&mut self, limits: &SearchLimits) -> Option<(Move, Score)> {
+        let result = self.analyze(limits, |_, _| {}).swap_remove(0);
+        Some((result.best_move?, result.score))
+    }
+
+    /// Searches the position within the limits and returns the `MultiPV` best lines, best
+    /// first, or fewer if there are fewer legal moves. The first line is the result of the
+    /// search as a whole, with a move unless the game is over.
+    ///
+    /// After each completed iteration, `progress` is called for each line with its number
+    /// counted from 1, as [`SearchResult::info`] takes it.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::engine::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_option("MultiPV", "3").unwrap();
+    /// engine.set_position(Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap());
+    /// let lines = engine.analyze(&"depth 3".parse().unwrap(), |multipv, line| {
+    ///     println!("{}", line.info(multipv));
+    /// });
+    /// assert_eq!(lines.len(), 3);
+    /// assert_eq!(lines[0].pv[0].to_lan(), "d2d5");
+    /// ```
+    pub fn analyze(
+        &mut self,
+        limits: &SearchLimits,
+        mut progress: impl FnMut(usize, &SearchResult),
+    ) -> Vec<SearchResult> {
+        let mut limits = limits.clone();
+        limits
+            .time
+            .move_overhead
+            .get_or_insert(self.options.move_overhead());
+        self.stop.reset();
+        if !self.options.keep_hash() {
+            self.state.clear_tt();
+        }
+        self.state.search(
+            &self.board,
+            &limits,
+            &self.stop,
+            self.options.threads(),
+            self.options.multipv(),
+            &mut progress,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::tt::TranspositionTable;
+
+    #[test]
+    fn test_new_game() {
+        let limits: SearchLimits = "depth 5".parse().unwrap();
+        let mut engine = Engine::new();
+        let fresh = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+
+        // The table filled by the first search makes the second one cheaper
+        let warm = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+        assert!(warm.nodes < fresh.nodes);
+
+        engine.set_position(Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap());
+        engine.new_game();
+        assert_eq!(engine.position(), &Board::default());
+        let cleared = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+        assert_eq!(cleared.nodes, fresh.nodes);
+        assert_eq!(cleared.pv, fresh.pv);
+    }
+
+    #[test]
+    fn test_keep_hash() {
+        let limits: SearchLimits = "depth 5".parse().unwrap();
+        let mut engine = Engine::new();
+        engine.analyze(&limits, |_, _| {});
+        let warm = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+
+        // Without the table, only the move ordering carries over
+        let mut engine = Engine::new();
+        engine.set_option("Keep Hash", "false").unwrap();
+        engine.analyze(&limits, |_, _| {});
+        let cold = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+        assert!(cold.nodes > warm.nodes);
+    }
+
+    #[test]
+    fn test_options() {
+        let mut engine = Engine::new();
+        engine.setoption("setoption name Hash value 1").unwrap();
+        assert_eq!(engine.options().hash(), 1);
+        assert_eq!(
+            engine.state.tt_capacity(),
+            TranspositionTable::new(1).capacity()
+        );
+        assert!(engine.set_option("Threads", "0").is_err());
+        assert_eq!(engine.options().threads(), 1);
+
+        // The helper threads add their nodes to the result
+        let limits: SearchLimits = "depth 4".parse().unwrap();
+        let single = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+        engine.new_game();
+        engine.set_option("Threads", "3").unwrap();
+        let parallel = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+        assert!(parallel.nodes > single.nodes);
+
+        // The whole clock is kept as overhead, leaving no time to search more than depth 1
+        engine.set_option("Threads", "1").unwrap();
+        engine.set_option("Move Overhead", "5000").unwrap();
+        let limits: SearchLimits = "wtime 5000 btime 5000".parse().unwrap();
+        let result = engine.analyze(&limits, |_, _| {}).swap_remove(0);
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn test_multipv() {
+        let mut engine = Engine::new();
+        engine.set_option("MultiPV", "4").unwrap();
+        engine.set_position(Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap());
+        let mut reports = Vec::new();
+        let lines = engine.analyze(&"depth 3".parse().unwrap(), |multipv, line| {
+            reports.push((line.depth, multipv, line.best_move))
+        });
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].best_move, Some(Move::from_lan("d2d5").unwrap()));
+        for pair in lines.windows(2) {
+            assert_ne!(pair[0].best_move, pair[1].best_move);
+            assert!(matches!(
+                (pair[0].score, pair[1].score),
+                (Score::Centipawns(first), Score::Centipawns(second)) if first >= second
+            ));
+        }
+        let last: Vec<_> = reports.iter().filter(|report| report.0 == 3).collect();
+        assert_eq!(last.len(), 4);
+        for (index, (line, report)) in lines.iter().zip(last).enumerate() {
+            assert_eq!((report.1, report.2), (index + 1, line.best_move));
+        }
+
+        // With fewer legal moves than lines, every move is a line
+        engine.set_position(Board::from_fen("7k/8/8/8/8/6p1/6P1/7K w - - 0 1").unwrap());
+        let lines = engine.analyze(&"depth 2".parse().unwrap(), |_, _| {});
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].best_move, Some(Move::from_lan("h1g1").unwrap()));
+    }
+
+    #[test]
+    fn test_best_move() {
+        let mut engine = Engine::new();
+        let limits: SearchLimits = "depth 3".parse().unwrap();
+        engine.set_position(Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap());
+        let (mov, score) = engine.best_move(&limits).unwrap();
+        assert_eq!(mov, Move::from_lan("d2d5").unwrap());
+        assert!(matches!(score, Score::Centipawns(cp) if cp > 0));
+
+        engine.set_position(Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap());
+        assert_eq!(engine.best_move(&limits), None);
+    }
+}
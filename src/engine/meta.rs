@@ -0,0 +1,97 @@
+//! Identity of the engine build: name, version, authors and the git commit it was built
+//! from, for the UCI `id` lines, bench signatures and PGN tags.
+//!
+//! ```
+//! use shax::engine::meta;
+//!
+//! assert_eq!(meta::NAME, "shax");
+//! assert!(meta::id_name().starts_with("shax 0."));
+//! ```
+
+use crate::pgn::Pgn;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Name of the engine.
+pub const NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Version of the engine, from `Cargo.toml`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Authors of the engine, separated by colons as in the Cargo metadata.
+pub const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
+
+/// Abbreviated hash of the git commit the engine was built from, [`None`] for builds outside
+/// a git checkout.
+pub const GIT_HASH: Option<&str> = option_env!("SHAX_GIT_HASH");
+
+/// Returns the name and version of the build, with the commit when it is known:
+/// `shax 0.1.2 (1a2b3c4d)`.
+pub fn id_name() -> String {
+    match GIT_HASH {
+        Some(hash) => format!("{NAME} {VERSION} ({hash})"),
+        None => format!("{NAME} {VERSION}"),
+    }
+}
+
+/// Returns the names of the authors, without their email addresses.
+///
+/// ```
+/// use shax::engine::meta;
+///
+/// assert!(meta::authors().all(|author| !author.contains('<')));
+/// ```
+pub fn authors() -> impl Iterator<Item = &'static str> {
+    AUTHORS
+        .split(':')
+        .map(|author| author.split('<').next().unwrap_or(author).trim())
+        .filter(|author| !author.is_empty())
+}
+
+/// Returns the `id name` and `id author` lines answering the UCI `uci` command.
+///
+/// ```
+/// use shax::engine::meta;
+///
+/// let [name, author] = meta::uci_id();
+/// assert_eq!(name, format!("id name {}", meta::id_name()));
+/// assert!(author.starts_with("id author "));
+/// ```
+pub fn uci_id() -> [String; 2] {
+    let authors: Vec<&str> = authors().collect();
+    [
+        format!("id name {}", id_name()),
+        format!("id author {}", authors.join(", ")),
+    ]
+}
+
+/// Returns the signature of a bench run: the build with the number of nodes searched, which
+/// changes whenever the search or move generation behaves differently.
+///
+/// ```
+/// use shax::engine::meta;
+///
+/// assert_eq!(
+///     meta::bench_signature(4865609),
+///     format!("{}: 4865609 nodes", meta::id_name())
+/// );
+/// ```
+pub fn bench_signature(nodes: u64) -> String {
+    format!("{}: {nodes} nodes", id_name())
+}
+
+/// Records the build as the `Annotator` of a game, for games played or analyzed by the
+/// engine.
+///
+/// ```
+/// use shax::engine::meta;
+/// use shax::pgn::Pgn;
+///
+/// let mut pgn = Pgn::new();
+/// meta::annotate(&mut pgn);
+/// assert_eq!(pgn.tag("Annotator"), Some(meta::id_name().as_str()));
+/// ```
+pub fn annotate(pgn: &mut Pgn) {
+    pgn.set_tag("Annotator", &id_name());
+}
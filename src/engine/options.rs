@@ -0,0 +1,500 @@
+//! Registry of the options an engine announces to the frontend and lets it change with the
+//! UCI `setoption` command.
+//!
+//! [`Options::new`] registers the built-in `Hash`, `Threads`, `Move Overhead`, `Ponder`,
+//! `MultiPV` and `Keep Hash` options. Programs embedding shax can register their own options next to them.
+//!
+//! ```
+//! use shax::engine::options::{OptionType, Options};
+//!
+//! let mut options = Options::new();
+//! options
+//!     .register("Style", OptionType::combo("Solid", &["Solid", "Aggressive"]))
+//!     .unwrap();
+//!
+//! options.setoption("setoption name Hash value 64").unwrap();
+//! options.setoption("setoption name style value aggressive").unwrap();
+//! assert_eq!(options.hash(), 64);
+//! assert_eq!(options.string("Style"), Some("Aggressive"));
+//! assert!(options.setoption("setoption name Threads value 0").is_err());
+//! ```
+
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+use core::time::Duration;
+
+/// Type of an option, with its default value and the values it accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionType {
+    /// Integer in the range `min..=max`.
+    Spin { default: i64, min: i64, max: i64 },
+    /// Boolean, set with `true` or `false`.
+    Check { default: bool },
+    /// Free text.
+    String { default: String },
+    /// One of a list of predefined strings.
+    Combo {
+        default: String,
+        values: Vec<String>,
+    },
+}
+
+impl OptionType {
+    pub fn spin(default: i64, min: i64, max: i64) -> Self {
+        OptionType::Spin { default, min, max }
+    }
+
+    pub fn check(default: bool) -> Self {
+        OptionType::Check { default }
+    }
+
+    pub fn string(default: &str) -> Self {
+        OptionType::String {
+            default: default.to_owned(),
+        }
+    }
+
+    pub fn combo(default: &str, values: &[&str]) -> Self {
+        OptionType::Combo {
+            default: default.to_owned(),
+            values: values.iter().map(|&value| value.to_owned()).collect(),
+        }
+    }
+
+    fn default_value(&self) -> OptionValue {
+        match self {
+            OptionType::Spin { default, .. } => OptionValue::Spin(*default),
+            OptionType::Check { default } => OptionValue::Check(*default),
+            OptionType::String { default } | OptionType::Combo { default, .. } => {
+                OptionValue::String(default.clone())
+            }
+        }
+    }
+
+    /// Parses a value of the type, [`None`] if it is not accepted. Combo values are matched
+    /// case-insensitively and stored as registered.
+    fn parse(&self, value: &str) -> Option<OptionValue> {
+        match self {
+            OptionType::Spin { min, max, .. } => value
+                .parse()
+                .ok()
+                .filter(|value| (min..=max).contains(&value))
+                .map(OptionValue::Spin),
+            OptionType::Check { .. } => match value {
+                "true" => Some(OptionValue::Check(true)),
+                "false" => Some(OptionValue::Check(false)),
+                _ => None,
+            },
+            OptionType::String { .. } => Some(OptionValue::String(value.to_owned())),
+            OptionType::Combo { values, .. } => values
+                .iter()
+                .find(|known| known.eq_ignore_ascii_case(value))
+                .map(|known| OptionValue::String(known.clone())),
+        }
+    }
+}
+
+/// Current value of an option. Combo options hold a [`OptionValue::String`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    Spin(i64),
+    Check(bool),
+    String(String),
+}
+
+/// Option registered in [`Options`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineOption {
+    name: String,
+    kind: OptionType,
+    value: OptionValue,
+}
+
+impl EngineOption {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &OptionType {
+        &self.kind
+    }
+
+    pub fn value(&self) -> &OptionValue {
+        &self.value
+    }
+
+    /// Returns the `option` line announcing the option in answer to the UCI `uci` command.
+    ///
+    /// ```
+    /// use shax::engine::options::Options;
+    ///
+    /// let options = Options::new();
+    /// let ponder = options.get("Ponder").unwrap();
+    /// assert_eq!(ponder.uci_line(), "option name Ponder type check default false");
+    /// ```
+    pub fn uci_line(&self) -> String {
+        let name = &self.name;
+        match &self.kind {
+            OptionType::Spin { default, min, max } => {
+                format!("option name {name} type spin default {default} min {min} max {max}")
+            }
+            OptionType::Check { default } => {
+                format!("option name {name} type check default {default}")
+            }
+            OptionType::String { default } => {
+                // UCI has no way to write an empty string but this placeholder
+                let default = if default.is_empty() {
+                    "<empty>"
+                } else {
+                    default
+                };
+                format!("option name {name} type string default {default}")
+            }
+            OptionType::Combo { default, values } => {
+                let mut line = format!("option name {name} type combo default {default}");
+                for value in values {
+                    line.push_str(" var ");
+                    line.push_str(value);
+                }
+                line
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionError {
+    /// No option has the name.
+    UnknownOption(String),
+    /// An option with the name is already registered.
+    DuplicateOption(String),
+    /// The value is not accepted by the type of the option.
+    InvalidValue { name: String, value: String },
+    /// The command is not a well-formed `setoption name <id> [value <x>]`.
+    BadCommand,
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownOption(name) => write!(f, "unknown option {name:?}"),
+            Self::DuplicateOption(name) => write!(f, "option {name:?} is already registered"),
+            Self::InvalidValue { name, value } => {
+                write!(f, "invalid value {value:?} for option {name:?}")
+            }
+            Self::BadCommand => write!(f, "expected setoption name <id> [value <x>]"),
+        }
+    }
+}
+
+impl error::Error for OptionError {}
+
+/// Options of the engine, in the order they were registered. Names are matched
+/// case-insensitively, as UCI requires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    options: Vec<EngineOption>,
+}
+
+impl Options {
+    /// Size of the transposition table in megabytes.
+    pub const HASH: &str = "Hash";
+    /// Number of search threads.
+    pub const THREADS: &str = "Threads";
+    /// Milliseconds kept on the clock for communication delays.
+    pub const MOVE_OVERHEAD: &str = "Move Overhead";
+    /// Whether the frontend may send `go ponder`.
+    pub const PONDER: &str = "Ponder";
+    /// Number of best lines to search and report.
+    pub const MULTI_PV: &str = "MultiPV";
+    /// Whether searches start from the transposition table left by the previous ones.
+    pub const KEEP_HASH: &str = "Keep Hash";
+
+    /// Creates the registry with the built-in options at their defaults.
+    pub fn new() -> Self {
+        let mut options = Options {
+            options: Vec::new(),
+        };
+        for (name, kind) in [
+            (Self::HASH, OptionType::spin(16, 1, 65_536)),
+            (Self::THREADS, OptionType::spin(1, 1, 256)),
+            (Self::MOVE_OVERHEAD, OptionType::spin(20, 0, 5_000)),
+            (Self::PONDER, OptionType::check(false)),
+            (Self::MULTI_PV, OptionType::spin(1, 1, 256)),
+            (Self::KEEP_HASH, OptionType::check(true)),
+        ] {
+            options.register(name, kind).unwrap();
+        }
+        options
+    }
+
+    /// Registers an option at its default value.
+    ///
+    /// Returns [`OptionError::DuplicateOption`] if an option with the same name exists and
+    /// [`OptionError::InvalidValue`] if the default is not accepted by the type itself.
+    pub fn register(&mut self, name: &str, kind: OptionType) -> Result<(), OptionError> {
+        if self.get(name).is_some() {
+            return Err(OptionError::DuplicateOption(name.to_owned()));
+        }
+        let value = kind.default_value();
+        let valid = match (&kind, &value) {
+            (OptionType::Spin { min, max, .. }, OptionValue::Spin(default)) => {
+                (min..=max).contains(&default)
+            }
+            (OptionType::Combo { values, .. }, OptionValue::String(default)) => {
+                values.contains(default)
+            }
+            _ => true,
+        };
+        if !valid {
+            return Err(OptionError::InvalidValue {
+                name: name.to_owned(),
+                value: format!("{value:?}"),
+            });
+        }
+        self.options.push(EngineOption {
+            name: name.to_owned(),
+            kind,
+            value,
+        });
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EngineOption> {
+        self.options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EngineOption> {
+        self.options.iter()
+    }
+
+    /// Sets an option from its textual value. The option keeps its value on error.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        let option = self
+            .options
+            .iter_mut()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| OptionError::UnknownOption(name.to_owned()))?;
+        option.value = option
+            .kind
+            .parse(value)
+            .ok_or_else(|| OptionError::InvalidValue {
+                name: option.name.clone(),
+                value: value.to_owned(),
+            })?;
+        Ok(())
+    }
+
+    /// Handles a UCI `setoption name <id> [value <x>]` command. Names and values may contain
+    /// spaces; runs of whitespace in them are read as a single space.
+    pub fn setoption(&mut self, command: &str) -> Result<(), OptionError> {
+        let mut tokens = command.split_whitespace();
+        if tokens.next() != Some("setoption") || tokens.next() != Some("name") {
+            return Err(OptionError::BadCommand);
+        }
+        let tokens: Vec<&str> = tokens.collect();
+        let (name, value) = match tokens.iter().position(|&token| token == "value") {
+            Some(index) => (&tokens[..index], tokens[index + 1..].join(" ")),
+            None => (&tokens[..], String::new()),
+        };
+        if name.is_empty() {
+            return Err(OptionError::BadCommand);
+        }
+        self.set(&name.join(" "), &value)
+    }
+
+    /// Returns the value of a spin option, [`None`] if there is no such spin option.
+    pub fn spin(&self, name: &str) -> Option<i64> {
+        match self.get(name)?.value {
+            OptionValue::Spin(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a check option, [`None`] if there is no such check option.
+    pub fn check(&self, name: &str) -> Option<bool> {
+        match self.get(name)?.value {
+            OptionValue::Check(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a string or combo option, [`None`] if there is no such option.
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match &self.get(name)?.value {
+            OptionValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Value of the `Hash` option: the transposition table size in megabytes.
+    pub fn hash(&self) -> usize {
+        self.spin(Self::HASH).unwrap() as usize
+    }
+
+    /// Value of the `Threads` option.
+    pub fn threads(&self) -> usize {
+        self.spin(Self::THREADS).unwrap() as usize
+    }
+
+    /// Value of the `Move Overhead` option.
+    pub fn move_overhead(&self) -> Duration {
+        Duration::from_millis(self.spin(Self::MOVE_OVERHEAD).unwrap() as u64)
+    }
+
+    /// Value of the `Ponder` option.
+    pub fn ponder(&self) -> bool {
+        self.check(Self::PONDER).unwrap()
+    }
+
+    /// Value of the `MultiPV` option.
+    pub fn multipv(&self) -> usize {
+        self.spin(Self::MULTI_PV).unwrap() as usize
+    }
+
+    /// Value of the `Keep Hash` option.
+    pub fn keep_hash(&self) -> bool {
+        self.check(Self::KEEP_HASH).unwrap()
+    }
+
+    /// Returns the `option` lines of every option, in registration order.
+    pub fn uci_lines(&self) -> Vec<String> {
+        self.options.iter().map(EngineOption::uci_line).collect()
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OptionValue::Spin(value) => write!(f, "{value}"),
+            OptionValue::Check(value) => write!(f, "{value}"),
+            OptionValue::String(value) => f.write_str(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_options() {
+        let options = Options::new();
+        assert_eq!(options.hash(), 16);
+        assert_eq!(options.threads(), 1);
+        assert_eq!(options.move_overhead(), Duration::from_millis(20));
+        assert!(!options.ponder());
+        assert_eq!(options.multipv(), 1);
+        assert!(options.keep_hash());
+        assert_eq!(
+            options.uci_lines(),
+            [
+                "option name Hash type spin default 16 min 1 max 65536",
+                "option name Threads type spin default 1 min 1 max 256",
+                "option name Move Overhead type spin default 20 min 0 max 5000",
+                "option name Ponder type check default false",
+                "option name MultiPV type spin default 1 min 1 max 256",
+                "option name Keep Hash type check default true",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_setoption() {
+        let mut options = Options::new();
+        options
+            .setoption("setoption name Move Overhead value 100")
+            .unwrap();
+        options
+            .setoption("setoption  name threads value 4")
+            .unwrap();
+        options
+            .setoption("setoption name Ponder value true")
+            .unwrap();
+        assert_eq!(options.move_overhead(), Duration::from_millis(100));
+        assert_eq!(options.threads(), 4);
+        assert!(options.ponder());
+
+        assert_eq!(
+            options.setoption("setoption name Hash value lots"),
+            Err(OptionError::InvalidValue {
+                name: "Hash".to_string(),
+                value: "lots".to_string()
+            })
+        );
+        assert_eq!(options.hash(), 16);
+        assert_eq!(
+            options.setoption("setoption name Ponder"),
+            Err(OptionError::InvalidValue {
+                name: "Ponder".to_string(),
+                value: String::new()
+            })
+        );
+        assert_eq!(
+            options.setoption("setoption name Contempt value 10"),
+            Err(OptionError::UnknownOption("Contempt".to_string()))
+        );
+        assert_eq!(
+            options.setoption("setoption Hash value 10"),
+            Err(OptionError::BadCommand)
+        );
+        assert_eq!(
+            options.setoption("setoption name value 10"),
+            Err(OptionError::BadCommand)
+        );
+    }
+
+    #[test]
+    fn test_register() {
+        let mut options = Options::new();
+        options
+            .register("Book File", OptionType::string(""))
+            .unwrap();
+        options
+            .register("Style", OptionType::combo("Solid", &["Solid", "Risky"]))
+            .unwrap();
+        assert_eq!(
+            options.register("hash", OptionType::spin(1, 1, 2)),
+            Err(OptionError::DuplicateOption("hash".to_string()))
+        );
+        assert!(options
+            .register("Level", OptionType::spin(30, 1, 20))
+            .is_err());
+        assert!(options
+            .register("Mood", OptionType::combo("Happy", &["Sad"]))
+            .is_err());
+
+        options
+            .setoption("setoption name Book File value my book.bin")
+            .unwrap();
+        assert_eq!(options.string("Book File"), Some("my book.bin"));
+        assert!(options
+            .setoption("setoption name Style value Wild")
+            .is_err());
+        assert_eq!(options.string("Style"), Some("Solid"));
+        assert_eq!(options.spin("Style"), None);
+
+        let lines = options.uci_lines();
+        assert_eq!(
+            lines[6],
+            "option name Book File type string default <empty>"
+        );
+        assert_eq!(
+            lines[7],
+            "option name Style type combo default Solid var Solid var Risky"
+        );
+        assert_eq!(options.iter().count(), 8);
+    }
+}
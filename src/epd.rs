@@ -0,0 +1,414 @@
+//! Test suites in Extended Position Description (EPD), such as Win at Chess (WAC) and the
+//! Strategic Test Suite (STS), for tracking the tactical and positional strength of the engine
+//! between versions.
+//!
+//! Each record is a FEN without the move counters followed by operations: `bm` lists the best
+//! moves, `am` the moves to avoid, `id` names the position and `c0` may give points per move
+//! as in STS (`c0 "Nf5=10, Be5=3";`). Moves are in SAN.
+//!
+//! ```
+//! use shax::epd::{run_suite, EpdRecord};
+//! use shax::search::TimeControl;
+//!
+//! let suite = EpdRecord::parse_suite(
+//!     "6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"back rank\";\n\
+//!      4k3/8/8/3q4/8/8/3R4/4K3 w - - am Kf1; id \"hanging queen\";",
+//! )
+//! .unwrap();
+//! let result = run_suite(&suite, 3, &TimeControl::default(), |_| {});
+//! assert_eq!(result.solved, 2);
+//! assert_eq!(result.points, result.max_points);
+//! ```
+
+use crate::board::{Board, ParseFenError};
+use crate::eval::EvalTerms;
+use crate::notation::Move;
+use crate::pgn::{parse_san, ParseSanError, SanMode};
+use crate::search::{search_with_evaluator, SearchLimits, SearchResult, TimeControl};
+use crate::stop::StopToken;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseEpdError {
+    /// The record has fewer than the 4 FEN fields.
+    MissingFen,
+    BadFen(ParseFenError),
+    /// A move of a `bm` or `am` operation is not a legal move of the position.
+    BadMove {
+        opcode: String,
+        error: ParseSanError,
+    },
+    /// A quoted operand is missing its closing quote.
+    UnterminatedString,
+    /// The record has neither `bm` nor `am`, so there is nothing to solve.
+    NoSolution,
+}
+
+impl fmt::Display for ParseEpdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingFen => write!(f, "expected 4 FEN fields"),
+            Self::BadFen(err) => write!(f, "bad FEN: {err}"),
+            Self::BadMove { opcode, error } => write!(f, "bad move in {opcode:?}: {error}"),
+            Self::UnterminatedString => write!(f, "unterminated string operand"),
+            Self::NoSolution => write!(f, "expected a bm or am operation"),
+        }
+    }
+}
+
+impl error::Error for ParseEpdError {}
+
+/// A position of a test suite with its solution.
+#[derive(Debug, Clone)]
+pub struct EpdRecord {
+    pub board: Board,
+    /// Name of the position from the `id` operation.
+    pub id: Option<String>,
+    /// Moves that solve the position, from `bm`. If empty, any move not in
+    /// [`avoid_moves`](Self::avoid_moves) does.
+    pub best_moves: Vec<Move>,
+    /// Moves that fail the position, from `am`.
+    pub avoid_moves: Vec<Move>,
+    /// Points for each move from `c0`, as in STS. Empty if the record does not give any.
+    pub points: Vec<(Move, u32)>,
+}
+
+impl EpdRecord {
+    /// Parses one EPD record.
+    ///
+    /// ```
+    /// use shax::epd::EpdRecord;
+    /// use shax::notation::Move;
+    ///
+    /// let record = EpdRecord::parse(
+    ///     r#"6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; c0 "Ra8=10, Kf1=1"; id "WAC.000";"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(record.id.as_deref(), Some("WAC.000"));
+    /// assert_eq!(record.best_moves, [Move::from_lan("a1a8").unwrap()]);
+    /// assert_eq!(record.points_for(Move::from_lan("g1f1").unwrap()), 1);
+    /// assert_eq!(record.max_points(), 10);
+    /// ```
+    pub fn parse(line: &str) -> Result<Self, ParseEpdError> {
+        let mut fields = line.trim().splitn(5, char::is_whitespace);
+        let fen: Vec<&str> = fields.by_ref().take(4).collect();
+        if fen.len() < 4 {
+            return Err(ParseEpdError::MissingFen);
+        }
+        let board = Board::from_fen(&fen.join(" ")).map_err(ParseEpdError::BadFen)?;
+
+        let mut record = EpdRecord {
+            board,
+            id: None,
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+            points: Vec::new(),
+        };
+        for operation in split_operations(fields.next().unwrap_or(""))? {
+            let Some((opcode, operands)) = operation.split_first() else {
+                continue;
+            };
+            let parse_move = |text: &str| {
+                parse_san(&record.board, text, SanMode::Lenient).map_err(|error| {
+                    ParseEpdError::BadMove {
+                        opcode: opcode.clone(),
+                        error,
+                    }
+                })
+            };
+            match opcode.as_str() {
+                "bm" => {
+                    record.best_moves = operands
+                        .iter()
+                        .map(|text| parse_move(text))
+                        .collect::<Result<_, _>>()?
+                }
+                "am" => {
+                    record.avoid_moves = operands
+                        .iter()
+                        .map(|text| parse_move(text))
+                        .collect::<Result<_, _>>()?
+                }
+                "id" => record.id = operands.first().cloned(),
+                // A comment, which only scores moves if every entry is of the form move=points
+                "c0" => {
+                    record.points = operands
+                        .iter()
+                        .flat_map(|operand| operand.split(','))
+                        .map(str::trim)
+                        .filter(|entry| !entry.is_empty())
+                        .map(|entry| {
+                            let (text, points) = entry.rsplit_once('=')?;
+                            Some((parse_move(text).ok()?, points.trim().parse().ok()?))
+                        })
+                        .collect::<Option<_>>()
+                        .unwrap_or_default();
+                }
+                _ => {}
+            }
+        }
+
+        if record.best_moves.is_empty() && record.avoid_moves.is_empty() {
+            return Err(ParseEpdError::NoSolution);
+        }
+        Ok(record)
+    }
+
+    /// Parses a suite with one record per line, skipping blank lines and lines starting with
+    /// `#`. The error gives the number of the offending line, counted from 1.
+    pub fn parse_suite(text: &str) -> Result<Vec<Self>, (usize, ParseEpdError)> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|(index, line)| Self::parse(line).map_err(|err| (index + 1, err)))
+            .collect()
+    }
+
+    /// Returns [`true`] if the move is one of the best moves, if any are given, and not one of
+    /// the moves to avoid.
+    pub fn is_solved_by(&self, mov: Move) -> bool {
+        (self.best_moves.is_empty() || self.best_moves.contains(&mov))
+            && !self.avoid_moves.contains(&mov)
+    }
+
+    /// Points scored by playing the move: those given by `c0` if any, otherwise 1 if the move
+    /// solves the position and 0 if not.
+    pub fn points_for(&self, mov: Move) -> u32 {
+        if self.points.is_empty() {
+            u32::from(self.is_solved_by(mov))
+        } else {
+            self.points
+                .iter()
+                .find(|&&(scored, _)| scored == mov)
+                .map_or(0, |&(_, points)| points)
+        }
+    }
+
+    /// Most points a move can score in the position.
+    pub fn max_points(&self) -> u32 {
+        self.points
+            .iter()
+            .map(|&(_, points)| points)
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+/// Splits the operations of a record at the semicolons outside quotes, each into its opcode
+/// followed by its operands. Quotes are removed from string operands.
+fn split_operations(text: &str) -> Result<Vec<Vec<String>>, ParseEpdError> {
+    let mut operations = Vec::new();
+    let mut operation = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ';' => {
+                chars.next();
+                operations.push(std::mem::take(&mut operation));
+            }
+            '"' => {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => string.push(c),
+                        None => return Err(ParseEpdError::UnterminatedString),
+                    }
+                }
+                operation.push(string);
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let mut token = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == ';' || c == '"' {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+                operation.push(token);
+            }
+        }
+    }
+    if !operation.is_empty() {
+        operations.push(operation);
+    }
+    Ok(operations)
+}
+
+/// Outcome of the search of one position of a suite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpdOutcome {
+    pub id: Option<String>,
+    pub result: SearchResult,
+    pub solved: bool,
+    pub points: u32,
+    pub max_points: u32,
+}
+
+/// Totals of a suite run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SuiteResult {
+    pub positions: usize,
+    pub solved: usize,
+    pub points: u32,
+    pub max_points: u32,
+}
+
+/// Searches each position of the suite with iterative deepening up to `max_depth` within the
+/// time control, usually a fixed `movetime`, and scores the move found. `progress` is called
+/// after each position.
+pub fn run_suite(
+    suite: &[EpdRecord],
+    max_depth: usize,
+    time: &TimeControl,
+    progress: impl FnMut(&EpdOutcome),
+) -> SuiteResult {
+    run_suite_with(suite, max_depth, time, EvalTerms::all(), progress)
+}
+
+/// Runs the suite as [`run_suite`] once with every evaluation term and then once per term with
+/// only that term switched off, returning the totals of each run: the first one with
+/// [`EvalTerms::all`], the others with the term left out. Terms whose removal does not cost any
+/// solved positions are candidates for removal or retuning.
+///
+/// Runs within a time control solve different positions from run to run, so a fixed depth
+/// compares the terms best.
+///
+/// ```
+/// use shax::epd::{suite_ablation, EpdRecord};
+/// use shax::eval::EvalTerms;
+/// use shax::search::TimeControl;
+///
+/// let suite = EpdRecord::parse_suite("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#;").unwrap();
+/// let report = suite_ablation(&suite, 2, &TimeControl::default());
+/// assert_eq!(report.len(), 1 + EvalTerms::all().iter().count());
+/// assert!(report.iter().all(|(_, result)| result.solved == 1)); // Mates need no evaluation
+/// ```
+pub fn suite_ablation(
+    suite: &[EpdRecord],
+    max_depth: usize,
+    time: &TimeControl,
+) -> Vec<(EvalTerms, SuiteResult)> {
+    let all = EvalTerms::all();
+    let run = |terms| (terms, run_suite_with(suite, max_depth, time, terms, |_| {}));
+    let mut report = vec![run(all)];
+    report.extend(all.iter().map(|term| run(all - term)));
+    report
+}
+
+fn run_suite_with(
+    suite: &[EpdRecord],
+    max_depth: usize,
+    time: &TimeControl,
+    terms: EvalTerms,
+    mut progress: impl FnMut(&EpdOutcome),
+) -> SuiteResult {
+    let limits = SearchLimits {
+        depth: Some(max_depth),
+        time: *time,
+        ..SearchLimits::default()
+    };
+    let mut total = SuiteResult::default();
+    for record in suite {
+        let result =
+            search_with_evaluator(&record.board, &limits, &StopToken::new(), terms, |_| {});
+        let (solved, points) = match result.best_move {
+            Some(mov) => (record.is_solved_by(mov), record.points_for(mov)),
+            None => (false, 0),
+        };
+        let outcome = EpdOutcome {
+            id: record.id.clone(),
+            result,
+            solved,
+            points,
+            max_points: record.max_points(),
+        };
+        total.positions += 1;
+        total.solved += usize::from(solved);
+        total.points += points;
+        total.max_points += outcome.max_points;
+        progress(&outcome);
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_operations() {
+        let operations = split_operations(r#"bm Nf5 Be5; id "a; b";  c0 "x";"#).unwrap();
+        assert_eq!(
+            operations,
+            [
+                vec!["bm", "Nf5", "Be5"],
+                vec!["id", "a; b"],
+                vec!["c0", "x"]
+            ]
+        );
+        assert_eq!(split_operations("bm e4").unwrap(), [vec!["bm", "e4"]]);
+        assert_eq!(
+            split_operations(r#"id "open"#),
+            Err(ParseEpdError::UnterminatedString)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            EpdRecord::parse("8/8/8 w -").unwrap_err(),
+            ParseEpdError::MissingFen
+        );
+        assert!(matches!(
+            EpdRecord::parse("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5;"),
+            Err(ParseEpdError::BadMove { opcode, .. }) if opcode == "bm"
+        ));
+        assert_eq!(
+            EpdRecord::parse(r#"4k3/8/8/8/8/8/8/4K3 w - - id "draw";"#).unwrap_err(),
+            ParseEpdError::NoSolution
+        );
+        assert_eq!(
+            EpdRecord::parse_suite("# comment\n\n4k3/8/8/8/8/8/8/4K3 w - - id \"x\";")
+                .unwrap_err()
+                .0,
+            3
+        );
+    }
+
+    #[test]
+    fn test_scoring() {
+        let record =
+            EpdRecord::parse(r#"4k3/8/8/8/8/8/8/R3K3 w - - bm Ra8+ Kd2; am Kd2; c0 "no points";"#)
+                .unwrap();
+        let check = Move::from_lan("a1a8").unwrap();
+        assert!(record.is_solved_by(check));
+        assert!(!record.is_solved_by(Move::from_lan("e1d2").unwrap()));
+        assert!(!record.is_solved_by(Move::from_lan("e1f2").unwrap()));
+        assert!(record.points.is_empty());
+        assert_eq!((record.points_for(check), record.max_points()), (1, 1));
+    }
+
+    #[test]
+    fn test_suite_ablation() {
+        let suite = EpdRecord::parse_suite(
+            "6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#;\n\
+             4k3/8/8/3q4/8/8/3R4/4K3 w - - bm Rxd5;",
+        )
+        .unwrap();
+        let time = TimeControl::default();
+        let report = suite_ablation(&suite, 3, &time);
+        assert_eq!(
+            report[0],
+            (EvalTerms::all(), run_suite(&suite, 3, &time, |_| {}))
+        );
+        assert_eq!(report.len(), 1 + EvalTerms::all().iter().count());
+        assert!(report.iter().all(|(_, result)| result.positions == 2));
+    }
+}
@@ -0,0 +1,581 @@
+use crate::attacks;
+use crate::board::{Board, Position};
+use crate::masks::{file_fill, RANK_1};
+use crate::notation::{Color, Piece};
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use strum::IntoEnumIterator;
+
+/// Material values in centipawns, indexed by [`Piece`]. These are the middlegame values, which
+/// move ordering and other users of a single value rely on.
+pub const PIECE_VALUES: [i32; 6] = [100, 500, 320, 330, 900, 0];
+
+/// Endgame material values in centipawns, indexed by [`Piece`]: pawns and rooks gain value as
+/// the board empties, knights lose some.
+pub const ENDGAME_PIECE_VALUES: [i32; 6] = [120, 530, 300, 330, 900, 0];
+
+/// Game phase weights of the pieces, indexed by [`Piece`].
+const PHASE_WEIGHTS: [i32; 6] = [0, 2, 1, 1, 4, 0];
+
+/// Game phase of the starting position, see [`game_phase`].
+pub const MAX_PHASE: i32 = 24;
+
+const BISHOP_PAIR: (i32, i32) = (30, 50);
+const DOUBLED_PAWN: (i32, i32) = (-15, -25);
+const CENTER: u64 = 0x0000001818000000; // d4, e4, d5, e5
+const CENTER_BONUS: i32 = 20;
+const EXTENDED_CENTER: u64 = 0x00003c3c3c3c0000; // c3 to f6
+const EXTENDED_CENTER_BONUS: i32 = 10;
+
+/// Middlegame and endgame bonus per safe square a piece attacks, indexed by [`Piece`]. Pawns
+/// and the king are not scored: their moves are covered by the piece-square tables.
+const MOBILITY: [(i32, i32); 6] = [(0, 0), (2, 4), (4, 4), (5, 5), (1, 2), (0, 0)];
+
+/// Values of the evaluation terms in centipawns, as (middlegame, endgame) pairs for the tapered
+/// ones. [`PARAMS`] are the values of this build; a copy of them keeps evaluating the same way
+/// after the evaluation is retuned, as frozen reference opponents need.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    pub piece_values: [i32; 6],
+    pub endgame_piece_values: [i32; 6],
+    pub bishop_pair: (i32, i32),
+    pub doubled_pawn: (i32, i32),
+    pub center_bonus: i32,
+    pub extended_center_bonus: i32,
+    pub mobility: [(i32, i32); 6],
+    pub middlegame_tables: [[i32; 64]; 6],
+    pub endgame_tables: [[i32; 64]; 6],
+}
+
+/// Evaluation parameters of this build, used by [`evaluate`] and [`evaluate_with`].
+pub const PARAMS: EvalParams = EvalParams {
+    piece_values: PIECE_VALUES,
+    endgame_piece_values: ENDGAME_PIECE_VALUES,
+    bishop_pair: BISHOP_PAIR,
+    doubled_pawn: DOUBLED_PAWN,
+    center_bonus: CENTER_BONUS,
+    extended_center_bonus: EXTENDED_CENTER_BONUS,
+    mobility: MOBILITY,
+    middlegame_tables: MIDDLEGAME_TABLES,
+    endgame_tables: ENDGAME_TABLES,
+};
+
+bitflags! {
+    /// Terms of the evaluation, which can be switched off individually with [`evaluate_with`]
+    /// to measure what they contribute.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct EvalTerms: u8 {
+        const MATERIAL = 0b0001;
+        const BISHOP_PAIR = 0b0010;
+        const DOUBLED_PAWNS = 0b0100;
+        const CENTER = 0b1000;
+        const PIECE_SQUARE = 0b1_0000;
+        const MOBILITY = 0b10_0000;
+    }
+}
+
+/// Static evaluation the search scores its leaves with, for trying other evaluation functions
+/// without changing the search. [`EvalTerms`] is the built-in evaluation, evaluating with
+/// [`evaluate_with`] and the terms it holds: [`EvalTerms::all`] is the one the search uses
+/// by default.
+///
+/// ```
+/// use shax::board::{Board, Position};
+/// use shax::eval::{EvalTerms, Evaluator, PIECE_VALUES};
+/// use shax::notation::Piece;
+///
+/// /// Counts the material only.
+/// struct Material;
+///
+/// impl Evaluator for Material {
+///     fn eval(&self, board: &Position) -> i32 {
+///         [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+///             .into_iter()
+///             .map(|piece| {
+///                 let count = |color| board.squares(color, piece).count() as i32;
+///                 let balance = count(board.active()) - count(board.active().opposite());
+///                 balance * PIECE_VALUES[piece as usize]
+///             })
+///             .sum()
+///     }
+/// }
+///
+/// let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+/// assert_eq!(Material.eval(&board), -400);
+/// assert!(EvalTerms::MATERIAL.eval(&board) < 0); // Tapered to the endgame values
+/// ```
+pub trait Evaluator {
+    /// Evaluates the position in centipawns from the perspective of the active color, as
+    /// [`evaluate`] does. Scores must stay well within ±30000, the range of mate scores.
+    fn eval(&self, board: &Position) -> i32;
+}
+
+/// Evaluates as the referenced evaluator, so that a search can borrow one the caller keeps.
+impl<E: Evaluator + ?Sized> Evaluator for &E {
+    fn eval(&self, board: &Position) -> i32 {
+        (**self).eval(board)
+    }
+}
+
+impl Evaluator for EvalTerms {
+    fn eval(&self, board: &Position) -> i32 {
+        evaluate_with(board, *self)
+    }
+}
+
+/// Evaluates every term with these values instead of the ones of this build.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::eval::{evaluate, Evaluator, PARAMS};
+///
+/// let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+/// assert_eq!(PARAMS.eval(&board), evaluate(&board));
+/// ```
+impl Evaluator for EvalParams {
+    fn eval(&self, board: &Position) -> i32 {
+        evaluate_params(board, EvalTerms::all(), self)
+    }
+}
+
+/// Statically evaluates the position in centipawns from the perspective of the active color:
+/// positive scores favor the side to move.
+///
+/// The evaluation consists of material, piece-square tables, mobility and a few simple
+/// positional terms (bishop pair, doubled pawns, central pawns and minor pieces). Each term has
+/// a middlegame and an endgame value, which are interpolated by the [`game_phase`].
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::eval::evaluate;
+/// use shax::notation::Move;
+///
+/// let mut board = Board::default();
+/// assert_eq!(evaluate(&board), 0);
+///
+/// board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+/// assert!(evaluate(&board) < 0); // Black to move, White controls the center
+/// ```
+pub fn evaluate(board: &Position) -> i32 {
+    evaluate_with(board, EvalTerms::all())
+}
+
+/// Same as [`evaluate`], but only the given terms are taken into account.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::eval::{evaluate_with, EvalTerms};
+/// use shax::notation::Move;
+///
+/// let mut board = Board::default();
+/// board.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+/// assert_eq!(evaluate_with(&board, EvalTerms::MATERIAL), 0);
+/// assert!(evaluate_with(&board, EvalTerms::CENTER) < 0);
+/// ```
+pub fn evaluate_with(board: &Position, terms: EvalTerms) -> i32 {
+    evaluate_params(board, terms, &PARAMS)
+}
+
+fn evaluate_params(board: &Position, terms: EvalTerms, params: &EvalParams) -> i32 {
+    let (white_mg, white_eg) = evaluate_color(board, Color::White, terms, params);
+    let (black_mg, black_eg) = evaluate_color(board, Color::Black, terms, params);
+    let score = taper(white_mg - black_mg, white_eg - black_eg, game_phase(board));
+    match board.active() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+/// Returns the game phase from the material left on the board: [`MAX_PHASE`] with all
+/// minor pieces, rooks and queens on the board, down to 0 with only kings and pawns. Extra
+/// pieces from promotions do not raise it above [`MAX_PHASE`].
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::eval::{game_phase, MAX_PHASE};
+///
+/// assert_eq!(game_phase(&Board::default()), MAX_PHASE);
+/// let rook_endgame = Board::from_fen("4k3/p4r2/8/8/8/8/P3R3/4K3 w - - 0 1").unwrap();
+/// assert_eq!(game_phase(&rook_endgame), 4);
+/// ```
+pub fn game_phase(board: &Position) -> i32 {
+    Piece::iter()
+        .map(|piece| {
+            let count = (board.bitboard(Color::White, piece) | board.bitboard(Color::Black, piece))
+                .count_ones() as i32;
+            count * PHASE_WEIGHTS[piece as usize]
+        })
+        .sum::<i32>()
+        .min(MAX_PHASE)
+}
+
+/// Interpolates between a middlegame and an endgame score by the game phase.
+fn taper(middlegame: i32, endgame: i32, phase: i32) -> i32 {
+    (middlegame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE
+}
+
+/// Measures how much each evaluation term contributes over a set of positions: the mean
+/// absolute change of the score in centipawns when only that term is switched off. Terms
+/// that barely move the score are candidates for removal or retuning.
+///
+/// A static score does not show whether the change matters to the moves the engine plays:
+/// `search::bench_ablation` and `epd::suite_ablation` search with each term switched off.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::eval::{ablation, EvalTerms};
+///
+/// let report = ablation(&[Board::default()]);
+/// assert_eq!(report.len(), EvalTerms::all().iter().count());
+/// assert!(report.iter().all(|&(_, contribution)| contribution == 0.0));
+/// ```
+pub fn ablation(positions: &[Board]) -> Vec<(EvalTerms, f64)> {
+    EvalTerms::all()
+        .iter()
+        .map(|term| {
+            let total: i64 = positions
+                .iter()
+                .map(|board| {
+                    let full = evaluate(board);
+                    let without = evaluate_with(board, EvalTerms::all() - term);
+                    (full - without).abs() as i64
+                })
+                .sum();
+            (term, total as f64 / positions.len().max(1) as f64)
+        })
+        .collect()
+}
+
+/// Returns the middlegame and endgame scores of the color.
+fn evaluate_color(
+    board: &Position,
+    color: Color,
+    terms: EvalTerms,
+    params: &EvalParams,
+) -> (i32, i32) {
+    let (mut mg, mut eg) = (0, 0);
+
+    if terms.contains(EvalTerms::MATERIAL) {
+        for piece in Piece::iter() {
+            let count = board.bitboard(color, piece).count_ones() as i32;
+            mg += count * params.piece_values[piece as usize];
+            eg += count * params.endgame_piece_values[piece as usize];
+        }
+    }
+
+    if terms.contains(EvalTerms::BISHOP_PAIR)
+        && board.bitboard(color, Piece::Bishop).count_ones() >= 2
+    {
+        mg += params.bishop_pair.0;
+        eg += params.bishop_pair.1;
+    }
+
+    let pawns = board.bitboard(color, Piece::Pawn);
+    if terms.contains(EvalTerms::DOUBLED_PAWNS) {
+        // Every pawn beyond the first on its file is doubled
+        let files = (file_fill(pawns) & RANK_1).count_ones() as i32;
+        let doubled = pawns.count_ones() as i32 - files;
+        mg += params.doubled_pawn.0 * doubled;
+        eg += params.doubled_pawn.1 * doubled;
+    }
+
+    // Central control matters for the middlegame only
+    if terms.contains(EvalTerms::CENTER) {
+        let central =
+            pawns | board.bitboard(color, Piece::Knight) | board.bitboard(color, Piece::Bishop);
+        mg += (central & CENTER).count_ones() as i32 * params.center_bonus;
+        mg += (central & EXTENDED_CENTER & !CENTER).count_ones() as i32
+            * params.extended_center_bonus;
+    }
+
+    if terms.contains(EvalTerms::PIECE_SQUARE) {
+        for piece in Piece::iter() {
+            for square in board.squares(color, piece) {
+                // The tables are laid out with the 8th rank first, as seen by White
+                let index = match color {
+                    Color::White => square as usize ^ 56,
+                    Color::Black => square as usize,
+                };
+                mg += params.middlegame_tables[piece as usize][index];
+                eg += params.endgame_tables[piece as usize][index];
+            }
+        }
+    }
+
+    if terms.contains(EvalTerms::MOBILITY) {
+        let (mobility_mg, mobility_eg) = mobility(board, color, &params.mobility);
+        mg += mobility_mg;
+        eg += mobility_eg;
+    }
+
+    (mg, eg)
+}
+
+/// Returns the middlegame and endgame mobility scores of the color's pieces: the squares they
+/// attack that are neither occupied by friendly pieces nor attacked by enemy pawns.
+fn mobility(board: &Position, color: Color, bonuses: &[(i32, i32); 6]) -> (i32, i32) {
+    let occupancy = |color| {
+        Piece::iter()
+            .map(|piece| board.bitboard(color, piece))
+            .fold(0, |acc, bb| acc | bb)
+    };
+    let enemy = color.opposite();
+    let occupied = occupancy(color) | occupancy(enemy);
+    let safe =
+        !occupancy(color) & !attacks::pawn_attacks(board.bitboard(enemy, Piece::Pawn), enemy);
+
+    let (mut mg, mut eg) = (0, 0);
+    for piece in [Piece::Rook, Piece::Knight, Piece::Bishop, Piece::Queen] {
+        let (bonus_mg, bonus_eg) = bonuses[piece as usize];
+        for square in board.squares(color, piece) {
+            let targets = match piece {
+                Piece::Rook => attacks::rook_attacks(square, occupied),
+                Piece::Knight => attacks::knight_attacks_from(square),
+                Piece::Bishop => attacks::bishop_attacks(square, occupied),
+                _ => attacks::queen_attacks(square, occupied),
+            };
+            let count = (targets & safe).count_ones() as i32;
+            mg += bonus_mg * count;
+            eg += bonus_eg * count;
+        }
+    }
+    (mg, eg)
+}
+
+/// Middlegame piece-square tables in centipawns, indexed by [`Piece`] and then by square from
+/// a8 to h1, as seen by White and mirrored for Black.
+pub const MIDDLEGAME_TABLES: [[i32; 64]; 6] = [
+    PAWN_MIDDLEGAME,
+    ROOK,
+    KNIGHT,
+    BISHOP,
+    QUEEN,
+    KING_MIDDLEGAME,
+];
+
+/// Endgame piece-square tables, laid out as [`MIDDLEGAME_TABLES`]. Pawns are pushed towards
+/// promotion and the king towards the center.
+pub const ENDGAME_TABLES: [[i32; 64]; 6] =
+    [PAWN_ENDGAME, ROOK, KNIGHT, BISHOP, QUEEN, KING_ENDGAME];
+
+#[rustfmt::skip]
+const PAWN_MIDDLEGAME: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const PAWN_ENDGAME: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    15,  15,  15,  15,  15,  15,  15,  15,
+     5,   5,   5,   5,   5,   5,   5,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::{CastlingRights, GameStatus, Move};
+
+    fn board_after(moves: &[&str]) -> Board {
+        let mut board = Board::default();
+        for mov in moves {
+            board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn test_evaluate_symmetric() {
+        assert_eq!(evaluate(&board_after(&[])), 0);
+        assert_eq!(evaluate(&board_after(&["e2e4", "e7e5"])), 0);
+        assert_eq!(evaluate(&board_after(&["g1f3", "g8f6", "b1c3", "b8c6"])), 0);
+    }
+
+    #[test]
+    fn test_evaluate_side_to_move() {
+        let board = board_after(&["e2e4", "d7d5", "e4d5"]);
+        assert_eq!(
+            evaluate_with(
+                &board,
+                EvalTerms::all() - EvalTerms::PIECE_SQUARE - EvalTerms::MOBILITY
+            ),
+            -(PIECE_VALUES[Piece::Pawn as usize] + CENTER_BONUS + DOUBLED_PAWN.0) // d2 and d5
+        );
+    }
+
+    #[test]
+    fn test_ablation() {
+        let board = board_after(&["e2e4", "d7d5", "e4d5"]);
+        let report = ablation(&[board]);
+        let contribution = |term| report.iter().find(|(t, _)| *t == term).unwrap().1;
+        assert_eq!(contribution(EvalTerms::MATERIAL), 100.0);
+        assert_eq!(contribution(EvalTerms::DOUBLED_PAWNS), 15.0);
+        assert_eq!(contribution(EvalTerms::CENTER), 20.0);
+        assert_eq!(contribution(EvalTerms::BISHOP_PAIR), 0.0);
+    }
+
+    #[test]
+    fn test_taper() {
+        assert_eq!(taper(100, 200, MAX_PHASE), 100);
+        assert_eq!(taper(100, 200, 0), 200);
+        assert_eq!(taper(100, 200, MAX_PHASE / 2), 150);
+
+        // A pawn is worth more once the pieces are traded
+        let middlegame = Board::from_fen("r3k3/7p/8/8/8/8/PP6/R3K3 w - - 0 1").unwrap();
+        let endgame = Board::from_fen("4k3/7p/8/8/8/8/PP6/4K3 w - - 0 1").unwrap();
+        assert!(
+            evaluate_with(&endgame, EvalTerms::MATERIAL)
+                > evaluate_with(&middlegame, EvalTerms::MATERIAL)
+        );
+    }
+
+    #[test]
+    fn test_piece_square_tables() {
+        let psqt = |board: &Board| evaluate_with(board, EvalTerms::PIECE_SQUARE);
+        // Mirrored for Black
+        assert_eq!(psqt(&board_after(&[])), 0);
+        assert_eq!(psqt(&board_after(&["b1c3", "b8c6"])), 0);
+
+        // A centralized knight beats one on the rim, for both colors
+        let center = Board::from_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+        let rim = Board::from_fen("4k3/8/8/8/N7/8/8/4K3 w - - 0 1").unwrap();
+        assert!(psqt(&center) > psqt(&rim));
+        let center = Board::from_fen("4k3/8/8/3n4/8/8/8/4K3 b - - 0 1").unwrap();
+        let rim = Board::from_fen("4k3/8/8/n7/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(psqt(&center) > psqt(&rim));
+
+        // In the endgame the king belongs in the center, in the middlegame behind its pawns
+        let endgame = Board::from_fen("4k3/8/8/8/3K4/8/8/8 w - - 0 1").unwrap();
+        let corner = Board::from_fen("4k3/8/8/8/8/8/8/6K1 w - - 0 1").unwrap();
+        assert!(psqt(&endgame) > psqt(&corner));
+        let castled = board_after(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "e1g1"]);
+        assert!(psqt(&castled) < 0); // Black to move, White's king is safer
+    }
+
+    #[test]
+    fn test_mobility() {
+        let mobility = |fen| evaluate_with(&Board::from_fen(fen).unwrap(), EvalTerms::MOBILITY);
+        // The knight on d4 reaches 8 squares, b3 and f3 are attacked by the pawns
+        let knight = mobility("4k3/8/8/8/2pN2p1/8/8/4K3 w - - 0 1");
+        assert_eq!(knight, 6 * MOBILITY[Piece::Knight as usize].0);
+        // A rook and bishop shut in by their own pieces have no mobility
+        assert_eq!(mobility("4k3/8/8/8/8/8/PPP5/RBK5 w - - 0 1"), 0);
+        // Black to move: White's developed bishop gives White the edge
+        assert!(mobility("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1") < 0);
+    }
+
+    #[test]
+    fn test_evaluate_positional_terms() {
+        let mut bitboards = [[0; 6]; 2];
+        bitboards[Color::White as usize][Piece::King as usize] = 0x10;
+        bitboards[Color::White as usize][Piece::Pawn as usize] = 0x0000000404000000; // c4, c5
+        bitboards[Color::Black as usize][Piece::King as usize] = 0x1000000000000000;
+        bitboards[Color::Black as usize][Piece::Bishop as usize] = 0x2400000000000000;
+        let board = Board::from_parts(
+            bitboards,
+            Color::White,
+            CastlingRights::empty(),
+            None,
+            0,
+            GameStatus::Ongoing,
+        );
+        let mg =
+            2 * PIECE_VALUES[Piece::Pawn as usize] + DOUBLED_PAWN.0 + 2 * EXTENDED_CENTER_BONUS
+                - 2 * PIECE_VALUES[Piece::Bishop as usize]
+                - BISHOP_PAIR.0;
+        let eg = 2 * ENDGAME_PIECE_VALUES[Piece::Pawn as usize] + DOUBLED_PAWN.1
+            - 2 * ENDGAME_PIECE_VALUES[Piece::Bishop as usize]
+            - BISHOP_PAIR.1;
+        assert_eq!(game_phase(&board), 2);
+        assert_eq!(
+            evaluate_with(
+                &board,
+                EvalTerms::all() - EvalTerms::PIECE_SQUARE - EvalTerms::MOBILITY
+            ),
+            (mg * 2 + eg * 22) / 24
+        );
+    }
+}
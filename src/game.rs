@@ -0,0 +1,377 @@
+use crate::board::{Board, MoveError};
+use crate::notation::{Color, GameStatus, Move, Winner};
+use crate::pgn::{san, Pgn};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+/// A move played in a [`Game`], with its Standard Algebraic Notation (SAN).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayedMove {
+    pub mov: Move,
+    pub san: String,
+}
+
+/// How a game ended by a player's decision rather than on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conclusion {
+    /// A draw was claimed by threefold repetition or the 50-move rule.
+    DrawClaimed,
+    /// The opponent of the winner resigned.
+    Resignation { winner: Color },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// The game is already over.
+    GameEnded,
+    /// Neither threefold repetition nor the 50-move rule applies.
+    NoDrawToClaim,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::GameEnded => write!(f, "game ended"),
+            Self::NoDrawToClaim => write!(f, "no draw to claim"),
+        }
+    }
+}
+
+impl error::Error for GameError {}
+
+/// A game on top of a [`Board`] that records the moves played, so that they can be taken back
+/// and replayed, and that tracks draw claims and resignations. Suited as the model of a GUI.
+///
+/// ```
+/// use shax::game::Game;
+/// use shax::notation::{Move, Winner};
+///
+/// let mut game = Game::new();
+/// for lan in ["f2f3", "e7e5", "g2g3"] {
+///     game.make_move(Move::from_lan(lan).unwrap()).unwrap();
+/// }
+/// assert_eq!(game.undo(), Some(Move::from_lan("g2g3").unwrap()));
+/// game.make_move(Move::from_lan("g2g4").unwrap()).unwrap();
+/// game.make_move(Move::from_lan("d8h4").unwrap()).unwrap();
+///
+/// assert_eq!(game.result(), Some(Winner::Black));
+/// let sans: Vec<_> = game.moves().iter().map(|played| played.san.as_str()).collect();
+/// assert_eq!(sans, ["f3", "e5", "g4", "Qh4#"]);
+/// assert!(game.pgn().write().unwrap().ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Board,
+    board: Board,
+    moves: Vec<PlayedMove>,
+    /// Moves taken back with [`Game::undo`], the next one to redo last.
+    undone: Vec<Move>,
+    conclusion: Option<Conclusion>,
+    auto_claim_draws: bool,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::from_board(Board::default())
+    }
+}
+
+impl Game {
+    /// Creates a game from the standard starting position.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a game starting from the given position.
+    pub fn from_board(board: Board) -> Self {
+        Game {
+            start: board.clone(),
+            board,
+            moves: Vec::new(),
+            undone: Vec::new(),
+            conclusion: None,
+            auto_claim_draws: false,
+        }
+    }
+
+    /// Makes the game claim draws by threefold repetition and the 50-move rule on behalf of
+    /// the side to move as soon as they become available, as casual servers do. Without it,
+    /// draws are only claimed with [`Game::claim_draw`].
+    ///
+    /// ```
+    /// use shax::game::{Conclusion, Game};
+    /// use shax::notation::Move;
+    ///
+    /// let mut game = Game::new().with_auto_claim_draws(true);
+    /// for lan in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///     game.make_move(Move::from_lan(lan).unwrap()).unwrap();
+    /// }
+    /// assert_eq!(game.conclusion(), Some(Conclusion::DrawClaimed));
+    /// ```
+    pub fn with_auto_claim_draws(mut self, auto_claim_draws: bool) -> Self {
+        self.auto_claim_draws = auto_claim_draws;
+        self.auto_claim();
+        self
+    }
+
+    pub fn auto_claims_draws(&self) -> bool {
+        self.auto_claim_draws
+    }
+
+    /// Returns the current position.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns the position the game started from.
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    /// Returns the moves played so far.
+    pub fn moves(&self) -> &[PlayedMove] {
+        &self.moves
+    }
+
+    /// Plays a move, discarding the moves that could be redone.
+    pub fn make_move(&mut self, mov: Move) -> Result<(), MoveError> {
+        if self.conclusion.is_some() {
+            return Err(MoveError::GameEnded);
+        }
+        let san = san(&self.board, mov)?;
+        self.board.make_move(mov)?;
+        self.moves.push(PlayedMove { mov, san });
+        self.undone.clear();
+        self.auto_claim();
+        Ok(())
+    }
+
+    /// Takes back the last move and returns it, or [`None`] if no move was played. A draw
+    /// claim or resignation made after the move is withdrawn with it.
+    pub fn undo(&mut self) -> Option<Move> {
+        let played = self.moves.pop()?;
+        self.undone.push(played.mov);
+        self.conclusion = None;
+
+        // Replaying keeps the repetition history of the board exact
+        self.board = self.start.clone();
+        for played in &self.moves {
+            self.board
+                .make_move(played.mov)
+                .expect("moves were legal when played");
+        }
+        Some(played.mov)
+    }
+
+    /// Replays the last move taken back and returns it, or [`None`] if there is none or the
+    /// game was concluded since.
+    pub fn redo(&mut self) -> Option<Move> {
+        if self.conclusion.is_some() {
+            return None;
+        }
+        let mov = self.undone.pop()?;
+        let san = san(&self.board, mov).expect("moves were legal when played");
+        self.board
+            .make_move(mov)
+            .expect("moves were legal when played");
+        self.moves.push(PlayedMove { mov, san });
+        self.auto_claim();
+        Some(mov)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.moves.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.conclusion.is_none() && !self.undone.is_empty()
+    }
+
+    /// Ends the game in a draw if the side to move can claim one by threefold repetition or
+    /// the 50-move rule, see [`Board::can_claim_draw`].
+    pub fn claim_draw(&mut self) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameEnded);
+        }
+        if !self.board.can_claim_draw() {
+            return Err(GameError::NoDrawToClaim);
+        }
+        self.conclusion = Some(Conclusion::DrawClaimed);
+        Ok(())
+    }
+
+    fn auto_claim(&mut self) {
+        if self.auto_claim_draws {
+            // Fails without effect if there is no draw to claim or the game is over
+            let _ = self.claim_draw();
+        }
+    }
+
+    /// Ends the game with a win for the opponent of `color`.
+    pub fn resign(&mut self, color: Color) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameEnded);
+        }
+        self.conclusion = Some(Conclusion::Resignation {
+            winner: color.opposite(),
+        });
+        Ok(())
+    }
+
+    /// Returns how the game was concluded by a player, if it was.
+    pub fn conclusion(&self) -> Option<Conclusion> {
+        self.conclusion
+    }
+
+    /// Returns the status of the current position on the board.
+    pub fn status(&self) -> GameStatus {
+        self.board.status()
+    }
+
+    /// Returns [`true`] if the game ended on the board or by a player's decision.
+    pub fn is_over(&self) -> bool {
+        self.conclusion.is_some() || self.board.status().is_over()
+    }
+
+    /// Returns the result of the game, or [`None`] while it is in progress.
+    pub fn result(&self) -> Option<Winner> {
+        match self.conclusion {
+            Some(Conclusion::DrawClaimed) => Some(Winner::Draw),
+            Some(Conclusion::Resignation { winner }) => Some(winner.into()),
+            None => self.board.winner(),
+        }
+    }
+
+    /// Returns the game as PGN, to which further tags can be added before writing it.
+    pub fn pgn(&self) -> Pgn {
+        let mut pgn = Pgn::from_position(&self.start);
+        for played in &self.moves {
+            pgn.push(played.mov);
+        }
+        let result = match self.result() {
+            Some(Winner::White) => "1-0",
+            Some(Winner::Black) => "0-1",
+            Some(Winner::Draw) => "1/2-1/2",
+            None => "*",
+        };
+        pgn.set_tag("Result", result);
+        pgn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut Game, moves: &[&str]) {
+        for lan in moves {
+            game.make_move(Move::from_lan(lan).unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_restores_positions() {
+        let mut game = Game::new();
+        play(&mut game, &["e2e4", "e7e5", "g1f3", "b8c6"]);
+        let fen = game.board().to_fen();
+
+        assert_eq!(game.undo(), Some(Move::from_lan("b8c6").unwrap()));
+        assert_eq!(game.undo(), Some(Move::from_lan("g1f3").unwrap()));
+        assert_eq!(game.moves().len(), 2);
+        assert!(game.can_redo());
+
+        assert_eq!(game.redo(), Some(Move::from_lan("g1f3").unwrap()));
+        assert_eq!(game.redo(), Some(Move::from_lan("b8c6").unwrap()));
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.board().to_fen(), fen);
+        assert_eq!(game.moves()[3].san, "Nc6");
+
+        while game.undo().is_some() {}
+        assert_eq!(game.board().to_fen(), Board::default().to_fen());
+        assert!(!game.can_undo());
+    }
+
+    #[test]
+    fn test_new_move_discards_redo() {
+        let mut game = Game::new();
+        play(&mut game, &["e2e4", "e7e5"]);
+        game.undo();
+        play(&mut game, &["c7c5"]);
+        assert!(!game.can_redo());
+        assert_eq!(game.redo(), None);
+    }
+
+    #[test]
+    fn test_draw_claim() {
+        let mut game = Game::new();
+        assert_eq!(game.claim_draw(), Err(GameError::NoDrawToClaim));
+
+        play(
+            &mut game,
+            &[
+                "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+            ],
+        );
+        assert_eq!(game.claim_draw(), Ok(()));
+        assert_eq!(game.result(), Some(Winner::Draw));
+        assert_eq!(
+            game.make_move(Move::from_lan("e2e4").unwrap()),
+            Err(MoveError::GameEnded)
+        );
+        assert!(game.pgn().write().unwrap().ends_with("1/2-1/2\n"));
+
+        // Taking back the last move withdraws the claim
+        game.undo();
+        assert_eq!(game.conclusion(), None);
+        assert_eq!(game.claim_draw(), Err(GameError::NoDrawToClaim));
+    }
+
+    #[test]
+    fn test_auto_claim_draws() {
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let mut game = Game::new().with_auto_claim_draws(true);
+        play(&mut game, &shuffle);
+        assert_eq!(game.conclusion(), None);
+        play(&mut game, &shuffle);
+        assert_eq!(game.conclusion(), Some(Conclusion::DrawClaimed));
+
+        // Undoing withdraws the claim and redoing claims again
+        game.undo();
+        assert_eq!(game.conclusion(), None);
+        game.redo();
+        assert_eq!(game.conclusion(), Some(Conclusion::DrawClaimed));
+
+        // The 50-move rule is claimed as well, also when enabled on a position that allows it
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 100 80").unwrap();
+        let game = Game::from_board(board).with_auto_claim_draws(true);
+        assert_eq!(game.result(), Some(Winner::Draw));
+    }
+
+    #[test]
+    fn test_resignation() {
+        let mut game = Game::new();
+        play(&mut game, &["e2e4"]);
+        game.resign(Color::Black).unwrap();
+        assert_eq!(
+            game.conclusion(),
+            Some(Conclusion::Resignation {
+                winner: Color::White
+            })
+        );
+        assert_eq!(game.result(), Some(Winner::White));
+        assert_eq!(game.resign(Color::White), Err(GameError::GameEnded));
+        assert!(game.pgn().write().unwrap().ends_with("1. e4 1-0\n"));
+    }
+
+    #[test]
+    fn test_pgn_from_position() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+        let mut game = Game::from_board(board);
+        play(&mut game, &["e8d7", "e2e4"]);
+        let pgn = game.pgn();
+        assert_eq!(pgn.tag("FEN"), Some("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1"));
+        assert!(pgn.write().unwrap().ends_with("1... Kd7 2. e4 *\n"));
+    }
+}
@@ -5,12 +5,38 @@
 //! What works now:
 //!
 //! - Move generation and validation
-//! - Full chess rules support (castling, en passant, promotion, repetition draws, 75-move rule)
+//! - Full chess rules support (castling, en passant, promotion, repetition draws, 75-move rule,
+//!   insufficient material)
+//! - Chess960 (Fischer Random) starting positions and castling
+//! - Static evaluation and an alpha-beta search with iterative deepening, move ordering and time
+//!   management (requires `std`)
+//! - Multi-threaded search (Lazy SMP) with a shared transposition table (requires `std`)
+//! - Move hints at beginner, club and master strength (requires `std`)
+//! - Rough Elo estimation against frozen reference levels, see the `rating` module (requires
+//!   `std`)
+//!
+//! Runnable programs using these, from a legal move server to a self-playing bot, are collected
+//! in the `cookbook` module.
+//!
+//! Optional features:
+//!
+//! - `serde`: `Serialize` and `Deserialize` for boards, moves and the other notation types,
+//!   using their FEN and LAN text
+//! - `serde-structural`: with `serde`, encodes moves and squares by their enum structure instead
+//!   of LAN text
+//! - `wasm`: `wasm-bindgen` bindings for using shax as the rules engine of a browser GUI, see
+//!   the `wasm` module
+//! - `pyo3`: Python bindings for scripting and notebooks, see the `python` module
+//! - `tracing`: [`tracing`](https://docs.rs/tracing) spans for `make_move`, move generation and
+//!   searches, and an event with the node count and score after each search iteration
+//! - `rayon`: `perft_parallel` and `perft_divide_parallel`, which count the root moves of a
+//!   perft on the [`rayon`](https://docs.rs/rayon) thread pool
+//! - `std` (default): with it disabled the crate is `no_std` and only needs `alloc`, so the move
+//!   generator can run on embedded targets
 //!
 //! Maybe someday:
 //!
-//! - Search algorithm
-//! - Position evaluation
+//! - Stronger search (pruning)
 //! - UCI implementation
 //!
 //! ## Example
@@ -39,10 +65,52 @@
 //! ```
 //!
 
-mod attacks;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+pub mod attacks;
+pub mod bitboard;
 pub mod board;
+#[cfg(feature = "std")]
+pub mod cookbook;
+#[cfg(feature = "std")]
+mod correction;
+mod cuckoo;
+pub mod engine;
+#[cfg(feature = "std")]
+pub mod epd;
+pub mod eval;
+pub mod game;
+mod magic;
+pub mod masks;
+pub mod movelist;
 pub mod notation;
-mod rays;
+pub mod opening;
+#[cfg(feature = "std")]
+mod ordering;
+pub mod pgn;
+pub mod positions;
+#[cfg(feature = "pyo3")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod rating;
+pub mod rays;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "std")]
+pub mod stats;
+pub mod stop;
+#[cfg(feature = "std")]
+pub mod tt;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod wire;
+mod zobrist;
 
 #[inline(always)]
 fn bitscan_forward(bb: u64) -> usize {
@@ -53,36 +121,3 @@ fn bitscan_forward(bb: u64) -> usize {
 fn bitscan_reverse(bb: u64) -> usize {
     bb.leading_zeros() as usize ^ 63
 }
-
-#[allow(unused)]
-const FILE_A: u64 = 0x0101010101010101;
-#[allow(unused)]
-const FILE_B: u64 = 0x0202020202020202;
-#[allow(unused)]
-const FILE_C: u64 = 0x0404040404040404;
-#[allow(unused)]
-const FILE_D: u64 = 0x0808080808080808;
-#[allow(unused)]
-const FILE_E: u64 = 0x1010101010101010;
-#[allow(unused)]
-const FILE_F: u64 = 0x2020202020202020;
-#[allow(unused)]
-const FILE_G: u64 = 0x4040404040404040;
-#[allow(unused)]
-const FILE_H: u64 = 0x8080808080808080;
-#[allow(unused)]
-const RANK_1: u64 = 0x00000000000000ff;
-#[allow(unused)]
-const RANK_2: u64 = 0x000000000000ff00;
-#[allow(unused)]
-const RANK_3: u64 = 0x0000000000ff0000;
-#[allow(unused)]
-const RANK_4: u64 = 0x00000000ff000000;
-#[allow(unused)]
-const RANK_5: u64 = 0x000000ff00000000;
-#[allow(unused)]
-const RANK_6: u64 = 0x0000ff0000000000;
-#[allow(unused)]
-const RANK_7: u64 = 0x00ff000000000000;
-#[allow(unused)]
-const RANK_8: u64 = 0xff00000000000000;
@@ -0,0 +1,240 @@
+use crate::attacks::{classical_bishop_attacks, classical_rook_attacks};
+use crate::masks::{FILE_A, FILE_H, RANK_1, RANK_8};
+use crate::notation::Square;
+use crate::rays::{get_rays_cache, Ray};
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+static MAGICS_CACHE: std::sync::OnceLock<Magics> = std::sync::OnceLock::new();
+
+#[cfg(feature = "std")]
+pub fn get_magics_cache() -> &'static Magics {
+    MAGICS_CACHE.get_or_init(Magics::new)
+}
+
+#[cfg(not(feature = "std"))]
+static MAGICS_CACHE: core::sync::atomic::AtomicPtr<Magics> =
+    core::sync::atomic::AtomicPtr::new(core::ptr::null_mut());
+
+/// Without `std` there is no `OnceLock`, so the tables are published through an atomic pointer.
+/// Threads racing on the first lookup may each build the tables, only one copy is kept.
+#[cfg(not(feature = "std"))]
+pub fn get_magics_cache() -> &'static Magics {
+    use alloc::boxed::Box;
+    use core::sync::atomic::Ordering;
+
+    let cached = MAGICS_CACHE.load(Ordering::Acquire);
+    if !cached.is_null() {
+        // SAFETY: a non-null pointer always comes from the leaked box below and is never freed
+        return unsafe { &*cached };
+    }
+    let fresh = Box::into_raw(Box::new(Magics::new()));
+    match MAGICS_CACHE.compare_exchange(
+        core::ptr::null_mut(),
+        fresh,
+        Ordering::AcqRel,
+        Ordering::Acquire,
+    ) {
+        // SAFETY: the box was just leaked into the cache and lives for the rest of the program
+        Ok(_) => unsafe { &*fresh },
+        Err(winner) => {
+            // SAFETY: `fresh` was never shared, and `winner` is the leaked box of another thread
+            unsafe {
+                drop(Box::from_raw(fresh));
+                &*winner
+            }
+        }
+    }
+}
+
+/// Magic bitboard lookup tables for sliding pieces. The attack set of a slider is found by
+/// hashing the relevant blockers with a multiply and shift into a per-square attack table.
+pub struct Magics {
+    rook: [Magic; 64],
+    bishop: [Magic; 64],
+    attacks: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Magic {
+    mask: u64,
+    #[cfg_attr(all(target_arch = "x86_64", target_feature = "bmi2"), allow(dead_code))]
+    magic: u64,
+    #[cfg_attr(all(target_arch = "x86_64", target_feature = "bmi2"), allow(dead_code))]
+    shift: u32,
+    offset: usize,
+}
+
+impl Magic {
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "bmi2")))]
+    #[inline(always)]
+    fn index(&self, blockers: u64) -> usize {
+        self.offset + ((blockers & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    /// With BMI2 the blockers are packed into a dense index directly. Both schemes address
+    /// `1 << mask.count_ones()` slots per square, so the table layout is shared. The choice is
+    /// made at compile time (`-C target-cpu=native`): dispatching on runtime detection costs
+    /// more per lookup than PEXT saves over the multiply.
+    #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
+    #[inline(always)]
+    fn index(&self, blockers: u64) -> usize {
+        // SAFETY: BMI2 is enabled for the whole compilation
+        self.offset + unsafe { core::arch::x86_64::_pext_u64(blockers, self.mask) } as usize
+    }
+}
+
+impl Magics {
+    #[inline(always)]
+    pub fn rook_attacks(&self, square: Square, blockers: u64) -> u64 {
+        self.attacks[self.rook[square as usize].index(blockers)]
+    }
+
+    #[inline(always)]
+    pub fn bishop_attacks(&self, square: Square, blockers: u64) -> u64 {
+        self.attacks[self.bishop[square as usize].index(blockers)]
+    }
+
+    fn new() -> Self {
+        let mut magics = Magics {
+            rook: [Magic::default(); 64],
+            bishop: [Magic::default(); 64],
+            attacks: Vec::with_capacity(0x19000 + 0x1480),
+        };
+
+        for (square, ray) in get_rays_cache().iter().enumerate() {
+            magics.rook[square] = magics.fill(
+                square,
+                rook_mask(ray),
+                ROOK_MAGICS[square],
+                classical_rook_attacks,
+            );
+        }
+
+        for (square, ray) in get_rays_cache().iter().enumerate() {
+            magics.bishop[square] = magics.fill(
+                square,
+                bishop_mask(ray),
+                BISHOP_MAGICS[square],
+                classical_bishop_attacks,
+            );
+        }
+
+        magics
+    }
+
+    /// Appends the attack table of a square, enumerating every blocker subset of the mask.
+    fn fill(
+        &mut self,
+        square: usize,
+        mask: u64,
+        magic: u64,
+        attacks: fn(Square, u64) -> u64,
+    ) -> Magic {
+        let square = Square::from_index(square).unwrap();
+        let bits = mask.count_ones();
+        let magic = Magic {
+            mask,
+            magic,
+            shift: 64 - bits,
+            offset: self.attacks.len(),
+        };
+        self.attacks.resize(magic.offset + (1 << bits), 0);
+
+        let mut subset = 0u64;
+        loop {
+            let index = magic.index(subset);
+            let attack = attacks(square, subset);
+            // Sliders always attack at least one square, so zero marks an unused slot
+            debug_assert!(self.attacks[index] == 0 || self.attacks[index] == attack);
+            self.attacks[index] = attack;
+            subset = subset.wrapping_sub(mask) & mask;
+            if subset == 0 {
+                break;
+            }
+        }
+
+        magic
+    }
+}
+
+/// Relevant rook blockers: edge squares never block anything behind them, so they are omitted.
+fn rook_mask(ray: &Ray) -> u64 {
+    (ray.north & !RANK_8) | (ray.south & !RANK_1) | (ray.east & !FILE_H) | (ray.west & !FILE_A)
+}
+
+fn bishop_mask(ray: &Ray) -> u64 {
+    (ray.north_east | ray.north_west | ray.south_east | ray.south_west)
+        & !(RANK_1 | RANK_8 | FILE_A | FILE_H)
+}
+
+#[rustfmt::skip]
+const ROOK_MAGICS: [u64; 64] = [
+    0x008000908064c000, 0x0040200040001000, 0x0180100080a0010a, 0x8880041000800800,
+    0x1200100201200804, 0x0200020004011008, 0x2180010000800600, 0x0200005088210204,
+    0x0400800040008021, 0x0400400020005000, 0x8240801000200080, 0x8611001004200900,
+    0x008180800c001800, 0x0100800200800400, 0x0a02000102000408, 0x8020802300104280,
+    0x0080004000402000, 0xe010104000402000, 0x0800808010002000, 0xa280210008100100,
+    0x0001818014000800, 0xa002010100080400, 0x0080240001020870, 0x0001020004048845,
+    0x0081826280004004, 0x2020810900284000, 0x0200100080802000, 0x0200080080100080,
+    0x8083080100100500, 0x4406000901000400, 0x0005020080800100, 0x0090204200008114,
+    0x0010400094800420, 0x0900804000802002, 0x0201001841002000, 0x4100080080801000,
+    0x4540040080800800, 0x0002001004040020, 0x0281195814001002, 0x1240800040800100,
+    0x0880042000524004, 0x02c080410206002c, 0x0801200241050010, 0x8400080010008080,
+    0x0008000500090010, 0x0082009084020008, 0x4012000108020004, 0x9000104d08860004,
+    0x2004204114800100, 0x0148802112400300, 0x0202842000100880, 0x001b080080900080,
+    0x001a002008100600, 0x0004008004020080, 0x5181000600040300, 0x0000044401128a00,
+    0x8044110480002441, 0x2008110084402202, 0x90806005090010c1, 0x000420310a004a42,
+    0x0023001004020801, 0x0882001008040102, 0x000230088118020c, 0x0000019025040042,
+];
+
+#[rustfmt::skip]
+const BISHOP_MAGICS: [u64; 64] = [
+    0x0045010808008680, 0x2002080204004898, 0x0210009a10400006, 0x0824050200810200,
+    0x0006061105004090, 0x00010108c0000000, 0x0814040282104004, 0x0012012201106800,
+    0x10823014100c1040, 0x0080c2088802808c, 0x0281108410404000, 0x0101212041826200,
+    0x0020141028221058, 0x2201020202200202, 0x000082a801482000, 0x0000008401411044,
+    0x0007103014300404, 0x0002091110010100, 0x42140012040c0808, 0x0800808802004020,
+    0x90c4004210140000, 0x0800200900a01000, 0x00d0400201108810, 0x80820183814412a0,
+    0x00a01008202202b4, 0x01c2021a09500402, 0x0084440208042400, 0x800400400c090100,
+    0xba10040010802100, 0xd182009006005000, 0x5011021001009004, 0x0020420200510400,
+    0x0292104000468800, 0x00043009091c0500, 0x0280441000020025, 0x0042820080080080,
+    0x0440101010010040, 0x1000900100808080, 0x0108108120089800, 0x0044010200012682,
+    0xc002500420900400, 0x0040482210710800, 0x0002060024000200, 0x0281020a44000800,
+    0xa0021200a4000200, 0x0001301000840840, 0x2868500108444220, 0x0004111041000200,
+    0x8044020842080200, 0x0000220104210200, 0x0000021201044000, 0x0000280884040028,
+    0x4012114010858003, 0x0000081004082b88, 0x3892700508208002, 0x00220a041b060400,
+    0x0812020284014881, 0x010434a282103100, 0x0490400824020800, 0x4a20002c00208800,
+    0x000000a011020200, 0x4002940a02482202, 0x5100100202140406, 0x02102000840540c1,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magics_match_classical() {
+        let magics = get_magics_cache();
+        let mut state: u64 = 1;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for square in 0..64 {
+            let square = Square::from_index(square).unwrap();
+            for _ in 0..200 {
+                let blockers = next() & next();
+                assert_eq!(
+                    magics.rook_attacks(square, blockers),
+                    classical_rook_attacks(square, blockers)
+                );
+                assert_eq!(
+                    magics.bishop_attacks(square, blockers),
+                    classical_bishop_attacks(square, blockers)
+                );
+            }
+        }
+    }
+}
@@ -1,25 +1,299 @@
 use shax::board::Board;
+use shax::engine::meta;
+use shax::epd::{self, EpdRecord};
 use shax::notation::Move;
-use std::io::{self, Write};
+use shax::pgn::Pgn;
+use shax::rating::{self, Player, LEVELS};
+use shax::search::{self, TimeControl, BENCH_DEPTH};
+use shax::stop::StopToken;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{self, Command, Stdio};
+use std::time::Duration;
 
-#[allow(dead_code)]
-fn ascii_u64(bb: u64) -> String {
-    let mut ascii = String::with_capacity(64 * 2 + 8);
-    for rank in (0..8).rev() {
-        for file in 0..8 {
-            let sq = rank * 8 + file;
-            ascii.push(if (bb >> sq) & 1 != 0 { 'x' } else { '.' });
-            ascii.push(' ');
+fn perft(board: &Board, depth: usize) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let divide = board
+        .perft_with_progress(depth, &StopToken::new(), |progress| {
+            let estimate = progress
+                .estimated_total
+                .map_or("?".to_string(), |total| total.to_string());
+            print!(
+                "\r[{}/{}] {}: {} of ~{} nodes",
+                progress.root_moves_done,
+                progress.root_moves,
+                progress.root_move,
+                progress.nodes,
+                estimate
+            );
+            let _ = stdout.flush();
+        })
+        .unwrap();
+    println!();
+
+    for (mov, nodes) in &divide {
+        println!("{mov}: {nodes}");
+    }
+    println!(
+        "Total: {}",
+        divide.iter().map(|(_, nodes)| nodes).sum::<u64>()
+    );
+    Ok(())
+}
+
+/// Runs `go perft` on an external UCI engine and returns the node count of each root move.
+fn engine_divide(engine: &str, board: &Board, depth: usize) -> io::Result<BTreeMap<String, u64>> {
+    let mut child = Command::new(engine)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let unexpected_eof = || io::Error::new(io::ErrorKind::UnexpectedEof, "engine exited");
+
+    writeln!(stdin, "uci")?;
+    stdin.flush()?;
+    while lines.next().ok_or_else(unexpected_eof)?? != "uciok" {}
+    if board.is_chess960() {
+        writeln!(stdin, "setoption name UCI_Chess960 value true")?;
+    }
+    writeln!(stdin, "position fen {}", board.to_fen())?;
+    writeln!(stdin, "go perft {depth}")?;
+    stdin.flush()?;
+
+    // Engines print one "move: nodes" line per root move and a total starting with "Nodes"
+    let mut divide = BTreeMap::new();
+    loop {
+        let line = lines.next().ok_or_else(unexpected_eof)??;
+        if line.starts_with("Nodes") {
+            break;
         }
-        if rank != 0 {
-            ascii.push('\n');
+        if let Some((mov, nodes)) = line.split_once(": ") {
+            if let (Ok(mov), Ok(nodes)) = (Move::from_lan_strict(mov), nodes.trim().parse()) {
+                divide.insert(mov.to_lan(), nodes);
+            }
         }
     }
-    ascii
+    writeln!(stdin, "quit")?;
+    drop(stdin);
+    child.wait()?;
+    Ok(divide)
+}
+
+/// `shax perft-compare --fen F --depth D --engine PATH`: compares perft divide with an
+/// external UCI engine and prints the root moves whose counts differ. Exits with status 1 on
+/// any difference.
+fn perft_compare(args: &[String]) -> io::Result<()> {
+    let mut fen = None;
+    let mut depth = None;
+    let mut engine = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        match (arg.as_str(), value) {
+            ("--fen", Some(value)) => fen = Some(value.clone()),
+            ("--depth", Some(value)) => depth = value.parse::<usize>().ok(),
+            ("--engine", Some(value)) => engine = Some(value.clone()),
+            _ => depth = None,
+        }
+    }
+    let (Some(depth), Some(engine)) = (depth, engine) else {
+        eprintln!("usage: shax perft-compare [--fen FEN] --depth DEPTH --engine PATH");
+        process::exit(2);
+    };
+    let board = match fen {
+        Some(fen) => Board::from_fen(&fen).unwrap_or_else(|err| {
+            eprintln!("invalid FEN: {err}");
+            process::exit(2);
+        }),
+        None => Board::default(),
+    };
+
+    let ours: BTreeMap<String, u64> = board
+        .perft_divide(depth)
+        .into_iter()
+        .map(|(mov, nodes)| (mov.to_lan(), nodes))
+        .collect();
+    let theirs = engine_divide(&engine, &board, depth)?;
+
+    let mut moves: Vec<&String> = ours.keys().chain(theirs.keys()).collect();
+    moves.sort();
+    moves.dedup();
+    let count = |nodes: Option<&u64>| nodes.map_or("-".to_string(), u64::to_string);
+    let mut differences = 0;
+    println!("{:<8}{:>14}{:>14}", "move", "shax", "engine");
+    for mov in moves {
+        let (a, b) = (ours.get(mov), theirs.get(mov));
+        let marker = if a == b { "" } else { "  <--" };
+        differences += usize::from(a != b);
+        println!("{mov:<8}{:>14}{:>14}{marker}", count(a), count(b));
+    }
+    println!(
+        "{:<8}{:>14}{:>14}",
+        "total",
+        ours.values().sum::<u64>(),
+        theirs.values().sum::<u64>()
+    );
+
+    if differences > 0 {
+        println!("{differences} root moves differ");
+        process::exit(1);
+    }
+    println!("All root moves match");
+    Ok(())
+}
+
+fn rate(args: &[String]) -> io::Result<()> {
+    let mut nodes = Some(20_000);
+    let mut pairs = Some(4);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next();
+        match (arg.as_str(), value) {
+            ("--nodes", Some(value)) => nodes = value.parse::<u64>().ok(),
+            ("--pairs", Some(value)) => pairs = value.parse::<u32>().ok(),
+            _ => nodes = None,
+        }
+    }
+    let (Some(nodes), Some(pairs)) = (nodes, pairs) else {
+        eprintln!("usage: shax rate [--nodes NODES] [--pairs PAIRS]");
+        process::exit(2);
+    };
+
+    println!(
+        "Rating {} at {nodes} nodes per move, {} games per level",
+        meta::id_name(),
+        pairs * 2
+    );
+    println!(
+        "{:<8}{:>8}{:>7}{:>6}{:>6}{:>6}{:>8}  performance",
+        "level", "nodes", "elo", "+", "=", "-", "score"
+    );
+    let elo = rating::rate(Player::current(nodes), &LEVELS, pairs, |result| {
+        let (Some(score), Some(performance)) = (result.wdl.score(), result.performance()) else {
+            return;
+        };
+        println!(
+            "{:<8}{:>8}{:>7}{:>6}{:>6}{:>6}{:>7.0}%  {:.0} +/- {:.0}",
+            result.level.name,
+            result.level.player.nodes,
+            result.level.elo,
+            result.wdl.wins,
+            result.wdl.draws,
+            result.wdl.losses,
+            score * 100.0,
+            performance.elo,
+            performance.margin
+        );
+    });
+    match elo {
+        Some(elo) => println!("Estimated Elo: {:.0} +/- {:.0}", elo.elo, elo.margin),
+        None => println!("No games played"),
+    }
+    Ok(())
+}
+
+/// `shax bench [DEPTH]`: searches the benchmark positions and reports the node count and
+/// speed, for comparing builds.
+fn bench(args: &[String]) -> io::Result<()> {
+    let depth = match args.first().map(|arg| arg.parse::<usize>()) {
+        None => BENCH_DEPTH,
+        Some(Ok(depth)) if depth > 0 => depth,
+        Some(_) => {
+            eprintln!("usage: shax bench [DEPTH]");
+            process::exit(2);
+        }
+    };
+
+    let result = search::bench(depth, |position, result| {
+        println!(
+            "{:<20}{:>12} nodes{:>10} nps",
+            position.name,
+            result.nodes,
+            result.nps()
+        );
+    });
+    println!("===========================");
+    println!("Total time (ms) : {}", result.time.as_millis());
+    println!("Nodes searched  : {}", result.nodes);
+    println!("Nodes/second    : {}", result.nps());
+    println!("{}", meta::bench_signature(result.nodes));
+    Ok(())
+}
+
+/// `shax epd FILE [--depth D] [--movetime MS]`: runs a test suite and reports the positions
+/// solved and the points scored.
+fn epd(args: &[String]) -> io::Result<()> {
+    let mut path = None;
+    let mut depth = Some(64);
+    let mut movetime = Some(1000);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => depth = args.next().and_then(|value| value.parse::<usize>().ok()),
+            "--movetime" => movetime = args.next().and_then(|value| value.parse::<u64>().ok()),
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => path = None,
+        }
+    }
+    let (Some(path), Some(depth), Some(movetime)) = (path, depth, movetime) else {
+        eprintln!("usage: shax epd FILE [--depth DEPTH] [--movetime MS]");
+        process::exit(2);
+    };
+
+    let suite = match EpdRecord::parse_suite(&std::fs::read_to_string(&path)?) {
+        Ok(suite) => suite,
+        Err((line, err)) => {
+            eprintln!("{path}:{line}: {err}");
+            process::exit(2);
+        }
+    };
+    let time = TimeControl {
+        movetime: Some(Duration::from_millis(movetime)),
+        ..TimeControl::default()
+    };
+    let result = epd::run_suite(&suite, depth, &time, |outcome| {
+        let mov = outcome
+            .result
+            .best_move
+            .map_or("-".to_string(), |mov| mov.to_lan());
+        println!(
+            "{:<20}{mov:>7}{:>4}/{:<4}{:>8}",
+            outcome.id.as_deref().unwrap_or("?"),
+            outcome.points,
+            outcome.max_points,
+            if outcome.solved { "solved" } else { "" }
+        );
+    });
+    println!(
+        "Solved {}/{}, scored {}/{} points",
+        result.solved, result.positions, result.points, result.max_points
+    );
+    Ok(())
 }
 
 fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("perft-compare") {
+        return perft_compare(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("rate") {
+        return rate(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("bench") {
+        return bench(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("epd") {
+        return epd(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("--version") {
+        println!("{}", meta::id_name());
+        return Ok(());
+    }
+
     let mut board = Board::default();
+    let mut pgn = Pgn::new();
+    meta::annotate(&mut pgn);
     let mut buffer = String::new();
     let mut error = String::new();
     let stdin = io::stdin();
@@ -27,13 +301,18 @@ fn main() -> io::Result<()> {
     loop {
         buffer.clear();
 
-        if board.winner().is_none() {
-            println!("{:#?} to move", board.active());
+        if board.status().is_over() {
+            println!("{:?}, winner: {:?}", board.status(), board.winner())
         } else {
-            println!("Winner: {:?}", board.winner())
+            println!("{:#?} to move", board.active());
         }
 
-        println!("{board:#?}");
+        println!("{board}");
+        println!(
+            "Repetitions: {}, plies until 50-move draw: {}",
+            board.repetition_count(),
+            board.rule50_ply_remaining()
+        );
 
         if !error.is_empty() {
             println!("ERROR: {error}");
@@ -43,9 +322,25 @@ fn main() -> io::Result<()> {
         stdout.flush()?;
         stdin.read_line(&mut buffer)?;
 
+        if buffer.trim() == "pgn" {
+            match pgn.write() {
+                Ok(text) => println!("{text}"),
+                Err(err) => error = format!("{err}"),
+            }
+            continue;
+        }
+
+        if let Some(depth) = buffer.trim().strip_prefix("perft ") {
+            match depth.parse() {
+                Ok(depth) => perft(&board, depth)?,
+                Err(err) => error = format!("{err}"),
+            }
+            continue;
+        }
+
         match Move::from_lan(&buffer) {
             Ok(mov) => match board.make_move(mov) {
-                Ok(()) => (),
+                Ok(()) => pgn.push(mov),
                 Err(err) => {
                     error = format!("{err:?}");
                     continue;
@@ -0,0 +1,198 @@
+//! Bitboard masks of files, ranks and per-square spans used by move generation and
+//! evaluation. Square A1 is bit 0 and H8 is bit 63.
+//!
+//! The per-square tables are generated at compile time.
+
+use crate::notation::{Color, Square};
+
+pub const FILE_A: u64 = 0x0101010101010101;
+pub const FILE_B: u64 = 0x0202020202020202;
+pub const FILE_C: u64 = 0x0404040404040404;
+pub const FILE_D: u64 = 0x0808080808080808;
+pub const FILE_E: u64 = 0x1010101010101010;
+pub const FILE_F: u64 = 0x2020202020202020;
+pub const FILE_G: u64 = 0x4040404040404040;
+pub const FILE_H: u64 = 0x8080808080808080;
+
+pub const RANK_1: u64 = 0x00000000000000ff;
+pub const RANK_2: u64 = 0x000000000000ff00;
+pub const RANK_3: u64 = 0x0000000000ff0000;
+pub const RANK_4: u64 = 0x00000000ff000000;
+pub const RANK_5: u64 = 0x000000ff00000000;
+pub const RANK_6: u64 = 0x0000ff0000000000;
+pub const RANK_7: u64 = 0x00ff000000000000;
+pub const RANK_8: u64 = 0xff00000000000000;
+
+/// Files indexed from A (0) to H (7).
+pub const FILES: [u64; 8] = [
+    FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H,
+];
+
+/// Ranks indexed from 1 (0) to 8 (7).
+pub const RANKS: [u64; 8] = [
+    RANK_1, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8,
+];
+
+pub const LIGHT_SQUARES: u64 = 0x55aa55aa55aa55aa;
+pub const DARK_SQUARES: u64 = !LIGHT_SQUARES;
+
+/// Returns every square on the files that contain at least one square of the bitboard, e.g. to
+/// count occupied files with `(file_fill(bb) & RANK_1).count_ones()`.
+///
+/// ```
+/// use shax::masks::{file_fill, FILE_B, FILE_G};
+///
+/// assert_eq!(file_fill(0x0000_0040_0000_0200), FILE_B | FILE_G);
+/// assert_eq!(file_fill(0), 0);
+/// ```
+pub fn file_fill(bb: u64) -> u64 {
+    let mut bb = bb;
+    bb |= bb << 8;
+    bb |= bb << 16;
+    bb |= bb << 32;
+    bb |= bb >> 8;
+    bb |= bb >> 16;
+    bb | bb >> 32
+}
+
+/// Returns the squares around a king used for king safety: the king square, the squares it
+/// attacks and one more rank in front of them, as seen by the given color.
+///
+/// ```
+/// use shax::masks::king_ring;
+/// use shax::notation::{Color, Square};
+///
+/// // f1, g1, h1, f2, g2, h2, f3, g3 and h3
+/// assert_eq!(king_ring(Square::G1, Color::White), 0x0000000000e0e0e0);
+/// assert_eq!(king_ring(Square::G1, Color::Black), 0x000000000000e0e0);
+/// ```
+pub fn king_ring(square: Square, color: Color) -> u64 {
+    MASKS.king_ring[color as usize][square as usize]
+}
+
+/// Returns the squares in front of the square on the same file, as seen by the given color.
+///
+/// ```
+/// use shax::masks::{pawn_front_span, FILE_E, RANK_1, RANK_2, RANK_3};
+/// use shax::notation::{Color, Square};
+///
+/// assert_eq!(pawn_front_span(Square::E2, Color::White), FILE_E & !(RANK_1 | RANK_2));
+/// assert_eq!(pawn_front_span(Square::E4, Color::Black), FILE_E & (RANK_1 | RANK_2 | RANK_3));
+/// ```
+pub fn pawn_front_span(square: Square, color: Color) -> u64 {
+    MASKS.front_span[color as usize][square as usize]
+}
+
+/// Returns the squares that must be free of enemy pawns for a pawn of the given color on the
+/// square to be passed: its front span and the front spans of both adjacent files.
+///
+/// ```
+/// use shax::masks::{passed_pawn_mask, FILE_C, FILE_D, FILE_E, RANK_5, RANK_6, RANK_7, RANK_8};
+/// use shax::notation::{Color, Square};
+///
+/// assert_eq!(
+///     passed_pawn_mask(Square::D4, Color::White),
+///     (FILE_C | FILE_D | FILE_E) & (RANK_5 | RANK_6 | RANK_7 | RANK_8)
+/// );
+/// ```
+pub fn passed_pawn_mask(square: Square, color: Color) -> u64 {
+    MASKS.passed_pawn[color as usize][square as usize]
+}
+
+struct Masks {
+    king_ring: [[u64; 64]; 2],
+    front_span: [[u64; 64]; 2],
+    passed_pawn: [[u64; 64]; 2],
+}
+
+const MASKS: Masks = generate();
+
+const fn generate() -> Masks {
+    let mut masks = Masks {
+        king_ring: [[0; 64]; 2],
+        front_span: [[0; 64]; 2],
+        passed_pawn: [[0; 64]; 2],
+    };
+
+    let mut square = 0;
+    while square < 64 {
+        let bb = 1u64 << square;
+        let (file, rank) = (square % 8, square / 8);
+
+        let zone = bb
+            | (bb << 8)
+            | (bb >> 8)
+            | ((bb << 1) & !FILE_A)
+            | ((bb >> 1) & !FILE_H)
+            | ((bb >> 7) & !FILE_A)
+            | ((bb << 7) & !FILE_H)
+            | ((bb << 9) & !FILE_A)
+            | ((bb >> 9) & !FILE_H);
+        masks.king_ring[Color::White as usize][square] = zone | (zone << 8);
+        masks.king_ring[Color::Black as usize][square] = zone | (zone >> 8);
+
+        // Shifting by whole ranks keeps the span inside the file, and 64-bit shifts are avoided
+        let north = if rank == 7 {
+            0
+        } else {
+            FILES[file] << ((rank + 1) * 8)
+        };
+        let south = if rank == 0 {
+            0
+        } else {
+            FILES[file] >> ((8 - rank) * 8)
+        };
+        masks.front_span[Color::White as usize][square] = north;
+        masks.front_span[Color::Black as usize][square] = south;
+
+        let adjacent = ((north << 1) & !FILE_A) | ((north >> 1) & !FILE_H);
+        masks.passed_pawn[Color::White as usize][square] = north | adjacent;
+        let adjacent = ((south << 1) & !FILE_A) | ((south >> 1) & !FILE_H);
+        masks.passed_pawn[Color::Black as usize][square] = south | adjacent;
+
+        square += 1;
+    }
+
+    masks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_are_mirrored() {
+        for square in 0..64 {
+            let mirrored = Square::from_index(square ^ 56).unwrap();
+            let square = Square::from_index(square).unwrap();
+            assert_eq!(
+                king_ring(square, Color::White).swap_bytes(),
+                king_ring(mirrored, Color::Black)
+            );
+            assert_eq!(
+                pawn_front_span(square, Color::White).swap_bytes(),
+                pawn_front_span(mirrored, Color::Black)
+            );
+            assert_eq!(
+                passed_pawn_mask(square, Color::White).swap_bytes(),
+                passed_pawn_mask(mirrored, Color::Black)
+            );
+        }
+    }
+
+    #[test]
+    fn test_edge_squares() {
+        assert_eq!(pawn_front_span(Square::A8, Color::White), 0);
+        assert_eq!(pawn_front_span(Square::H1, Color::Black), 0);
+        assert_eq!(
+            passed_pawn_mask(Square::A2, Color::White),
+            (FILE_A | FILE_B) & !(RANK_1 | RANK_2)
+        );
+        assert_eq!(
+            passed_pawn_mask(Square::H7, Color::Black),
+            (FILE_G | FILE_H) & !(RANK_7 | RANK_8)
+        );
+        assert_eq!(king_ring(Square::A8, Color::White).count_ones(), 4);
+        assert_eq!(king_ring(Square::A8, Color::Black).count_ones(), 6);
+    }
+}
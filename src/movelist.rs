@@ -0,0 +1,103 @@
+use crate::notation::{Move, RegularMove, Square};
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+/// Upper bound on the number of legal moves in any chess position (the known maximum is 218).
+pub const MAX_MOVES: usize = 256;
+
+/// Fixed-capacity list of moves stored inline, filled by [`Position::generate_moves_into`]
+/// without touching the allocator.
+///
+/// [`Position::generate_moves_into`]: crate::board::Position::generate_moves_into
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::movelist::MoveList;
+///
+/// let board = Board::default();
+/// let mut moves = MoveList::new();
+/// board.generate_moves_into(&mut moves);
+/// assert_eq!(moves.len(), 20);
+/// assert!(moves.iter().any(|mov| mov.to_lan() == "e2e4"));
+/// ```
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        let empty = Move::Regular(RegularMove {
+            src: Square::A1,
+            dst: Square::A1,
+        });
+        MoveList {
+            moves: [empty; MAX_MOVES],
+            len: 0,
+        }
+    }
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a move.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list already holds [`MAX_MOVES`] moves.
+    pub fn push(&mut self, mov: Move) {
+        self.moves[self.len] = mov;
+        self.len += 1;
+    }
+
+    /// Keeps only the moves for which `keep` returns [`true`], in their order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Move) -> bool) {
+        let mut len = 0;
+        for index in 0..self.len {
+            let mov = self.moves[index];
+            if keep(&mov) {
+                self.moves[len] = mov;
+                len += 1;
+            }
+        }
+        self.len = len;
+    }
+
+    /// Removes all moves, keeping the storage for reuse.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = core::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -1,10 +1,19 @@
+use crate::bitboard::Bitboard;
+use crate::masks::{FILES, RANKS};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use bitflags::bitflags;
-use std::error;
-use std::fmt;
+use core::cmp::Ordering;
+use core::error;
+use core::fmt;
+use core::str::FromStr;
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, FromRepr};
 
 bitflags! {
+    /// Castling rights of both players. Each flag refers to the castling rook recorded by the
+    /// board, which stands on the a- or h-file in standard chess but may be elsewhere in
+    /// Chess960.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct CastlingRights : u8 {
         const WHITE_KINGSIDE = 0b1000;
@@ -22,6 +31,8 @@ pub enum ParseMoveError {
     BadSrcRank(char),
     BadDstFile(char),
     BadDstRank(char),
+    /// Characters were left after a complete move.
+    TrailingCharacter(char),
 }
 
 impl fmt::Display for ParseMoveError {
@@ -43,13 +54,34 @@ impl fmt::Display for ParseMoveError {
             Self::BadDstRank(c) => {
                 write!(f, "expected dst rank to be one of '12345678', got {c:?}")
             }
+            Self::TrailingCharacter(c) => write!(f, "unexpected {c:?} after the move"),
         }
     }
 }
 
 impl error::Error for ParseMoveError {}
 
+/// Moves are ordered by source square, then destination square, then promotion piece (none
+/// first, then rook, knight, bishop and queen), with squares ordered from a1 to h8 rank by
+/// rank. The order does not depend on how moves were generated, so sorting gives a stable
+/// order for output and tests.
+///
+/// ```
+/// use shax::notation::Move;
+///
+/// let mut moves: Vec<Move> = ["e7e8q", "b1c3", "e7e8r", "a2a3", "e7e8"]
+///     .iter()
+///     .map(|lan| Move::from_lan(lan).unwrap())
+///     .collect();
+/// moves.sort();
+/// let sorted: Vec<String> = moves.iter().map(Move::to_lan).collect();
+/// assert_eq!(sorted, ["b1c3", "a2a3", "e7e8", "e7e8r", "e7e8q"]);
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-structural",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum Move {
     /// Regular move, including captures, that is not a promotion or castling move.
     Regular(RegularMove),
@@ -57,17 +89,25 @@ pub enum Move {
     /// Promotion move, where the pawn is promoted to the specified [`PromotionPiece`].
     Promotion(PromotionMove),
 
-    /// Castling move, where the king is moved from its starting [`Square`] to the file G
-    /// (kingside) or C (queenside).
+    /// Castling move, encoded as the king capturing its own rook: `src` is the square of the
+    /// king and `dst` the square of the castling rook. The king ends up on the file G
+    /// (kingside) or C (queenside) and the rook next to it, which also covers Chess960
+    /// positions where the king may not move at all.
     Castling(CastlingMove),
 }
 
 impl Move {
-    /// Parses a move in Long Algebraic Notation (LAN). Examples:
+    /// Parses a move in Long Algebraic Notation (LAN), as typed by a person. Surrounding
+    /// whitespace is ignored, letters may be uppercase, the squares may be separated by `-` or
+    /// `x` and the promotion piece may follow a `=`: `"E2-E4"` and `"e7xe8=Q"` are accepted.
+    /// Use [`Move::from_lan_strict`] for moves exchanged with other programs.
+    ///
+    /// Castling is parsed as a regular king move, either to its destination (`e1g1`) or onto
+    /// the castling rook (`e1h1`, as in Chess960), and resolved by the board when played.
     ///
     /// ```
     /// use shax::notation::{
-    ///     CastlingMove, Move, ParseMoveError, PromotionMove, PromotionPiece, RegularMove, Square,
+    ///     Move, ParseMoveError, PromotionMove, PromotionPiece, RegularMove, Square,
     /// };
     ///
     /// fn main() -> Result<(), ParseMoveError> {
@@ -87,7 +127,7 @@ impl Move {
     ///     );
     ///     assert_eq!(
     ///         Move::from_lan("e1g1")?,
-    ///         Move::Castling(CastlingMove {
+    ///         Move::Regular(RegularMove {
     ///             src: Square::E1,
     ///             dst: Square::G1
     ///         })
@@ -100,71 +140,171 @@ impl Move {
     ///             piece: PromotionPiece::Queen
     ///         })
     ///     );
+    ///     assert_eq!(Move::from_lan(" E2-E4\n")?, Move::from_lan("e2e4")?);
+    ///     assert_eq!(Move::from_lan("e7xe8=Q")?, Move::from_lan("e7e8q")?);
     ///     Ok(())
     /// }
     /// ```
     pub fn from_lan(mov: &str) -> Result<Self, ParseMoveError> {
-        let mut mov = mov.chars();
-
-        let src_file_char = mov.next().ok_or(ParseMoveError::NotEnoughCharacters)?;
-        let src_rank_char = mov.next().ok_or(ParseMoveError::NotEnoughCharacters)?;
-
-        let (src_file, src_rank) = (
-            src_file_char as isize - 'a' as isize,
-            src_rank_char as isize - '1' as isize,
-        );
-
-        if !(0..8).contains(&src_file) {
-            return Err(ParseMoveError::BadSrcFile(src_file_char));
-        }
-        if !(0..8).contains(&src_rank) {
-            return Err(ParseMoveError::BadSrcRank(src_rank_char));
-        }
-
-        let src = Square::from_repr((src_rank * 8 + src_file) as usize).unwrap();
-
-        let dst_file_char = mov.next().ok_or(ParseMoveError::NotEnoughCharacters)?;
-        let dst_rank_char = mov.next().ok_or(ParseMoveError::NotEnoughCharacters)?;
+        Self::parse_lan(mov.trim(), true)
+    }
 
-        let (dst_file, dst_rank) = (
-            dst_file_char as isize - 'a' as isize,
-            dst_rank_char as isize - '1' as isize,
-        );
+    /// Parses a move in the exact LAN of the UCI protocol: four lowercase characters for the
+    /// squares, followed by the promotion piece if any, and nothing else.
+    ///
+    /// ```
+    /// use shax::notation::{Move, ParseMoveError};
+    ///
+    /// assert_eq!(Move::from_lan_strict("e7e8q"), Move::from_lan("e7e8q"));
+    /// assert_eq!(Move::from_lan_strict("E2e4"), Err(ParseMoveError::BadSrcFile('E')));
+    /// assert_eq!(Move::from_lan_strict("e2-e4"), Err(ParseMoveError::BadDstFile('-')));
+    /// assert_eq!(Move::from_lan_strict("e2e4 "), Err(ParseMoveError::BadPromotionPiece(' ')));
+    /// assert_eq!(Move::from_lan_strict("e7e8qq"), Err(ParseMoveError::TrailingCharacter('q')));
+    /// ```
+    pub fn from_lan_strict(mov: &str) -> Result<Self, ParseMoveError> {
+        Self::parse_lan(mov, false)
+    }
 
-        if !(0..8).contains(&dst_file) {
-            return Err(ParseMoveError::BadDstFile(dst_file_char));
+    fn parse_lan(mov: &str, lenient: bool) -> Result<Self, ParseMoveError> {
+        let mut mov = mov
+            .chars()
+            .map(|c| if lenient { c.to_ascii_lowercase() } else { c })
+            .peekable();
+
+        let src = Self::parse_square(
+            &mut mov,
+            ParseMoveError::BadSrcFile,
+            ParseMoveError::BadSrcRank,
+        )?;
+        if lenient {
+            mov.next_if(|&c| c == '-' || c == 'x');
         }
-        if !(0..8).contains(&dst_rank) {
-            return Err(ParseMoveError::BadDstRank(dst_rank_char));
+        let dst = Self::parse_square(
+            &mut mov,
+            ParseMoveError::BadDstFile,
+            ParseMoveError::BadDstRank,
+        )?;
+        if lenient {
+            mov.next_if_eq(&'=');
         }
 
-        let dst = Square::from_repr((dst_rank * 8 + dst_file) as usize).unwrap();
-
         let promote_to = match mov.next() {
             Some('r') => Some(PromotionPiece::Rook),
             Some('n') => Some(PromotionPiece::Knight),
             Some('b') => Some(PromotionPiece::Bishop),
             Some('q') => Some(PromotionPiece::Queen),
-            Some(' ') => None,
-            Some('\n') => None,
-            Some('\r') => None,
             None => None,
             Some(other) => return Err(ParseMoveError::BadPromotionPiece(other)),
         };
+        if let Some(other) = mov.next() {
+            return Err(ParseMoveError::TrailingCharacter(other));
+        }
 
         match promote_to {
             Some(piece) => Ok(Move::Promotion(PromotionMove { src, dst, piece })),
-            None => {
-                if matches!(
-                    (src, dst),
-                    (Square::E1, Square::G1)
-                        | (Square::E8, Square::G8)
-                        | (Square::E1, Square::C1)
-                        | (Square::E8, Square::C8)
-                ) {
-                    Ok(Move::Castling(CastlingMove { src, dst }))
+            None => Ok(Move::Regular(RegularMove { src, dst })),
+        }
+    }
+
+    fn parse_square(
+        mov: &mut impl Iterator<Item = char>,
+        bad_file: fn(char) -> ParseMoveError,
+        bad_rank: fn(char) -> ParseMoveError,
+    ) -> Result<Square, ParseMoveError> {
+        let file_char = mov.next().ok_or(ParseMoveError::NotEnoughCharacters)?;
+        let rank_char = mov.next().ok_or(ParseMoveError::NotEnoughCharacters)?;
+
+        let (file, rank) = (
+            file_char as isize - 'a' as isize,
+            rank_char as isize - '1' as isize,
+        );
+
+        if !(0..8).contains(&file) {
+            return Err(bad_file(file_char));
+        }
+        if !(0..8).contains(&rank) {
+            return Err(bad_rank(rank_char));
+        }
+
+        Ok(Square::from_index((rank * 8 + file) as usize).unwrap())
+    }
+
+    /// Formats the move in Long Algebraic Notation (LAN), the inverse of [`Move::from_lan`].
+    ///
+    /// Standard castling is written as the king move (`e1g1`). Castling from any other
+    /// arrangement is written as the king taking its own rook (`b1a1`), as in Chess960.
+    ///
+    /// ```
+    /// use shax::notation::{CastlingMove, Move, ParseMoveError, Square};
+    ///
+    /// fn main() -> Result<(), ParseMoveError> {
+    ///     assert_eq!(Move::from_lan("e2e4")?.to_lan(), "e2e4");
+    ///     assert_eq!(Move::from_lan("e7e8q")?.to_lan(), "e7e8q");
+    ///
+    ///     let castling = Move::Castling(CastlingMove {
+    ///         src: Square::E1,
+    ///         dst: Square::H1,
+    ///     });
+    ///     assert_eq!(castling.to_lan(), "e1g1");
+    ///     let castling = Move::Castling(CastlingMove {
+    ///         src: Square::B8,
+    ///         dst: Square::A8,
+    ///     });
+    ///     assert_eq!(castling.to_string(), "b8a8");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_lan(&self) -> String {
+        self.to_string()
+    }
+
+    fn sort_key(&self) -> (Square, Square, Option<PromotionPiece>, u8) {
+        match *self {
+            Move::Regular(RegularMove { src, dst }) => (src, dst, None, 0),
+            Move::Promotion(PromotionMove { src, dst, piece }) => (src, dst, Some(piece), 1),
+            Move::Castling(CastlingMove { src, dst }) => (src, dst, None, 2),
+        }
+    }
+}
+
+impl PartialOrd for Move {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Move {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Regular(regular) => write!(f, "{}{}", regular.src, regular.dst),
+            Move::Promotion(promotion) => {
+                let piece = match promotion.piece {
+                    PromotionPiece::Rook => 'r',
+                    PromotionPiece::Knight => 'n',
+                    PromotionPiece::Bishop => 'b',
+                    PromotionPiece::Queen => 'q',
+                };
+                write!(f, "{}{}{piece}", promotion.src, promotion.dst)
+            }
+            Move::Castling(castling) => {
+                let standard = castling.src.file() == File::E
+                    && (castling.dst.file() == File::A || castling.dst.file() == File::H);
+                if standard {
+                    let file = if castling.dst.file() == File::H {
+                        File::G
+                    } else {
+                        File::C
+                    };
+                    let dst = Square::at(file, castling.src.rank());
+                    write!(f, "{}{dst}", castling.src)
                 } else {
-                    Ok(Move::Regular(RegularMove { src, dst }))
+                    write!(f, "{}{}", castling.src, castling.dst)
                 }
             }
         }
@@ -172,18 +312,30 @@ impl Move {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-structural",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct CastlingMove {
     pub src: Square,
     pub dst: Square,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-structural",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct RegularMove {
     pub src: Square,
     pub dst: Square,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde-structural",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct PromotionMove {
     pub src: Square,
     pub dst: Square,
@@ -198,6 +350,16 @@ impl PromotionMove {
     }
 }
 
+/// Side of the board a king castles towards.
+#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(usize)]
+pub enum CastlingSide {
+    /// Towards the h-file, the king ending on g1 or g8.
+    Kingside,
+    /// Towards the a-file, the king ending on c1 or c8.
+    Queenside,
+}
+
 #[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(usize)]
 pub enum Winner {
@@ -207,12 +369,82 @@ pub enum Winner {
 }
 
 #[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(usize)]
+#[repr(u8)]
 pub enum Color {
     White,
     Black,
 }
 
+/// State of the game as decided by the rules, see [`Position::status`].
+///
+/// [`Position::status`]: crate::board::Position::status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    /// The game is still in progress.
+    Ongoing,
+
+    /// The side to move is checkmated by the winner.
+    Checkmate { winner: Color },
+
+    /// The side to move has no legal moves but is not in check.
+    Stalemate,
+
+    /// The same position has occurred five times (FIDE 9.6.1).
+    FivefoldRepetition,
+
+    /// 75 moves have been made by each player without a capture or pawn move (FIDE 9.6.2).
+    SeventyFiveMoves,
+
+    /// The same position has occurred three times, which ends the game automatically under
+    /// [`RulesProfile::Practical`] and can otherwise be claimed (FIDE 9.2).
+    ///
+    /// [`RulesProfile::Practical`]: crate::board::RulesProfile::Practical
+    ThreefoldRepetition,
+
+    /// 50 moves have been made by each player without a capture or pawn move, which ends the
+    /// game automatically under [`RulesProfile::Practical`] and can otherwise be claimed
+    /// (FIDE 9.3).
+    ///
+    /// [`RulesProfile::Practical`]: crate::board::RulesProfile::Practical
+    FiftyMoves,
+
+    /// Neither player has enough material left to checkmate (FIDE 9.6.2).
+    InsufficientMaterial,
+
+    /// Neither player can checkmate because the pawns are locked and the kings cannot break
+    /// through, see [`Position::is_blocked_position`] (FIDE 9.6.2).
+    ///
+    /// [`Position::is_blocked_position`]: crate::board::Position::is_blocked_position
+    DeadPosition,
+}
+
+impl GameStatus {
+    /// Returns [`true`] if the game has concluded.
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::Ongoing
+    }
+
+    /// Returns the winner if the game has concluded, or [`None`] if still in progress.
+    ///
+    /// ```
+    /// use shax::notation::{Color, GameStatus, Winner};
+    ///
+    /// let checkmate = GameStatus::Checkmate {
+    ///     winner: Color::Black,
+    /// };
+    /// assert_eq!(checkmate.winner(), Some(Winner::Black));
+    /// assert_eq!(GameStatus::Stalemate.winner(), Some(Winner::Draw));
+    /// assert_eq!(GameStatus::Ongoing.winner(), None);
+    /// ```
+    pub fn winner(&self) -> Option<Winner> {
+        match self {
+            GameStatus::Ongoing => None,
+            GameStatus::Checkmate { winner } => Some((*winner).into()),
+            _ => Some(Winner::Draw),
+        }
+    }
+}
+
 impl From<Color> for Winner {
     fn from(color: Color) -> Self {
         Winner::from_repr(color as usize).unwrap()
@@ -271,22 +503,46 @@ impl Color {
         }
     }
 
+    /// Returns the castling right of the color on the given side.
+    pub const fn castling_flag(&self, side: CastlingSide) -> CastlingRights {
+        match side {
+            CastlingSide::Kingside => self.kingside_castling_flag(),
+            CastlingSide::Queenside => self.queenside_castling_flag(),
+        }
+    }
+
     /// ```
     /// use shax::notation::Color;
     ///
-    /// assert_eq!(Color::White.promotion_rank(), 7);
-    /// assert_eq!(Color::Black.promotion_rank(), 0);
+    /// use shax::notation::Rank;
+    ///
+    /// assert_eq!(Color::White.promotion_rank(), Rank::R8);
+    /// assert_eq!(Color::Black.promotion_rank(), Rank::R1);
     /// ```
-    pub const fn promotion_rank(&self) -> usize {
+    pub const fn promotion_rank(&self) -> Rank {
         match self {
-            Color::White => 7,
-            Color::Black => 0,
+            Color::White => Rank::R8,
+            Color::Black => Rank::R1,
         }
     }
+
+    /// Rank where the king and rooks of the color start the game.
+    ///
+    /// ```
+    /// use shax::notation::Color;
+    ///
+    /// use shax::notation::Rank;
+    ///
+    /// assert_eq!(Color::White.back_rank(), Rank::R1);
+    /// assert_eq!(Color::Black.back_rank(), Rank::R8);
+    /// ```
+    pub const fn back_rank(&self) -> Rank {
+        self.opposite().promotion_rank()
+    }
 }
 
 #[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(usize)]
+#[repr(u8)]
 pub enum Piece {
     Pawn,
     Rook,
@@ -296,8 +552,12 @@ pub enum Piece {
     King,
 }
 
-#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(usize)]
+#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde-structural",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[repr(u8)]
 pub enum PromotionPiece {
     Rook = 1,
     Knight = 2,
@@ -307,13 +567,149 @@ pub enum PromotionPiece {
 
 impl From<PromotionPiece> for Piece {
     fn from(piece: PromotionPiece) -> Self {
-        Piece::from_repr(piece as usize).unwrap()
+        Piece::from_repr(piece as u8).unwrap()
+    }
+}
+
+/// File of the board, from A on the queenside to H on the kingside.
+///
+/// ```
+/// use shax::masks::FILE_E;
+/// use shax::notation::{File, Square};
+///
+/// assert_eq!(Square::E4.file(), File::E);
+/// assert_eq!(File::from_char('e'), Some(File::E));
+/// assert_eq!(File::E.to_char(), 'e');
+/// assert_eq!(File::E.mask().0, FILE_E);
+/// assert_eq!(File::H.offset(1), None);
+/// ```
+#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+impl File {
+    /// Returns the file with the given index, counting from A = 0 to H = 7.
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < 8 {
+            File::from_repr(index as u8)
+        } else {
+            None
+        }
+    }
+
+    /// Parses a lowercase file letter, `'a'` to `'h'`.
+    pub fn from_char(c: char) -> Option<Self> {
+        File::from_index((c as usize).wrapping_sub('a' as usize))
+    }
+
+    pub fn to_char(self) -> char {
+        (b'a' + self as u8) as char
+    }
+
+    /// Returns the squares of the file.
+    pub fn mask(self) -> Bitboard {
+        Bitboard(FILES[self as usize])
+    }
+
+    /// Returns the file `offset` files towards H, or [`None`] if it would leave the board.
+    pub fn offset(self, offset: isize) -> Option<Self> {
+        File::from_index((self as usize).checked_add_signed(offset)?)
+    }
+}
+
+/// Rank of the board, from 1 on White's side to 8 on Black's side.
+///
+/// ```
+/// use shax::masks::RANK_4;
+/// use shax::notation::{Rank, Square};
+///
+/// assert_eq!(Square::E4.rank(), Rank::R4);
+/// assert_eq!(Rank::from_char('4'), Some(Rank::R4));
+/// assert_eq!(Rank::R4.to_char(), '4');
+/// assert_eq!(Rank::R4.mask().0, RANK_4);
+/// assert_eq!(Rank::R1.offset(-1), None);
+/// ```
+#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Rank {
+    R1,
+    R2,
+    R3,
+    R4,
+    R5,
+    R6,
+    R7,
+    R8,
+}
+
+impl Rank {
+    /// Returns the rank with the given index, counting from 1 = 0 to 8 = 7.
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < 8 {
+            Rank::from_repr(index as u8)
+        } else {
+            None
+        }
+    }
+
+    /// Parses a rank digit, `'1'` to `'8'`.
+    pub fn from_char(c: char) -> Option<Self> {
+        Rank::from_index((c as usize).wrapping_sub('1' as usize))
+    }
+
+    pub fn to_char(self) -> char {
+        (b'1' + self as u8) as char
+    }
+
+    /// Returns the squares of the rank.
+    pub fn mask(self) -> Bitboard {
+        Bitboard(RANKS[self as usize])
+    }
+
+    /// Returns the rank `offset` ranks towards rank 8, or [`None`] if it would leave the
+    /// board.
+    pub fn offset(self, offset: isize) -> Option<Self> {
+        Rank::from_index((self as usize).checked_add_signed(offset)?)
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl From<File> for Bitboard {
+    fn from(file: File) -> Self {
+        file.mask()
+    }
+}
+
+impl From<Rank> for Bitboard {
+    fn from(rank: Rank) -> Self {
+        rank.mask()
     }
 }
 
 #[rustfmt::skip]
-#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(usize)]
+#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde-structural", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
 pub enum Square {
     A1, B1, C1, D1, E1, F1, G1, H1,
     A2, B2, C2, D2, E2, F2, G2, H2,
@@ -326,11 +722,120 @@ pub enum Square {
 }
 
 impl Square {
-    pub fn rank(&self) -> usize {
-        *self as usize & 56
+    /// Returns the square on the given file and rank, both counted from 0, or [`None`] if
+    /// either is off the board.
+    ///
+    /// ```
+    /// use shax::notation::Square;
+    ///
+    /// assert_eq!(Square::new(4, 3), Some(Square::E4));
+    /// assert_eq!(Square::new(8, 0), None);
+    /// ```
+    pub fn new(file: usize, rank: usize) -> Option<Self> {
+        if file < 8 && rank < 8 {
+            Square::from_index(rank * 8 + file)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the square with the given index, counting from A1 = 0 to H8 = 63.
+    ///
+    /// ```
+    /// use shax::notation::Square;
+    ///
+    /// assert_eq!(Square::from_index(28), Some(Square::E4));
+    /// assert_eq!(Square::from_index(64), None);
+    /// ```
+    pub const fn from_index(index: usize) -> Option<Self> {
+        if index < 64 {
+            Square::from_repr(index as u8)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the square on the given file and rank.
+    ///
+    /// ```
+    /// use shax::notation::{File, Rank, Square};
+    ///
+    /// assert_eq!(Square::at(File::E, Rank::R4), Square::E4);
+    /// ```
+    pub const fn at(file: File, rank: Rank) -> Self {
+        match Square::from_index(rank as usize * 8 + file as usize) {
+            Some(square) => square,
+            None => unreachable!(),
+        }
+    }
+
+    pub const fn rank(&self) -> Rank {
+        match Rank::from_index(*self as usize / 8) {
+            Some(rank) => rank,
+            None => unreachable!(),
+        }
+    }
+
+    pub const fn file(&self) -> File {
+        match File::from_index(*self as usize % 8) {
+            Some(file) => file,
+            None => unreachable!(),
+        }
+    }
+
+    /// Returns the square moved by the given number of files and ranks, or [`None`] if it
+    /// would leave the board.
+    ///
+    /// ```
+    /// use shax::notation::Square;
+    ///
+    /// assert_eq!(Square::G1.try_offset(-1, 2), Some(Square::F3));
+    /// assert_eq!(Square::H4.try_offset(1, 0), None);
+    /// ```
+    pub fn try_offset(&self, dx: isize, dy: isize) -> Option<Self> {
+        Some(Square::at(self.file().offset(dx)?, self.rank().offset(dy)?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSquareError(pub String);
+
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected a square such as 'e4', got {:?}", self.0)
+    }
+}
+
+impl error::Error for ParseSquareError {}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    /// Parses a square in algebraic notation such as `e4`.
+    ///
+    /// ```
+    /// use shax::notation::Square;
+    ///
+    /// assert_eq!("e4".parse(), Ok(Square::E4));
+    /// assert!("e9".parse::<Square>().is_err());
+    /// assert_eq!(Square::E4.to_string(), "e4");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => {
+                match (File::from_char(file), Rank::from_char(rank)) {
+                    (Some(file), Some(rank)) => Ok(Square::at(file, rank)),
+                    _ => Err(ParseSquareError(s.to_string())),
+                }
+            }
+            _ => Err(ParseSquareError(s.to_string())),
+        }
     }
+}
 
-    pub fn file(&self) -> usize {
-        *self as usize % 8
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
     }
 }
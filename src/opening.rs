@@ -0,0 +1,185 @@
+use crate::board::Board;
+use crate::eval::evaluate;
+use crate::movelist::MoveList;
+use crate::notation::{GameStatus, Move};
+use crate::zobrist::splitmix64;
+use alloc::vec::Vec;
+
+/// How [`OpeningRandomizer`] picks among the moves that pass its evaluation threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Distribution {
+    /// Every candidate is equally likely.
+    #[default]
+    Uniform,
+    /// Candidates are weighted by how close they are to the best move: the best move gets a
+    /// weight of `threshold + 1`, a move losing `threshold` centipawns a weight of 1.
+    Weighted,
+}
+
+/// Plays the first plies of a game at random so that deterministic self-play does not repeat
+/// the same game over and over, without needing an external opening book.
+///
+/// Each ply is chosen among the legal moves whose static evaluation is at most `threshold`
+/// centipawns worse than the best one, which keeps the openings reasonable. The randomizer is
+/// seeded, so a given seed always produces the same sequence of openings.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::opening::OpeningRandomizer;
+///
+/// let mut randomizer = OpeningRandomizer::new(8, 42).with_threshold(30);
+/// let mut board = Board::default();
+/// let moves = randomizer.play(&mut board);
+/// assert_eq!(moves.len(), 8);
+///
+/// let mut replay = Board::default();
+/// let same = OpeningRandomizer::new(8, 42).with_threshold(30).play(&mut replay);
+/// assert_eq!(moves, same);
+/// assert_eq!(board.to_fen(), replay.to_fen());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpeningRandomizer {
+    plies: usize,
+    threshold: i32,
+    distribution: Distribution,
+    state: u64,
+}
+
+impl OpeningRandomizer {
+    /// Creates a randomizer playing `plies` random plies, accepting moves up to 50 centipawns
+    /// worse than the best one, picked uniformly.
+    pub fn new(plies: usize, seed: u64) -> Self {
+        OpeningRandomizer {
+            plies,
+            threshold: 50,
+            distribution: Distribution::Uniform,
+            state: seed,
+        }
+    }
+
+    /// Sets how many centipawns worse than the best move a move may be to be played. A
+    /// threshold of 0 only keeps moves tied for the best evaluation.
+    pub fn with_threshold(mut self, threshold: i32) -> Self {
+        self.threshold = threshold.max(0);
+        self
+    }
+
+    pub fn with_distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    /// Plays up to the configured number of random plies on `board` and returns them. Fewer
+    /// plies are played if the game ends first.
+    pub fn play(&mut self, board: &mut Board) -> Vec<Move> {
+        let mut played = Vec::with_capacity(self.plies);
+        for _ in 0..self.plies {
+            if board.status() != GameStatus::Ongoing {
+                break;
+            }
+            let Some(mov) = self.pick(board) else {
+                break;
+            };
+            board
+                .make_move(mov)
+                .expect("candidate moves are generated legal");
+            played.push(mov);
+        }
+        played
+    }
+
+    fn pick(&mut self, board: &Board) -> Option<Move> {
+        let mut moves = MoveList::new();
+        board.generate_moves_into(&mut moves);
+
+        let mut scratch = board.clone();
+        let scored: Vec<(Move, i32)> = moves
+            .iter()
+            .map(|&mov| {
+                let undo = scratch.do_move(mov);
+                let score = -evaluate(&scratch);
+                scratch.undo_move(undo);
+                (mov, score)
+            })
+            .collect();
+
+        let best = scored.iter().map(|&(_, score)| score).max()?;
+        let candidates: Vec<(Move, u64)> = scored
+            .into_iter()
+            .filter(|&(_, score)| best - score <= self.threshold)
+            .map(|(mov, score)| {
+                let weight = match self.distribution {
+                    Distribution::Uniform => 1,
+                    Distribution::Weighted => {
+                        (self.threshold as i64 - (best - score) as i64 + 1) as u64
+                    }
+                };
+                (mov, weight)
+            })
+            .collect();
+
+        let total: u64 = candidates.iter().map(|&(_, weight)| weight).sum();
+        let mut roll = self.next_random() % total;
+        for &(mov, weight) in &candidates {
+            if roll < weight {
+                return Some(mov);
+            }
+            roll -= weight;
+        }
+        unreachable!("the roll is below the total weight")
+    }
+
+    fn next_random(&mut self) -> u64 {
+        let value;
+        (self.state, value) = splitmix64(self.state);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_threshold_plays_best_moves() {
+        // the only move that does not lose material is taking the queen
+        let mut board = Board::from_fen("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let moves = OpeningRandomizer::new(1, 7)
+            .with_threshold(0)
+            .play(&mut board);
+        assert_eq!(moves, [Move::from_lan("e4d5").unwrap()]);
+    }
+
+    #[test]
+    fn test_seeds_diversify_openings() {
+        let openings: Vec<_> = (0..16)
+            .map(|seed| {
+                let mut board = Board::default();
+                OpeningRandomizer::new(6, seed)
+                    .with_distribution(Distribution::Weighted)
+                    .play(&mut board);
+                board.to_fen()
+            })
+            .collect();
+        let mut distinct = openings.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert!(distinct.len() > openings.len() / 2);
+    }
+
+    #[test]
+    fn test_unbounded_threshold() {
+        let mut board = Board::default();
+        let moves = OpeningRandomizer::new(4, 3)
+            .with_threshold(i32::MAX)
+            .with_distribution(Distribution::Weighted)
+            .play(&mut board);
+        assert_eq!(moves.len(), 4);
+    }
+
+    #[test]
+    fn test_stops_when_game_ends() {
+        let mut board = Board::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(OpeningRandomizer::new(4, 1).play(&mut board).is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+//! Move ordering for the search. Trying the best moves first makes alpha-beta cut off sooner,
+//! so the order is: the hash move, captures and promotions by MVV-LVA (most valuable victim,
+//! least valuable attacker), the killer moves of the ply, and the other quiet moves by their
+//! history score.
+
+use crate::board::{endpoints, Position};
+use crate::eval::PIECE_VALUES;
+use crate::movelist::{MoveList, MAX_MOVES};
+use crate::notation::{Move, Piece};
+use crate::search::MAX_PLY;
+
+const HASH_MOVE: i32 = 3_000_000;
+const CAPTURE: i32 = 2_000_000;
+const KILLER: i32 = 1_000_000;
+
+/// Killer moves and history scores collected while searching, used to order quiet moves.
+pub(crate) struct MoveOrdering {
+    /// Quiet moves that caused a beta cutoff at each ply, the most recent first.
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    /// Scores of quiet moves by color, source and destination square, increased each time the
+    /// move causes a beta cutoff.
+    history: [[[i32; 64]; 64]; 2],
+}
+
+impl MoveOrdering {
+    pub(crate) fn new() -> Self {
+        MoveOrdering {
+            killers: [[None; 2]; MAX_PLY],
+            history: [[[0; 64]; 64]; 2],
+        }
+    }
+
+    /// Sorts `moves` from the most to the least promising.
+    pub(crate) fn order(
+        &self,
+        board: &Position,
+        moves: &mut MoveList,
+        hash_move: Option<Move>,
+        ply: usize,
+    ) {
+        let mut scores = [0; MAX_MOVES];
+        for (score, &mov) in scores.iter_mut().zip(moves.iter()) {
+            *score = self.score(board, mov, hash_move, ply);
+        }
+
+        // Insertion sort, which is fast on lists this short and keeps equal moves in the order
+        // they were generated
+        for i in 1..moves.len() {
+            let mut j = i;
+            while j > 0 && scores[j - 1] < scores[j] {
+                scores.swap(j - 1, j);
+                moves.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+
+    fn score(&self, board: &Position, mov: Move, hash_move: Option<Move>, ply: usize) -> i32 {
+        if Some(mov) == hash_move {
+            return HASH_MOVE;
+        }
+        if let Some(value) = tactical_value(board, mov) {
+            return CAPTURE + value;
+        }
+        match self.killers.get(ply) {
+            Some([first, _]) if *first == Some(mov) => KILLER + 1,
+            Some([_, second]) if *second == Some(mov) => KILLER,
+            _ => {
+                let (src, dst) = squares(mov);
+                self.history[board.active() as usize][src][dst]
+            }
+        }
+    }
+
+    /// Records that `mov` caused a beta cutoff, searched `depth` plies deep at `ply`.
+    /// Captures and promotions are ordered by MVV-LVA already, so only quiet moves are kept.
+    pub(crate) fn record_cutoff(&mut self, board: &Position, mov: Move, ply: usize, depth: usize) {
+        if tactical_value(board, mov).is_some() || ply >= MAX_PLY {
+            return;
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mov) {
+            killers[1] = killers[0];
+            killers[0] = Some(mov);
+        }
+
+        let (src, dst) = squares(mov);
+        let history = &mut self.history[board.active() as usize][src][dst];
+        *history += (depth * depth) as i32;
+        if *history >= KILLER {
+            // Keep history scores below the killers by halving them all
+            for scores in self.history.iter_mut().flatten() {
+                for score in scores {
+                    *score /= 2;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the MVV-LVA value of a capture or promotion, or [`None`] for a quiet move.
+fn tactical_value(board: &Position, mov: Move) -> Option<i32> {
+    let promotion = match mov {
+        Move::Promotion(promotion) => PIECE_VALUES[Piece::from(promotion.piece) as usize],
+        _ => 0,
+    };
+    let victim = board
+        .captured_by(mov)
+        .map(|piece| PIECE_VALUES[piece as usize]);
+    if victim.is_none() && promotion == 0 {
+        return None;
+    }
+    let attacker = board
+        .on_square(endpoints(mov).0)
+        .map_or(0, |(_, piece)| attacker_rank(piece));
+    Some(10 * (victim.unwrap_or(0) + promotion) - attacker)
+}
+
+/// Order of the attackers from the least to the most valuable, the king last.
+fn attacker_rank(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 0,
+        Piece::Knight => 1,
+        Piece::Bishop => 2,
+        Piece::Rook => 3,
+        Piece::Queen => 4,
+        Piece::King => 5,
+    }
+}
+
+fn squares(mov: Move) -> (usize, usize) {
+    let (src, dst) = endpoints(mov);
+    (src as usize, dst as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    fn ordered(board: &Board, ordering: &MoveOrdering, hash_move: Option<&str>) -> Vec<String> {
+        let mut moves = MoveList::new();
+        board.generate_moves_into(&mut moves);
+        let hash_move = hash_move.map(|lan| Move::from_lan(lan).unwrap());
+        ordering.order(board, &mut moves, hash_move, 0);
+        moves.iter().map(Move::to_lan).collect()
+    }
+
+    #[test]
+    fn test_captures_by_mvv_lva() {
+        // The queen on d5 can be taken by the pawn and the rook, the knight on b5 by the pawn
+        let board = Board::from_fen("4k3/8/8/1n1q4/2P5/8/3R4/4K3 w - - 0 1").unwrap();
+        let moves = ordered(&board, &MoveOrdering::new(), None);
+        assert_eq!(moves[..3], ["c4d5", "d2d5", "c4b5"]);
+
+        let moves = ordered(&board, &MoveOrdering::new(), Some("e1f1"));
+        assert_eq!(moves[..2], ["e1f1", "c4d5"]);
+    }
+
+    #[test]
+    fn test_killers_and_history() {
+        let board = Board::default();
+        let mut ordering = MoveOrdering::new();
+        let mov = |lan| Move::from_lan(lan).unwrap();
+        ordering.record_cutoff(&board, mov("g1f3"), 0, 1);
+        ordering.record_cutoff(&board, mov("e2e4"), 0, 1);
+        ordering.record_cutoff(&board, mov("a2a3"), 1, 4);
+        let moves = ordered(&board, &ordering, None);
+        // Killers of ply 0 first, the most recent one before, then the history of ply 1
+        assert_eq!(moves[..3], ["e2e4", "g1f3", "a2a3"]);
+    }
+}
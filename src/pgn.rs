@@ -0,0 +1,521 @@
+use crate::board::{Board, MoveError};
+use crate::notation::{Color, File, Move, Piece, Rank, RegularMove, Square, Winner};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error;
+use core::fmt::{self, Write};
+
+/// Maximum line length of exported movetext, as recommended by the PGN standard.
+const LINE_WIDTH: usize = 80;
+
+/// Portable Game Notation (PGN) writer for a game played from the starting position.
+///
+/// ```
+/// use shax::notation::Move;
+/// use shax::pgn::Pgn;
+/// use std::error::Error;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let mut pgn = Pgn::new();
+///     pgn.set_tag("White", "Alice");
+///     pgn.set_tag("Black", "Bob");
+///     for lan in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+///         pgn.push(Move::from_lan(lan)?);
+///     }
+///
+///     let text = pgn.write()?;
+///     assert!(text.contains("[White \"Alice\"]"));
+///     assert!(text.contains("[Result \"0-1\"]"));
+///     assert!(text.ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pgn {
+    tags: Vec<(String, String)>,
+    moves: Vec<Move>,
+    /// FEN of the starting position if the game did not start from the standard position.
+    start: Option<String>,
+}
+
+impl Default for Pgn {
+    /// Creates a game with the Seven Tag Roster filled with unknown values.
+    fn default() -> Self {
+        let tags = [
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+            ("Result", "*"),
+        ];
+        Pgn {
+            tags: tags
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            moves: Vec::new(),
+            start: None,
+        }
+    }
+}
+
+impl Pgn {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a game starting from the given position, recorded with the `SetUp` and `FEN`
+    /// tags.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::notation::Move;
+    /// use shax::pgn::Pgn;
+    ///
+    /// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let mut pgn = Pgn::from_position(&board);
+    /// pgn.push(Move::from_lan("a1a8").unwrap());
+    ///
+    /// let text = pgn.write().unwrap();
+    /// assert!(text.contains("[SetUp \"1\"]\n[FEN \"6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\"]"));
+    /// assert!(text.ends_with("1. Ra8# 1-0\n"));
+    /// ```
+    pub fn from_position(board: &Board) -> Self {
+        let fen = board.to_fen();
+        let mut pgn = Self::default();
+        if fen != Board::default().to_fen() {
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", &fen);
+            pgn.start = Some(fen);
+        }
+        pgn
+    }
+
+    /// Sets a tag pair, replacing the value of an existing tag with the same name.
+    ///
+    /// The `Result` tag is filled in automatically by [`Pgn::write`] when the game has
+    /// concluded on the board.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Returns the value of the tag with the given name.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Appends a move played in the game.
+    pub fn push(&mut self, mov: Move) {
+        self.moves.push(mov)
+    }
+
+    /// Returns the moves played in the game.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Replays the game and serializes it as PGN with Standard Algebraic Notation (SAN)
+    /// movetext. Fails if any of the moves is illegal.
+    pub fn write(&self) -> Result<String, MoveError> {
+        let mut board = match &self.start {
+            Some(fen) => Board::from_fen(fen).expect("the FEN was written by a board"),
+            None => Board::default(),
+        };
+        let mut tokens = Vec::with_capacity(self.moves.len() * 3 / 2 + 1);
+        for (ply, &mov) in self.moves.iter().enumerate() {
+            let number = board.fullmove_number();
+            if board.active() == Color::White {
+                tokens.push(format!("{number}."));
+            } else if ply == 0 {
+                tokens.push(format!("{number}..."));
+            }
+            tokens.push(san(&board, mov)?);
+            board.make_move(mov)?;
+        }
+
+        let result = match board.winner() {
+            Some(Winner::White) => "1-0",
+            Some(Winner::Black) => "0-1",
+            Some(Winner::Draw) => "1/2-1/2",
+            None => self.tag("Result").unwrap_or("*"),
+        };
+        tokens.push(result.to_string());
+
+        let mut pgn = String::new();
+        for (name, value) in &self.tags {
+            let value = if name == "Result" { result } else { value };
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(pgn, "[{name} \"{value}\"]").unwrap();
+        }
+        pgn.push('\n');
+
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len > 0 && line_len + 1 + token.len() > LINE_WIDTH {
+                pgn.push('\n');
+                line_len = 0;
+            } else if line_len > 0 {
+                pgn.push(' ');
+                line_len += 1;
+            }
+            line_len += token.len();
+            pgn.push_str(&token);
+        }
+        pgn.push('\n');
+
+        Ok(pgn)
+    }
+}
+
+/// Converts a legal move to Standard Algebraic Notation (SAN), e.g. `Nbd7`, `exd5`, `e8=Q+`
+/// or `O-O-O`.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::notation::Move;
+/// use shax::pgn::san;
+/// use std::error::Error;
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///     let board = Board::default();
+///     assert_eq!(san(&board, Move::from_lan("g1f3")?)?, "Nf3");
+///     assert_eq!(san(&board, Move::from_lan("e2e4")?)?, "e4");
+///     Ok(())
+/// }
+/// ```
+pub fn san(board: &Board, mov: Move) -> Result<String, MoveError> {
+    let color = board.active();
+    let mov = board.validate_move(color, mov)?;
+    let mut after = board.clone();
+    after.make_move(mov)?;
+
+    let mut san = String::new();
+    match mov {
+        Move::Castling(castling) => {
+            if castling.dst.file() > castling.src.file() {
+                san.push_str("O-O")
+            } else {
+                san.push_str("O-O-O")
+            }
+        }
+        Move::Regular(_) | Move::Promotion(_) => {
+            let (src, dst) = match mov {
+                Move::Regular(regular) => (regular.src, regular.dst),
+                Move::Promotion(promotion) => (promotion.src, promotion.dst),
+                Move::Castling(_) => unreachable!(),
+            };
+            let (_, piece) = board.on_square(src).ok_or(MoveError::NothingToMove)?;
+            let capture = board.on_square(dst).is_some()
+                || (piece == Piece::Pawn && src.file() != dst.file());
+
+            if piece == Piece::Pawn {
+                if capture {
+                    san.push(src.file().to_char());
+                }
+            } else {
+                san.push(piece_char(piece));
+
+                let rivals: Vec<_> = board
+                    .moves(color, piece)
+                    .filter_map(|other| match other {
+                        Move::Regular(other) if other.dst == dst && other.src != src => {
+                            Some(other.src)
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                if !rivals.is_empty() {
+                    if rivals.iter().all(|other| other.file() != src.file()) {
+                        san.push(src.file().to_char());
+                    } else if rivals.iter().all(|other| other.rank() != src.rank()) {
+                        san.push(src.rank().to_char());
+                    } else {
+                        san.push(src.file().to_char());
+                        san.push(src.rank().to_char());
+                    }
+                }
+            }
+
+            if capture {
+                san.push('x');
+            }
+            san.push(dst.file().to_char());
+            san.push(dst.rank().to_char());
+
+            if let Move::Promotion(promotion) = mov {
+                san.push('=');
+                san.push(piece_char(promotion.piece.into()));
+            }
+        }
+    }
+
+    if after.winner() == Some(color.into()) {
+        san.push('#');
+    } else if after.is_check(color.opposite()) {
+        san.push('+');
+    }
+
+    Ok(san)
+}
+
+/// How forgiving [`parse_san`] is about notation that does not follow the SAN standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanMode {
+    /// Accepts only the canonical SAN of a move, as written by [`san`]: captures must be
+    /// marked with `x`, checks with `+` or `#`, and disambiguation must be minimal. Suitable
+    /// for importing PGN files.
+    Strict,
+
+    /// Accepts common sloppiness of human input: missing or superfluous `x`, `+` and `#`,
+    /// extra disambiguation, promotions without `=`, lowercase piece letters other than `b`,
+    /// castling with zeros and moves in LAN such as `g1f3` or `e2-e4`.
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSanError {
+    /// The text is not a move in algebraic notation.
+    Malformed(String),
+
+    /// No legal move matches the text.
+    IllegalMove(String),
+
+    /// More than one legal move matches the text.
+    AmbiguousMove(String),
+
+    /// The text matches a legal move whose canonical SAN is different (strict mode only).
+    NonCanonical { text: String, expected: String },
+}
+
+impl fmt::Display for ParseSanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Malformed(text) => write!(f, "malformed move {text:?}"),
+            Self::IllegalMove(text) => write!(f, "no legal move matches {text:?}"),
+            Self::AmbiguousMove(text) => write!(f, "move {text:?} is ambiguous"),
+            Self::NonCanonical { text, expected } => {
+                write!(f, "expected move {text:?} to be written as {expected:?}")
+            }
+        }
+    }
+}
+
+impl error::Error for ParseSanError {}
+
+/// Parses a move of the active color in Standard Algebraic Notation (SAN). Trailing
+/// annotations such as `!` or `?!` are ignored in both modes.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::notation::Move;
+/// use shax::pgn::{parse_san, ParseSanError, SanMode};
+///
+/// let board = Board::default();
+/// let nf3 = Move::from_lan("g1f3").unwrap();
+/// assert_eq!(parse_san(&board, "Nf3", SanMode::Strict), Ok(nf3));
+/// assert_eq!(parse_san(&board, "Ng1f3", SanMode::Lenient), Ok(nf3));
+/// assert_eq!(
+///     parse_san(&board, "Ng1f3", SanMode::Strict),
+///     Err(ParseSanError::NonCanonical {
+///         text: "Ng1f3".to_string(),
+///         expected: "Nf3".to_string()
+///     })
+/// );
+/// ```
+pub fn parse_san(board: &Board, text: &str, mode: SanMode) -> Result<Move, ParseSanError> {
+    let malformed = || ParseSanError::Malformed(text.to_string());
+    let trimmed = text.trim().trim_end_matches(['!', '?']);
+    let lenient = mode == SanMode::Lenient;
+    let color = board.active();
+
+    let body = if lenient {
+        trimmed.trim_end_matches(['+', '#'])
+    } else {
+        trimmed.strip_suffix(['+', '#']).unwrap_or(trimmed)
+    };
+
+    let candidates: Vec<Move> = match body {
+        "O-O" | "O-O-O" => castling_candidates(board, body == "O-O"),
+        "0-0" | "0-0-0" | "o-o" | "o-o-o" if lenient => castling_candidates(board, body.len() == 3),
+        _ => {
+            let pattern = SanPattern::parse(body, lenient).ok_or_else(malformed)?;
+            board
+                .color_moves(color)
+                .filter(|&mov| pattern.matches(board, mov))
+                .collect()
+        }
+    };
+
+    let mov = match candidates[..] {
+        [mov] => mov,
+        [] => return Err(ParseSanError::IllegalMove(text.to_string())),
+        _ => return Err(ParseSanError::AmbiguousMove(text.to_string())),
+    };
+
+    if !lenient {
+        let expected = san(board, mov).expect("generated moves are legal");
+        if trimmed != expected {
+            return Err(ParseSanError::NonCanonical {
+                text: text.to_string(),
+                expected,
+            });
+        }
+    }
+
+    Ok(mov)
+}
+
+fn castling_candidates(board: &Board, kingside: bool) -> Vec<Move> {
+    let color = board.active();
+    board
+        .squares(color, Piece::King)
+        .flat_map(|king| board.castling_moves(color, king))
+        .filter(|mov| match mov {
+            Move::Castling(castling) => (castling.dst.file() > castling.src.file()) == kingside,
+            _ => false,
+        })
+        .collect()
+}
+
+/// Parts of a non-castling SAN move. The source square is only known as far as the
+/// disambiguation goes.
+struct SanPattern {
+    piece: Option<Piece>,
+    src_file: Option<File>,
+    src_rank: Option<Rank>,
+    dst: Square,
+    promotion: Option<Piece>,
+}
+
+impl SanPattern {
+    fn parse(body: &str, lenient: bool) -> Option<Self> {
+        let mut chars: Vec<char> = body.chars().collect();
+
+        let promotion = match chars[..] {
+            [.., '=', piece] => {
+                chars.truncate(chars.len() - 2);
+                Some(parse_piece(piece, lenient)?)
+            }
+            [.., '1' | '8', piece] if lenient && piece.is_ascii_alphabetic() => {
+                chars.pop();
+                Some(parse_piece(piece, lenient)?)
+            }
+            _ => None,
+        };
+
+        let piece = match chars.first() {
+            Some(&c) if c.is_ascii_uppercase() || (lenient && "nrqk".contains(c)) => {
+                chars.remove(0);
+                Some(parse_piece(c, lenient)?)
+            }
+            _ => None,
+        };
+
+        if chars.len() < 2 {
+            return None;
+        }
+        let dst_rank = chars.pop().and_then(Rank::from_char)?;
+        let dst_file = chars.pop().and_then(File::from_char)?;
+        let dst = Square::at(dst_file, dst_rank);
+
+        match chars.last() {
+            Some('x') => {
+                chars.pop();
+            }
+            Some('-') if lenient => {
+                chars.pop();
+            }
+            _ => (),
+        }
+
+        let (src_file, src_rank) = match chars[..] {
+            [] => (None, None),
+            [c] => match (File::from_char(c), Rank::from_char(c)) {
+                (None, None) => return None,
+                parts => parts,
+            },
+            [file, rank] => (Some(File::from_char(file)?), Some(Rank::from_char(rank)?)),
+            _ => return None,
+        };
+
+        // A full source square without a piece letter is LAN, which may move any piece
+        let piece = match (piece, src_file, src_rank) {
+            (None, Some(_), Some(_)) if lenient => None,
+            (None, _, _) => Some(Piece::Pawn),
+            (piece, _, _) => piece,
+        };
+
+        Some(SanPattern {
+            piece,
+            src_file,
+            src_rank,
+            dst,
+            promotion,
+        })
+    }
+
+    fn matches(&self, board: &Board, mov: Move) -> bool {
+        let (src, dst, promotion) = match mov {
+            Move::Regular(regular) => (regular.src, regular.dst, None),
+            Move::Promotion(promotion) => {
+                (promotion.src, promotion.dst, Some(promotion.piece.into()))
+            }
+            Move::Castling(_) => return self.matches_castling(board, mov),
+        };
+        let piece = board.on_square(src).map(|(_, piece)| piece);
+
+        dst == self.dst
+            && promotion == self.promotion
+            && self.piece.is_none_or(|expected| piece == Some(expected))
+            && self.src_file.is_none_or(|file| src.file() == file)
+            && self.src_rank.is_none_or(|rank| src.rank() == rank)
+    }
+
+    /// Returns [`true`] if the pattern is castling written in LAN, such as `e1g1` or the king
+    /// taking its own rook, resolved against the board as [`Move::from_lan`] moves are.
+    fn matches_castling(&self, board: &Board, mov: Move) -> bool {
+        match (self.src_file, self.src_rank) {
+            (Some(file), Some(rank)) if self.promotion.is_none() => {
+                let lan = Move::Regular(RegularMove {
+                    src: Square::at(file, rank),
+                    dst: self.dst,
+                });
+                self.piece.is_none_or(|piece| piece == Piece::King)
+                    && board.validate_move(board.active(), lan) == Ok(mov)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn parse_piece(c: char, lenient: bool) -> Option<Piece> {
+    let c = if lenient { c.to_ascii_uppercase() } else { c };
+    match c {
+        'R' => Some(Piece::Rook),
+        'N' => Some(Piece::Knight),
+        'B' => Some(Piece::Bishop),
+        'Q' => Some(Piece::Queen),
+        'K' => Some(Piece::King),
+        _ => None,
+    }
+}
+
+fn piece_char(piece: Piece) -> char {
+    match piece {
+        Piece::Pawn => 'P',
+        Piece::Rook => 'R',
+        Piece::Knight => 'N',
+        Piece::Bishop => 'B',
+        Piece::Queen => 'Q',
+        Piece::King => 'K',
+    }
+}
@@ -0,0 +1,242 @@
+//! Well-known positions for tests, benchmarks and examples, with their perft node counts.
+//!
+//! The positions are parsed from FEN at compile time, so [`NamedPosition::board`] builds a
+//! [`Board`] without parsing.
+//!
+//! ```
+//! use shax::positions::{ALL, KIWIPETE};
+//!
+//! let board = KIWIPETE.board();
+//! assert_eq!(board.to_fen(), KIWIPETE.fen);
+//! assert_eq!(board.perft(2), KIWIPETE.perft[1]);
+//!
+//! for position in ALL {
+//!     assert_eq!(position.board().perft(1), position.perft[0], "{}", position.name);
+//! }
+//! ```
+
+use crate::board::Board;
+use crate::notation::{CastlingRights, Color, Square};
+
+/// Bitboards of the standard starting position, indexed by color and then by piece.
+pub const DEFAULT_BITBOARDS: [[u64; 6]; 2] = [
+    [
+        // White
+        0x000000000000ff00, // Pawn
+        0x0000000000000081, // Rook
+        0x0000000000000042, // Knight
+        0x0000000000000024, // Bishop
+        0x0000000000000008, // Queen
+        0x0000000000000010, // King
+    ],
+    [
+        // Black
+        0x00ff000000000000, // Pawn
+        0x8100000000000000, // Rook
+        0x4200000000000000, // Knight
+        0x2400000000000000, // Bishop
+        0x0800000000000000, // Queen
+        0x1000000000000000, // King
+    ],
+];
+
+/// A named position of standard chess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamedPosition {
+    pub name: &'static str,
+    pub fen: &'static str,
+    /// Perft node counts from depth 1 up.
+    pub perft: &'static [u64],
+    bitboards: [[u64; 6]; 2],
+    active: Color,
+    castling: CastlingRights,
+    en_passant: Option<Square>,
+    halfclock: u16,
+    fullmove: u16,
+}
+
+impl NamedPosition {
+    /// Creates a position from a FEN of standard chess. Invalid FENs fail to compile when
+    /// used in a constant.
+    const fn new(name: &'static str, fen: &'static str, perft: &'static [u64]) -> Self {
+        let fen = fen.as_bytes();
+        let mut bitboards = [[0; 6]; 2];
+        let mut i = 0;
+        let (mut file, mut rank) = (0, 7);
+        while fen[i] != b' ' {
+            match fen[i] {
+                b'/' => {
+                    file = 0;
+                    rank -= 1;
+                }
+                b'1'..=b'8' => file += (fen[i] - b'0') as usize,
+                c => {
+                    let color = if c.is_ascii_uppercase() { 0 } else { 1 };
+                    let piece = match c.to_ascii_lowercase() {
+                        b'p' => 0,
+                        b'r' => 1,
+                        b'n' => 2,
+                        b'b' => 3,
+                        b'q' => 4,
+                        b'k' => 5,
+                        _ => panic!("bad piece"),
+                    };
+                    bitboards[color][piece] |= 1 << (rank * 8 + file);
+                    file += 1;
+                }
+            }
+            i += 1;
+        }
+
+        let active = match fen[i + 1] {
+            b'w' => Color::White,
+            b'b' => Color::Black,
+            _ => panic!("bad active color"),
+        };
+        i += 3;
+
+        let mut castling = CastlingRights::empty();
+        while fen[i] != b' ' {
+            let flag = match fen[i] {
+                b'K' => CastlingRights::WHITE_KINGSIDE,
+                b'Q' => CastlingRights::WHITE_QUEENSIDE,
+                b'k' => CastlingRights::BLACK_KINGSIDE,
+                b'q' => CastlingRights::BLACK_QUEENSIDE,
+                b'-' => CastlingRights::empty(),
+                _ => panic!("bad castling rights"),
+            };
+            castling = castling.union(flag);
+            i += 1;
+        }
+        i += 1;
+
+        let en_passant = if fen[i] == b'-' {
+            i += 2;
+            None
+        } else {
+            let square = (fen[i + 1] - b'1') as usize * 8 + (fen[i] - b'a') as usize;
+            i += 3;
+            Square::from_index(square)
+        };
+
+        let mut halfclock = 0;
+        while fen[i] != b' ' {
+            halfclock = halfclock * 10 + (fen[i] - b'0') as u16;
+            i += 1;
+        }
+        i += 1;
+        let mut fullmove = 0;
+        while i < fen.len() {
+            fullmove = fullmove * 10 + (fen[i] - b'0') as u16;
+            i += 1;
+        }
+
+        NamedPosition {
+            name,
+            fen: match core::str::from_utf8(fen) {
+                Ok(fen) => fen,
+                Err(_) => panic!("bad FEN"),
+            },
+            perft,
+            bitboards,
+            active,
+            castling,
+            en_passant,
+            halfclock,
+            fullmove,
+        }
+    }
+
+    /// Returns a board set up with the position.
+    pub fn board(&self) -> Board {
+        Board::from_setup(
+            self.bitboards,
+            self.active,
+            self.castling,
+            self.en_passant,
+            self.halfclock,
+            self.fullmove,
+        )
+    }
+}
+
+/// The standard starting position.
+pub const STARTING: NamedPosition = NamedPosition::new(
+    "Starting position",
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    &[20, 400, 8902, 197281, 4865609],
+);
+
+/// Position 2 of the Chess Programming Wiki perft results, rich in castling, en passant and
+/// promotions.
+pub const KIWIPETE: NamedPosition = NamedPosition::new(
+    "Kiwipete",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    &[48, 2039, 97862, 4085603],
+);
+
+/// Position 3 of the Chess Programming Wiki perft results: a rook endgame with en passant
+/// captures that expose the king along the rank.
+pub const POSITION_3: NamedPosition = NamedPosition::new(
+    "Position 3",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    &[14, 191, 2812, 43238, 674624],
+);
+
+/// Position 4 of the Chess Programming Wiki perft results, with promotions and checks.
+pub const POSITION_4: NamedPosition = NamedPosition::new(
+    "Position 4",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    &[6, 264, 9467, 422333],
+);
+
+/// Position 5 of the Chess Programming Wiki perft results.
+pub const POSITION_5: NamedPosition = NamedPosition::new(
+    "Position 5",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    &[44, 1486, 62379, 2103487],
+);
+
+/// Position 6 of the Chess Programming Wiki perft results, a quiet middlegame.
+pub const POSITION_6: NamedPosition = NamedPosition::new(
+    "Position 6",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    &[46, 2079, 89890, 3894594],
+);
+
+/// Lucena position: White wins by building a bridge with the rook.
+pub const LUCENA: NamedPosition = NamedPosition::new(
+    "Lucena position",
+    "1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1",
+    &[14, 200, 2746, 38747],
+);
+
+/// Philidor position: Black draws by keeping the rook on the sixth rank.
+pub const PHILIDOR: NamedPosition = NamedPosition::new(
+    "Philidor position",
+    "4k3/8/r7/3PK3/8/8/8/3R4 b - - 0 1",
+    &[19, 292, 5046, 83867],
+);
+
+/// Every position of the module.
+pub const ALL: [NamedPosition; 8] = [
+    STARTING, KIWIPETE, POSITION_3, POSITION_4, POSITION_5, POSITION_6, LUCENA, PHILIDOR,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boards_match_fen() {
+        for position in ALL {
+            let board = position.board();
+            let parsed = Board::from_fen(position.fen).unwrap();
+            assert_eq!(board.to_fen(), position.fen);
+            assert_eq!(board.zobrist(), parsed.zobrist(), "{}", position.name);
+            assert_eq!(board.status(), parsed.status(), "{}", position.name);
+            assert_eq!(board.perft(2), position.perft[1], "{}", position.name);
+        }
+        assert_eq!(STARTING.board().to_fen(), Board::default().to_fen());
+    }
+}
@@ -0,0 +1,222 @@
+//! Python bindings, enabled by the `pyo3` feature, for scripting, dataset generation and
+//! teaching notebooks. The API mirrors the Rust one. Build the extension module as a `cdylib`
+//! and place it on the Python path under the module name:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features pyo3 --crate-type cdylib
+//! cp target/release/libshax.so shax.so
+//! ```
+//!
+//! ```python
+//! from shax import Board, Color, Move
+//!
+//! board = Board()
+//! len(board.color_moves(Color.White))  # 20
+//! board.make_move("e2e4")
+//! board.make_move(Move("e7e5"))
+//! board.to_fen()  # 'rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2'
+//! Board.from_fen("8/8/8/8/8/8/8/K1k5 w - - 0 1")
+//! ```
+//!
+//! Errors are raised as `ValueError` with the message of the underlying Rust error.
+
+use crate::board::Board;
+use crate::notation::{Color, Move, PromotionPiece};
+use crate::wire::encode_move;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+fn value_error(err: impl ToString) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// Side of a player, exported to Python as `Color`.
+#[pyclass(name = "Color", eq, eq_int, from_py_object)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PyColor {
+    White,
+    Black,
+}
+
+impl From<Color> for PyColor {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::White => PyColor::White,
+            Color::Black => PyColor::Black,
+        }
+    }
+}
+
+impl From<PyColor> for Color {
+    fn from(color: PyColor) -> Self {
+        match color {
+            PyColor::White => Color::White,
+            PyColor::Black => Color::Black,
+        }
+    }
+}
+
+/// A move, exported to Python as `Move` and created from its LAN.
+#[pyclass(name = "Move", frozen, from_py_object)]
+#[derive(Clone)]
+pub struct PyMove(Move);
+
+#[pymethods]
+impl PyMove {
+    #[new]
+    fn new(lan: &str) -> PyResult<Self> {
+        Self::from_lan(lan)
+    }
+
+    #[staticmethod]
+    fn from_lan(lan: &str) -> PyResult<Self> {
+        Move::from_lan(lan).map(PyMove).map_err(value_error)
+    }
+
+    fn to_lan(&self) -> String {
+        self.0.to_lan()
+    }
+
+    /// Source square, e.g. `"e2"`.
+    #[getter]
+    fn src(&self) -> String {
+        self.to_lan()[..2].to_string()
+    }
+
+    /// Destination square as written in LAN, e.g. `"e4"`.
+    #[getter]
+    fn dst(&self) -> String {
+        self.to_lan()[2..4].to_string()
+    }
+
+    /// Promotion piece (`"q"`, `"r"`, `"b"` or `"n"`), or `None`.
+    #[getter]
+    fn promotion(&self) -> Option<&'static str> {
+        match self.0 {
+            Move::Promotion(promotion) => Some(match promotion.piece {
+                PromotionPiece::Queen => "q",
+                PromotionPiece::Rook => "r",
+                PromotionPiece::Bishop => "b",
+                PromotionPiece::Knight => "n",
+            }),
+            _ => None,
+        }
+    }
+
+    fn __str__(&self) -> String {
+        self.to_lan()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Move('{}')", self.to_lan())
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __hash__(&self) -> u64 {
+        encode_move(self.0).into()
+    }
+}
+
+/// Either a `Move` or its LAN string.
+#[derive(FromPyObject)]
+enum MoveArg {
+    Move(PyMove),
+    Lan(String),
+}
+
+/// A chess game, exported to Python as `Board`.
+#[pyclass(name = "Board", from_py_object)]
+#[derive(Clone)]
+pub struct PyBoard(Board);
+
+#[pymethods]
+impl PyBoard {
+    /// Creates a board from a FEN, or with the standard starting position.
+    #[new]
+    #[pyo3(signature = (fen = None))]
+    fn new(fen: Option<&str>) -> PyResult<Self> {
+        match fen {
+            Some(fen) => Self::from_fen(fen),
+            None => Ok(PyBoard(Board::default())),
+        }
+    }
+
+    #[staticmethod]
+    fn from_fen(fen: &str) -> PyResult<Self> {
+        Board::from_fen(fen).map(PyBoard).map_err(value_error)
+    }
+
+    fn to_fen(&self) -> String {
+        self.0.to_fen()
+    }
+
+    fn active(&self) -> PyColor {
+        self.0.active().into()
+    }
+
+    /// Plays a `Move` or a move in LAN, raising `ValueError` if it is illegal.
+    fn make_move(&mut self, mov: MoveArg) -> PyResult<()> {
+        let mov = match mov {
+            MoveArg::Move(mov) => mov.0,
+            MoveArg::Lan(lan) => Move::from_lan(&lan).map_err(value_error)?,
+        };
+        self.0.make_move(mov).map_err(value_error)
+    }
+
+    /// Legal moves of the given color.
+    fn color_moves(&self, color: PyColor) -> Vec<PyMove> {
+        self.0.color_moves(color.into()).map(PyMove).collect()
+    }
+
+    /// Legal moves of the side to move, empty once the game is over.
+    fn legal_moves(&self) -> Vec<PyMove> {
+        if self.0.status().is_over() {
+            return Vec::new();
+        }
+        self.color_moves(self.active())
+    }
+
+    fn is_check(&self, color: PyColor) -> bool {
+        self.0.is_check(color.into())
+    }
+
+    fn is_checkmate(&self) -> bool {
+        self.0.is_checkmate()
+    }
+
+    fn is_stalemate(&self) -> bool {
+        self.0.is_stalemate()
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.0.status().is_over()
+    }
+
+    fn perft(&self, depth: usize) -> u64 {
+        self.0.perft(depth)
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Board('{}')", self.0.to_fen())
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "shax")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBoard>()?;
+    module.add_class::<PyColor>()?;
+    module.add_class::<PyMove>()?;
+    Ok(())
+}
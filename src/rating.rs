@@ -0,0 +1,304 @@
+//! Rough Elo estimation by playing against frozen reference levels, for checking progress
+//! between releases without a test cluster.
+//!
+//! Each [`Level`] searches a fixed number of nodes per move with its own copy of the
+//! evaluation parameters, so its games depend neither on the machine and its load nor on how
+//! the build being rated evaluates. The Elo of the levels are nominal anchors chosen once and
+//! never changed: estimates are comparable with each other, not with any rating list.
+
+mod snapshot;
+
+use crate::board::Board;
+use crate::eval::{self, EvalParams};
+use crate::game::Game;
+use crate::notation::{Color, Winner};
+use crate::opening::OpeningRandomizer;
+use crate::search::search_nodes;
+use crate::stats::{EloEstimate, Wdl};
+
+/// Games still running after this many plies are scored as draws.
+const MAX_PLIES: usize = 300;
+
+/// Random plies played before the players take over, so that the games differ.
+const OPENING_PLIES: usize = 6;
+
+/// Frozen reference opponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    pub name: &'static str,
+    /// Nominal Elo of the level.
+    pub elo: i32,
+    pub player: Player,
+}
+
+/// Reference levels, from weakest to strongest.
+pub const LEVELS: [Level; 4] = [
+    Level {
+        name: "novice",
+        elo: 800,
+        player: Player {
+            nodes: 200,
+            eval: &snapshot::NOVICE,
+        },
+    },
+    Level {
+        name: "casual",
+        elo: 1100,
+        player: Player {
+            nodes: 1_000,
+            eval: &snapshot::CASUAL,
+        },
+    },
+    Level {
+        name: "club",
+        elo: 1400,
+        player: Player {
+            nodes: 5_000,
+            eval: &snapshot::CLUB,
+        },
+    },
+    Level {
+        name: "strong",
+        elo: 1700,
+        player: Player {
+            nodes: 20_000,
+            eval: &snapshot::STRONG,
+        },
+    },
+];
+
+/// Engine playing with a fixed number of nodes per move and the given evaluation parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Player {
+    pub nodes: u64,
+    pub eval: &'static EvalParams,
+}
+
+impl Player {
+    /// The current build, evaluating with [`eval::PARAMS`].
+    pub fn current(nodes: u64) -> Self {
+        Player {
+            nodes,
+            eval: &eval::PARAMS,
+        }
+    }
+}
+
+/// Plays a game between two players after a random opening picked by `seed`, adjudicating it
+/// as a draw after 300 plies. Draws are claimed as soon as possible.
+///
+/// ```
+/// use shax::rating::{play, Player};
+///
+/// let weak = Player::current(50);
+/// assert_eq!(play(weak, weak, 7), play(weak, weak, 7));
+/// ```
+pub fn play(white: Player, black: Player, seed: u64) -> Winner {
+    let mut start = Board::default();
+    OpeningRandomizer::new(OPENING_PLIES, seed).play(&mut start);
+    let mut game = Game::from_board(start).with_auto_claim_draws(true);
+
+    while !game.is_over() && game.moves().len() < MAX_PLIES {
+        let player = match game.board().active() {
+            Color::White => white,
+            Color::Black => black,
+        };
+        let result = search_nodes(game.board(), player.nodes, player.eval);
+        let Some(mov) = result.best_move else { break };
+        game.make_move(mov).expect("the search returns legal moves");
+    }
+    game.result().unwrap_or(Winner::Draw)
+}
+
+/// Games of the rated player against one level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelResult {
+    pub level: Level,
+    pub wdl: Wdl,
+}
+
+impl LevelResult {
+    /// Elo the rated player performed at against the level, or [`None`] without games.
+    ///
+    /// ```
+    /// use shax::rating::{LevelResult, LEVELS};
+    /// use shax::stats::Wdl;
+    ///
+    /// let wdl = Wdl { wins: 3, draws: 2, losses: 3 };
+    /// let performance = LevelResult { level: LEVELS[2], wdl }.performance().unwrap();
+    /// assert_eq!(performance.elo, LEVELS[2].elo as f64);
+    /// assert!(performance.margin > 100.0);
+    /// ```
+    pub fn performance(&self) -> Option<EloEstimate> {
+        let difference = self.wdl.elo()?;
+        Some(EloEstimate {
+            elo: self.level.elo as f64 + difference.elo,
+            margin: difference.margin,
+        })
+    }
+}
+
+/// Plays `pairs` pairs of games against each level, usually [`LEVELS`], each pair from the
+/// same opening with colors reversed, and estimates the Elo of the player as
+/// [`estimate_elo`] does. `progress` is called after each level.
+pub fn rate(
+    player: Player,
+    levels: &[Level],
+    pairs: u32,
+    mut progress: impl FnMut(&LevelResult),
+) -> Option<EloEstimate> {
+    let mut results = Vec::with_capacity(levels.len());
+    for &level in levels {
+        let mut result = LevelResult {
+            level,
+            wdl: Wdl::default(),
+        };
+        for seed in 0..pairs as u64 {
+            result
+                .wdl
+                .record(play(player, level.player, seed), Color::White);
+            result
+                .wdl
+                .record(play(level.player, player, seed), Color::Black);
+        }
+        progress(&result);
+        results.push(result);
+    }
+    estimate_elo(&results)
+}
+
+/// Estimates the Elo for which the expected score against the levels equals the points
+/// scored, or [`None`] without games. Estimates are capped 800 points beyond the weakest and
+/// strongest levels played, as a perfect score only gives a lower bound. The margin is the
+/// one of all the games counted together, as if played against a single opponent.
+///
+/// ```
+/// use shax::rating::{estimate_elo, LevelResult, LEVELS};
+/// use shax::stats::Wdl;
+///
+/// let even = LevelResult {
+///     level: LEVELS[2],
+///     wdl: Wdl { wins: 3, draws: 2, losses: 3 },
+/// };
+/// let estimate = estimate_elo(&[even]).unwrap();
+/// assert_eq!(estimate.elo.round() as i32, LEVELS[2].elo);
+/// assert!(estimate.margin > 100.0);
+/// ```
+pub fn estimate_elo(results: &[LevelResult]) -> Option<EloEstimate> {
+    let played = || results.iter().filter(|result| result.wdl.games() > 0);
+    let weakest = played().map(|result| result.level.elo).min()?;
+    let strongest = played().map(|result| result.level.elo).max()?;
+    let total = played().fold(Wdl::default(), |total, result| Wdl {
+        wins: total.wins + result.wdl.wins,
+        draws: total.draws + result.wdl.draws,
+        losses: total.losses + result.wdl.losses,
+    });
+    let points = total.wins as f64 + total.draws as f64 / 2.0;
+    let expected = |elo: f64| -> f64 {
+        played()
+            .map(|result| {
+                let difference = (result.level.elo as f64 - elo) / 400.0;
+                result.wdl.games() as f64 / (1.0 + 10f64.powf(difference))
+            })
+            .sum()
+    };
+
+    // The expected score grows with the Elo, so bisect for the one matching the points
+    let (mut low, mut high) = (weakest as f64 - 800.0, strongest as f64 + 800.0);
+    for _ in 0..64 {
+        let middle = (low + high) / 2.0;
+        if expected(middle) < points {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(EloEstimate {
+        elo: (low + high) / 2.0,
+        margin: total.elo()?.margin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Evaluator;
+
+    fn result(level: Level, wins: u64, draws: u64, losses: u64) -> LevelResult {
+        LevelResult {
+            level,
+            wdl: Wdl {
+                wins,
+                draws,
+                losses,
+            },
+        }
+    }
+
+    fn elo(results: &[LevelResult]) -> Option<i32> {
+        estimate_elo(results).map(|estimate| estimate.elo.round() as i32)
+    }
+
+    #[test]
+    fn test_estimate_elo() {
+        assert_eq!(elo(&[]), None);
+        assert_eq!(elo(&[result(LEVELS[0], 0, 0, 0)]), None);
+
+        // 75% against a level is about 191 points above it
+        assert_eq!(elo(&[result(LEVELS[1], 3, 0, 1)]), Some(1291));
+        assert_eq!(elo(&[result(LEVELS[1], 1, 0, 3)]), Some(909));
+
+        // Even results against levels around it
+        let results = [result(LEVELS[1], 3, 0, 1), result(LEVELS[3], 1, 0, 3)];
+        assert_eq!(elo(&results), Some(1400));
+
+        // Perfect scores are capped, with no upper bound on the margin
+        assert_eq!(elo(&[result(LEVELS[3], 8, 0, 0)]), Some(2500));
+        assert_eq!(elo(&[result(LEVELS[0], 0, 0, 8)]), Some(0));
+        let perfect = estimate_elo(&[result(LEVELS[3], 8, 0, 0)]).unwrap();
+        assert_eq!(perfect.margin, f64::INFINITY);
+    }
+
+    #[test]
+    fn test_estimate_margin() {
+        // More games narrow the interval around the same estimate
+        let few = estimate_elo(&[result(LEVELS[1], 6, 4, 2)]).unwrap();
+        let many = estimate_elo(&[result(LEVELS[1], 60, 40, 20)]).unwrap();
+        assert_eq!(few.elo.round(), many.elo.round());
+        assert!(many.margin < few.margin / 3.0);
+    }
+
+    #[test]
+    fn test_levels_are_ordered() {
+        for pair in LEVELS.windows(2) {
+            assert!(pair[0].elo < pair[1].elo);
+            assert!(pair[0].player.nodes < pair[1].player.nodes);
+        }
+    }
+
+    #[test]
+    fn test_levels_are_frozen() {
+        // The levels keep their own parameters, whatever the build evaluates with
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let scores = LEVELS.map(|level| level.player.eval.eval(&board));
+        assert_eq!(scores, [-377, -382, -382, -389]);
+    }
+
+    #[test]
+    fn test_rate_counts_every_game() {
+        let levels = LEVELS.map(|level| Level {
+            player: Player {
+                nodes: 20,
+                ..level.player
+            },
+            ..level
+        });
+        let mut rated = 0;
+        let elo = rate(Player::current(20), &levels, 2, |result| {
+            assert_eq!(result.wdl.games(), 4);
+            rated += 1;
+        });
+        assert_eq!(rated, levels.len());
+        assert!(elo.is_some());
+    }
+}
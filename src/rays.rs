@@ -1,14 +1,133 @@
-use crate::{FILE_A, FILE_H};
-use std::sync::OnceLock;
+//! Precomputed rays and the squares joining two squares, for pin and check detection.
 
-static RAYS_CACHE: OnceLock<[Ray; 64]> = OnceLock::new();
+use crate::masks::{FILE_A, FILE_H};
+use crate::notation::Square;
+use strum_macros::{EnumIter, FromRepr};
 
-pub fn get_rays_cache() -> &'static [Ray; 64] {
-    RAYS_CACHE.get_or_init(get_rays)
+static RAYS: [Ray; 64] = get_rays();
+
+static BETWEEN: [[u64; 64]; 64] = get_between();
+
+static LINE: [[u64; 64]; 64] = get_line();
+
+pub(crate) fn get_rays_cache() -> &'static [Ray; 64] {
+    &RAYS
+}
+
+/// Returns the squares strictly between `a` and `b` if they share a rank, file or diagonal, or
+/// an empty bitboard otherwise.
+///
+/// ```
+/// use shax::notation::Square;
+/// use shax::rays::between;
+///
+/// assert_eq!(between(Square::A1, Square::D4), 1 << Square::B2 as u64 | 1 << Square::C3 as u64);
+/// assert_eq!(between(Square::E1, Square::E2), 0);
+/// assert_eq!(between(Square::A1, Square::B3), 0);
+/// ```
+#[inline(always)]
+pub fn between(a: Square, b: Square) -> u64 {
+    BETWEEN[a as usize][b as usize]
+}
+
+/// Returns the whole rank, file or diagonal through `a` and `b`, from edge to edge of the
+/// board, or an empty bitboard if they do not share one or are the same square.
+///
+/// ```
+/// use shax::masks::FILE_E;
+/// use shax::notation::Square;
+/// use shax::rays::line;
+///
+/// assert_eq!(line(Square::E2, Square::E5), FILE_E);
+/// assert_eq!(line(Square::A1, Square::H8), 0x8040201008040201);
+/// assert_eq!(line(Square::A1, Square::B3), 0);
+/// ```
+#[inline(always)]
+pub fn line(a: Square, b: Square) -> u64 {
+    LINE[a as usize][b as usize]
+}
+
+/// Direction of a sliding move, as seen from White.
+#[derive(FromRepr, EnumIter, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Direction {
+    pub const fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::SouthWest => Direction::NorthEast,
+        }
+    }
+
+    /// Returns whether the direction runs along a rank or file rather than a diagonal.
+    pub const fn is_orthogonal(self) -> bool {
+        matches!(
+            self,
+            Direction::North | Direction::South | Direction::East | Direction::West
+        )
+    }
+}
+
+/// Moves every square of the bitboard one step in the given direction, dropping the squares
+/// that would leave the board.
+///
+/// ```
+/// use shax::masks::{FILE_A, FILE_B, FILE_H};
+/// use shax::notation::Square;
+/// use shax::rays::{shift, Direction};
+///
+/// assert_eq!(shift(1 << Square::E4 as u64, Direction::NorthEast), 1 << Square::F5 as u64);
+/// assert_eq!(shift(FILE_A, Direction::East), FILE_B);
+/// assert_eq!(shift(FILE_H, Direction::East), 0);
+/// ```
+#[inline(always)]
+pub const fn shift(bitboard: u64, direction: Direction) -> u64 {
+    match direction {
+        Direction::North => north_one(bitboard),
+        Direction::South => south_one(bitboard),
+        Direction::East => east_one(bitboard),
+        Direction::West => west_one(bitboard),
+        Direction::NorthEast => north_one(east_one(bitboard)),
+        Direction::NorthWest => north_one(west_one(bitboard)),
+        Direction::SouthEast => south_one(east_one(bitboard)),
+        Direction::SouthWest => south_one(west_one(bitboard)),
+    }
+}
+
+/// Returns the squares from `square`, excluded, to the edge of the board in the given
+/// direction, as on an empty board.
+///
+/// ```
+/// use shax::masks::FILE_E;
+/// use shax::notation::Square;
+/// use shax::rays::{ray, Direction};
+///
+/// assert_eq!(ray(Square::E2, Direction::North), FILE_E & !0xffff);
+/// assert_eq!(ray(Square::H1, Direction::East), 0);
+/// ```
+#[inline(always)]
+pub fn ray(square: Square, direction: Direction) -> u64 {
+    RAYS[square as usize].get(direction)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct Ray {
+pub(crate) struct Ray {
     pub north: u64,
     pub south: u64,
     pub east: u64,
@@ -19,45 +138,77 @@ pub struct Ray {
     pub south_west: u64,
 }
 
-fn get_rays() -> [Ray; 64] {
-    let mut rays = [Ray::default(); 64];
+impl Ray {
+    #[inline(always)]
+    pub const fn get(&self, direction: Direction) -> u64 {
+        match direction {
+            Direction::North => self.north,
+            Direction::South => self.south,
+            Direction::East => self.east,
+            Direction::West => self.west,
+            Direction::NorthEast => self.north_east,
+            Direction::NorthWest => self.north_west,
+            Direction::SouthEast => self.south_east,
+            Direction::SouthWest => self.south_west,
+        }
+    }
+}
+
+const fn get_rays() -> [Ray; 64] {
+    let empty = Ray {
+        north: 0,
+        south: 0,
+        east: 0,
+        west: 0,
+        north_east: 0,
+        north_west: 0,
+        south_east: 0,
+        south_west: 0,
+    };
+    let mut rays = [empty; 64];
 
-    for (square, ray) in rays.iter_mut().enumerate() {
-        ray.north = north_ray(square);
-        ray.south = south_ray(square);
-        ray.east = east_ray(square);
-        ray.west = west_ray(square);
+    let mut square = 0;
+    while square < 64 {
+        rays[square].north = north_ray(square);
+        rays[square].south = south_ray(square);
+        rays[square].east = east_ray(square);
+        rays[square].west = west_ray(square);
+        square += 1;
     }
 
     let mut north_east_slider: u64 = 0x8040201008040200;
     let mut south_east_slider: u64 = 0x0002040810204080;
-    for file in 0..8 {
+    let mut file = 0;
+    while file < 8 {
         let mut north_slider = north_east_slider;
         let mut south_slider = south_east_slider;
-        for rank8 in (0..64).step_by(8) {
-            rays[rank8 + file].north_east = north_slider;
+        let mut rank = 0;
+        while rank < 8 {
+            rays[rank * 8 + file].north_east = north_slider;
             north_slider = north_one(north_slider);
-        }
-        for rank8 in (0..64).step_by(8).rev() {
-            rays[rank8 + file].south_east = south_slider;
+            rays[(7 - rank) * 8 + file].south_east = south_slider;
             south_slider = south_one(south_slider);
+            rank += 1;
         }
         north_east_slider = east_one(north_east_slider);
         south_east_slider = east_one(south_east_slider);
+        file += 1;
     }
 
     let mut north_west_slider: u64 = 0x0102040810204000;
     let mut south_west_slider: u64 = 0x0040201008040201;
-    for file in (0..8).rev() {
+    let mut file = 8;
+    while file > 0 {
+        file -= 1;
         let mut north_slider = north_west_slider;
         let mut south_slider = south_west_slider;
-        for rank8 in (0..64).step_by(8) {
-            rays[rank8 + file].north_west = north_slider;
+        let mut rank = 0;
+        while rank < 8 {
+            rays[rank * 8 + file].north_west = north_slider;
             north_slider = north_one(north_slider);
-        }
-        for rank8 in (0..64).step_by(8).rev() {
-            rays[rank8 + file].south_west = south_slider;
+            rays[(7 - rank) * 8 + file].south_west = south_slider;
             south_slider = south_one(south_slider);
+            rank += 1;
         }
         north_west_slider = west_one(north_west_slider);
         south_west_slider = west_one(south_west_slider);
@@ -66,49 +217,171 @@ fn get_rays() -> [Ray; 64] {
     rays
 }
 
+/// Returns the rays of a square in the eight directions, each followed by the ray in the
+/// opposite direction.
+const fn directions(ray: &Ray) -> [(u64, u64); 8] {
+    [
+        (ray.north, ray.south),
+        (ray.south, ray.north),
+        (ray.east, ray.west),
+        (ray.west, ray.east),
+        (ray.north_east, ray.south_west),
+        (ray.south_west, ray.north_east),
+        (ray.north_west, ray.south_east),
+        (ray.south_east, ray.north_west),
+    ]
+}
+
+const fn get_between() -> [[u64; 64]; 64] {
+    let rays = get_rays();
+    let mut between = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let from_a = directions(&rays[a]);
+        let mut direction = 0;
+        while direction < 8 {
+            let ray = from_a[direction].0;
+            let mut b = 0;
+            while b < 64 {
+                if ray & 1 << b != 0 {
+                    // The ray from `a` towards `b` meets the ray from `b` back towards `a`
+                    let back = directions(&rays[b])[direction].1;
+                    between[a][b] = ray & back;
+                }
+                b += 1;
+            }
+            direction += 1;
+        }
+        a += 1;
+    }
+    between
+}
+
+const fn get_line() -> [[u64; 64]; 64] {
+    let rays = get_rays();
+    let mut line = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let from_a = directions(&rays[a]);
+        let mut direction = 0;
+        while direction < 8 {
+            let (ray, opposite) = from_a[direction];
+            let mut b = 0;
+            while b < 64 {
+                if ray & 1 << b != 0 {
+                    line[a][b] = ray | opposite | 1 << a;
+                }
+                b += 1;
+            }
+            direction += 1;
+        }
+        a += 1;
+    }
+    line
+}
+
 #[inline(always)]
-fn north_ray(square: usize) -> u64 {
+const fn north_ray(square: usize) -> u64 {
     (FILE_A << 8) << square
 }
 
 #[inline(always)]
-fn south_ray(square: usize) -> u64 {
+const fn south_ray(square: usize) -> u64 {
     (FILE_H >> 8) >> (square ^ 63)
 }
 
 #[inline(always)]
-fn east_ray(square: usize) -> u64 {
+const fn east_ray(square: usize) -> u64 {
     ((1 << (square | 7)) - (1 << square)) << 1
 }
 
 #[inline(always)]
-fn west_ray(square: usize) -> u64 {
+const fn west_ray(square: usize) -> u64 {
     (1 << square) - (1 << (square & 56))
 }
 
 #[inline(always)]
-fn north_one(bb: u64) -> u64 {
+const fn north_one(bb: u64) -> u64 {
     bb << 8
 }
 
 #[inline(always)]
-fn south_one(bb: u64) -> u64 {
+const fn south_one(bb: u64) -> u64 {
     bb >> 8
 }
 
 #[inline(always)]
-fn east_one(bb: u64) -> u64 {
+const fn east_one(bb: u64) -> u64 {
     (bb << 1) & !FILE_A
 }
 
 #[inline(always)]
-fn west_one(bb: u64) -> u64 {
+const fn west_one(bb: u64) -> u64 {
     (bb >> 1) & !FILE_H
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::attacks;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_between_and_line() {
+        for a in Square::iter() {
+            for b in Square::iter() {
+                let (a_bb, b_bb) = (1 << a as usize, 1 << b as usize);
+                let aligned = a != b
+                    && (attacks::rook_attacks(a, 0) | attacks::bishop_attacks(a, 0)) & b_bb != 0;
+                let expected = if !aligned {
+                    0
+                } else if attacks::rook_attacks(a, 0) & b_bb != 0 {
+                    attacks::rook_attacks(a, b_bb) & attacks::rook_attacks(b, a_bb)
+                } else {
+                    attacks::bishop_attacks(a, b_bb) & attacks::bishop_attacks(b, a_bb)
+                };
+                assert_eq!(between(a, b), expected, "{a:?} {b:?}");
+                assert_eq!(between(a, b), between(b, a));
+
+                assert_eq!(line(a, b), line(b, a));
+                if aligned {
+                    assert_eq!(
+                        line(a, b) & (between(a, b) | a_bb | b_bb),
+                        between(a, b) | a_bb | b_bb
+                    );
+                    assert!(line(a, b).count_ones() >= 2);
+                } else {
+                    assert_eq!(line(a, b), 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shift_walks_rays() {
+        for square in Square::iter() {
+            for direction in Direction::iter() {
+                let (mut walked, mut bb) = (0, 1 << square as u64);
+                while bb != 0 {
+                    bb = shift(bb, direction);
+                    walked |= bb;
+                }
+                assert_eq!(walked, ray(square, direction), "{square:?} {direction:?}");
+
+                let slider = if direction.is_orthogonal() {
+                    attacks::rook_attacks(square, 0)
+                } else {
+                    attacks::bishop_attacks(square, 0)
+                };
+                assert_eq!(ray(square, direction) & !slider, 0);
+                for other in
+                    Square::iter().filter(|&other| ray(square, direction) & 1 << other as u64 != 0)
+                {
+                    assert!(ray(other, direction.opposite()) & 1 << square as u64 != 0);
+                }
+            }
+        }
+    }
 
     #[test]
     fn test_rays_north() {
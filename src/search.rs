@@ -0,0 +1,2266 @@
+use crate::board::{Board, Position};
+use crate::correction::CorrectionHistory;
+use crate::eval::{EvalTerms, Evaluator};
+use crate::movelist::MoveList;
+use crate::notation::{Color, Move, Piece};
+use crate::ordering::MoveOrdering;
+use crate::positions;
+use crate::stop::{StopToken, Stopped};
+use crate::tt::{Bound, Entry, TranspositionTable};
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Deepest ply the search can reach, including the quiescence search.
+pub const MAX_PLY: usize = 128;
+
+const MATE: i32 = 32_000;
+const INFINITY: i32 = MATE + 1;
+
+/// Least depth at which a node without a hash move is searched one ply shallower.
+const IIR_DEPTH: usize = 4;
+
+/// Margins of reverse futility pruning, indexed by the remaining depth: a node whose static
+/// evaluation exceeds beta by the margin is assumed to fail high without being searched. Nodes
+/// deeper than the table are always searched.
+const RFP_MARGINS: [i32; 4] = [0, 120, 240, 360];
+
+/// Score of a position from the perspective of the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    /// Evaluation in centipawns: positive scores favor the side to move.
+    Centipawns(i32),
+    /// Forced mate in the given number of moves: positive if the side to move mates, negative
+    /// if it gets mated. `Mate(0)` means the side to move is already checkmated.
+    Mate(i32),
+}
+
+impl Score {
+    fn from_internal(score: i32) -> Self {
+        let plies = MATE - score.abs();
+        if plies as usize > MAX_PLY {
+            Score::Centipawns(score)
+        } else if score > 0 {
+            Score::Mate((plies + 1) / 2)
+        } else {
+            Score::Mate(-plies / 2)
+        }
+    }
+}
+
+/// Formats the score as in the UCI `info` command: `cp 35` or `mate -2`.
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "cp {cp}"),
+            Score::Mate(moves) => write!(f, "mate {moves}"),
+        }
+    }
+}
+
+/// Why a search returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The requested depth was searched completely.
+    Depth,
+    /// The time limit was reached.
+    Time,
+    /// The node limit was reached.
+    Nodes,
+    /// Stopping was requested through a [`StopToken`].
+    Stopped,
+    /// A mate within the number of moves of [`SearchLimits::mate`] was found.
+    Mate,
+}
+
+/// Outcome of a search, with the statistics engines usually report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// Best move found, [`None`] if the side to move has no legal moves.
+    pub best_move: Option<Move>,
+    pub score: Score,
+    /// Depth of the main search in plies.
+    pub depth: usize,
+    /// Deepest ply reached, including the quiescence search.
+    pub seldepth: usize,
+    pub nodes: u64,
+    pub time: Duration,
+    /// Principal variation: the expected line of play, starting with the best move.
+    pub pv: Vec<Move>,
+    pub stop_reason: StopReason,
+}
+
+impl SearchResult {
+    /// Nodes searched per second.
+    pub fn nps(&self) -> u64 {
+        match self.time.as_micros() {
+            0 => 0,
+            micros => (self.nodes as u128 * 1_000_000 / micros) as u64,
+        }
+    }
+
+    /// Formats the result as a UCI `info` line for the `multipv`th best line, counted from 1.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::search::search;
+    ///
+    /// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let info = search(&board, 1).info(1);
+    /// assert!(info.starts_with("info depth 1 seldepth "));
+    /// assert!(info.contains(" multipv 1 score mate 1 nodes "));
+    /// assert!(info.ends_with(" pv a1a8"));
+    /// ```
+    pub fn info(&self, multipv: usize) -> String {
+        let mut info = format!(
+            "info depth {} seldepth {} multipv {multipv} score {} nodes {} nps {} time {}",
+            self.depth,
+            self.seldepth,
+            self.score,
+            self.nodes,
+            self.nps(),
+            self.time.as_millis()
+        );
+        if !self.pv.is_empty() {
+            info.push_str(" pv");
+            for mov in &self.pv {
+                info.push(' ');
+                info.push_str(&mov.to_lan());
+            }
+        }
+        info
+    }
+}
+
+/// Root move a search is starting on, as reported to [`search_with_currmove`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrMove {
+    /// Depth of the iteration searching the move.
+    pub depth: usize,
+    pub mov: Move,
+    /// Position of the move in the order the iteration searches the root moves, counted
+    /// from 1.
+    pub number: usize,
+    /// Time since the search started.
+    pub time: Duration,
+}
+
+impl CurrMove {
+    /// Formats the report as a UCI `info` line.
+    ///
+    /// ```
+    /// use shax::notation::Move;
+    /// use shax::search::CurrMove;
+    /// use std::time::Duration;
+    ///
+    /// let report = CurrMove {
+    ///     depth: 12,
+    ///     mov: Move::from_lan("e2e4").unwrap(),
+    ///     number: 3,
+    ///     time: Duration::from_secs(5),
+    /// };
+    /// assert_eq!(report.info(), "info depth 12 currmove e2e4 currmovenumber 3");
+    /// ```
+    pub fn info(&self) -> String {
+        format!(
+            "info depth {} currmove {} currmovenumber {}",
+            self.depth,
+            self.mov.to_lan(),
+            self.number
+        )
+    }
+}
+
+/// Time control of a search, as given by the UCI `go` command. Remaining times and
+/// increments are per player; the side to move is taken from the board.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeControl {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    /// Moves until the next time control, if it is not sudden death.
+    pub movestogo: Option<u32>,
+    /// Exact time to search, which overrides the clock.
+    pub movetime: Option<Duration>,
+    /// Time kept in reserve on the clock for communication delays, the equivalent of the UCI
+    /// `Move Overhead` option. [`DEFAULT_MOVE_OVERHEAD`] if not set.
+    pub move_overhead: Option<Duration>,
+}
+
+/// Time kept in reserve on the clock for communication delays, unless the time control sets
+/// its own.
+pub const DEFAULT_MOVE_OVERHEAD: Duration = Duration::from_millis(20);
+
+impl TimeControl {
+    /// Returns the soft limit, after which no new iteration is started, and the hard limit,
+    /// at which the search is aborted. [`None`] if the search is not limited by time.
+    fn budget(&self, color: Color) -> Option<(Duration, Duration)> {
+        if let Some(movetime) = self.movetime {
+            return Some((movetime, movetime));
+        }
+        let (time, inc) = match color {
+            Color::White => (self.wtime?, self.winc.unwrap_or_default()),
+            Color::Black => (self.btime?, self.binc.unwrap_or_default()),
+        };
+        let overhead = self.move_overhead.unwrap_or(DEFAULT_MOVE_OVERHEAD);
+        let available = time.saturating_sub(overhead);
+        let moves = self.movestogo.unwrap_or(30).max(1);
+        let hard = available / 2;
+        let soft = (available / moves + inc * 3 / 4).min(hard);
+        Some((soft, (soft * 4).min(hard)))
+    }
+}
+
+/// Limits of a search started with [`search_with_limits`]. The search ends at the first limit
+/// reached, and only when stopped through its [`StopToken`] if none is set.
+///
+/// Parsing reads the arguments of the UCI `go` command:
+///
+/// ```
+/// use shax::notation::Move;
+/// use shax::search::SearchLimits;
+/// use std::time::Duration;
+///
+/// let limits: SearchLimits = "wtime 60000 btime 55000 winc 1000 binc 1000 nodes 100000"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(limits.time.wtime, Some(Duration::from_secs(60)));
+/// assert_eq!(limits.nodes, Some(100_000));
+///
+/// let limits: SearchLimits = "depth 6 searchmoves e2e4 d2d4".parse().unwrap();
+/// assert_eq!(limits.depth, Some(6));
+/// assert_eq!(limits.root_moves, ["e2e4", "d2d4"].map(|lan| Move::from_lan(lan).unwrap()));
+/// assert!("depth six".parse::<SearchLimits>().is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Maximum depth in plies.
+    pub depth: Option<usize>,
+    /// Maximum number of nodes. The search may end a few nodes later.
+    pub nodes: Option<u64>,
+    /// Clock or fixed time per move, allotted as by [`search_timed`].
+    pub time: TimeControl,
+    /// Number of moves within which to find a mate, the equivalent of UCI `go mate`. The
+    /// search ends as soon as such a mate is found, or after the depth a mate in that many
+    /// moves needs.
+    pub mate: Option<usize>,
+    /// Root moves to search, the equivalent of UCI `go searchmoves`. Every legal move is
+    /// searched if empty. Moves that are not legal are ignored, and if none is legal every
+    /// legal move is searched as well.
+    pub root_moves: Vec<Move>,
+    /// Whether the search runs on the opponent's time, the equivalent of UCI `go ponder`. The
+    /// time control only applies from [`StopToken::ponderhit`] on, and the search does not
+    /// return before that or a stop, even once its other limits are reached.
+    pub ponder: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseGoError {
+    /// A keyword is not followed by a value.
+    MissingValue(String),
+    BadValue {
+        keyword: String,
+        value: String,
+    },
+    UnknownToken(String),
+}
+
+impl fmt::Display for ParseGoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingValue(keyword) => write!(f, "expected a value after {keyword:?}"),
+            Self::BadValue { keyword, value } => {
+                write!(f, "invalid value {value:?} for {keyword:?}")
+            }
+            Self::UnknownToken(token) => write!(f, "unknown token {token:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseGoError {}
+
+impl FromStr for SearchLimits {
+    type Err = ParseGoError;
+
+    /// Parses the arguments of the UCI `go` command, without the `go` itself. Times are in
+    /// milliseconds, negative clock times counting as zero. `infinite` is accepted and changes
+    /// nothing.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut limits = SearchLimits::default();
+        let mut tokens = text.split_whitespace().peekable();
+        while let Some(token) = tokens.next() {
+            let mut value = || -> Result<&str, ParseGoError> {
+                tokens
+                    .next()
+                    .ok_or_else(|| ParseGoError::MissingValue(token.to_string()))
+            };
+            let bad_value = |value: &str| ParseGoError::BadValue {
+                keyword: token.to_string(),
+                value: value.to_string(),
+            };
+            let millis = |value: &str| -> Result<Option<Duration>, ParseGoError> {
+                let millis: i64 = value.parse().map_err(|_| bad_value(value))?;
+                Ok(Some(Duration::from_millis(millis.max(0) as u64)))
+            };
+            match token {
+                "depth" => {
+                    let value = value()?;
+                    limits.depth = Some(value.parse().map_err(|_| bad_value(value))?);
+                }
+                "nodes" => {
+                    let value = value()?;
+                    limits.nodes = Some(value.parse().map_err(|_| bad_value(value))?);
+                }
+                "mate" => {
+                    let value = value()?;
+                    limits.mate = Some(value.parse().map_err(|_| bad_value(value))?);
+                }
+                "movestogo" => {
+                    let value = value()?;
+                    limits.time.movestogo = Some(value.parse().map_err(|_| bad_value(value))?);
+                }
+                "wtime" => limits.time.wtime = millis(value()?)?,
+                "btime" => limits.time.btime = millis(value()?)?,
+                "winc" => limits.time.winc = millis(value()?)?,
+                "binc" => limits.time.binc = millis(value()?)?,
+                "movetime" => limits.time.movetime = millis(value()?)?,
+                "searchmoves" => {
+                    while let Some(mov) = tokens
+                        .peek()
+                        .and_then(|lan| Move::from_lan_strict(lan).ok())
+                    {
+                        limits.root_moves.push(mov);
+                        tokens.next();
+                    }
+                }
+                "ponder" => limits.ponder = true,
+                "infinite" => {}
+                _ => return Err(ParseGoError::UnknownToken(token.to_string())),
+            }
+        }
+        Ok(limits)
+    }
+}
+
+/// Searches the position with iterative deepening within the limits. The result tells which
+/// limit ended the search, with [`StopReason::Mate`] once the mate asked for is found.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::search::{search_with_limits, Score, SearchLimits, StopReason};
+/// use shax::stop::StopToken;
+///
+/// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+/// let limits = SearchLimits {
+///     mate: Some(2),
+///     ..SearchLimits::default()
+/// };
+/// let result = search_with_limits(&board, &limits, &StopToken::new());
+/// assert_eq!(result.score, Score::Mate(1));
+/// assert_eq!(result.stop_reason, StopReason::Mate);
+/// ```
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::notation::Move;
+/// use shax::search::{search_with_limits, SearchLimits};
+/// use shax::stop::StopToken;
+///
+/// // Examine the quiet king move instead of winning the queen
+/// let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+/// let limits: SearchLimits = "depth 3 searchmoves e1f1".parse().unwrap();
+/// let result = search_with_limits(&board, &limits, &StopToken::new());
+/// assert_eq!(result.best_move, Some(Move::from_lan("e1f1").unwrap()));
+/// ```
+pub fn search_with_limits(board: &Board, limits: &SearchLimits, stop: &StopToken) -> SearchResult {
+    search_with_progress(board, limits, stop, |_| {})
+}
+
+/// Same as [`search_with_limits`], but calls `progress` after each completed iteration with
+/// its result so far, so that the analysis can be shown while the search runs. Each report
+/// has [`StopReason::Depth`], its depth having been searched completely.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::search::{search_with_progress, SearchLimits};
+/// use shax::stop::StopToken;
+///
+/// let limits: SearchLimits = "depth 4".parse().unwrap();
+/// let mut depths = Vec::new();
+/// let result = search_with_progress(&Board::default(), &limits, &StopToken::new(), |report| {
+///     println!("{}", report.info(1));
+///     depths.push(report.depth);
+/// });
+/// assert_eq!(depths, [1, 2, 3, 4]);
+/// assert_eq!(result.depth, 4);
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(depth = limits.depth))
+)]
+pub fn search_with_progress(
+    board: &Board,
+    limits: &SearchLimits,
+    stop: &StopToken,
+    progress: impl FnMut(&SearchResult),
+) -> SearchResult {
+    search_with_evaluator(board, limits, stop, EvalTerms::all(), progress)
+}
+
+/// Same as [`search_with_progress`], but also calls `currmove` as each iteration starts
+/// searching each root move, for the UCI `info currmove` lines. Frontends usually report these
+/// only after the first seconds of a search, when the iterations have become slow enough for
+/// the lines to be read: the report carries the time since the search started.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::search::{search_with_currmove, SearchLimits};
+/// use shax::stop::StopToken;
+///
+/// let limits: SearchLimits = "depth 2".parse().unwrap();
+/// let mut reports = Vec::new();
+/// search_with_currmove(&Board::default(), &limits, &StopToken::new(), |_| {}, |report| {
+///     reports.push(*report);
+/// });
+/// assert_eq!(reports.len(), 2 * 20); // The 20 root moves, at each depth
+/// assert_eq!((reports[0].depth, reports[0].number), (1, 1));
+/// assert_eq!((reports[39].depth, reports[39].number), (2, 20));
+/// ```
+pub fn search_with_currmove(
+    board: &Board,
+    limits: &SearchLimits,
+    stop: &StopToken,
+    mut progress: impl FnMut(&SearchResult),
+    mut currmove: impl FnMut(&CurrMove),
+) -> SearchResult {
+    let mut searcher = Searcher::new(board, stop, None);
+    let mut report = |_: usize, result: &SearchResult| progress(result);
+    searcher.progress = Some(&mut report);
+    searcher.currmove = Some(&mut currmove);
+    searcher.search_limits(board, limits)
+}
+
+/// Same as [`search_with_progress`], but scores the positions with `evaluator` instead of the
+/// built-in evaluation.
+///
+/// ```
+/// use shax::board::{Board, Position};
+/// use shax::eval::Evaluator;
+/// use shax::notation::Move;
+/// use shax::search::{search_with_evaluator, Score, SearchLimits};
+/// use shax::stop::StopToken;
+///
+/// /// Sees no difference between positions, leaving the search only mates and draws.
+/// struct Indifferent;
+///
+/// impl Evaluator for Indifferent {
+///     fn eval(&self, _: &Position) -> i32 {
+///         0
+///     }
+/// }
+///
+/// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+/// let limits: SearchLimits = "depth 2".parse().unwrap();
+/// let result = search_with_evaluator(&board, &limits, &StopToken::new(), Indifferent, |_| {});
+/// assert_eq!(result.best_move, Some(Move::from_lan("a1a8").unwrap()));
+/// assert_eq!(result.score, Score::Mate(1));
+/// ```
+pub fn search_with_evaluator(
+    board: &Board,
+    limits: &SearchLimits,
+    stop: &StopToken,
+    evaluator: impl Evaluator,
+    mut progress: impl FnMut(&SearchResult),
+) -> SearchResult {
+    let mut searcher = Searcher::with_evaluator(board, stop, None, evaluator);
+    let mut report = |_: usize, result: &SearchResult| progress(result);
+    searcher.progress = Some(&mut report);
+    searcher.search_limits(board, limits)
+}
+
+/// What searches learn that stays useful for the next searches of the same game: the
+/// transposition table, the killer moves and history scores of the move ordering, and the
+/// correction history of the static evaluation.
+pub(crate) struct SearchState {
+    tt: TranspositionTable,
+    /// Size of the transposition table in megabytes, as it was asked for.
+    megabytes: usize,
+    ordering: Box<MoveOrdering>,
+    correction: Box<CorrectionHistory>,
+}
+
+impl SearchState {
+    /// Creates an empty state with a transposition table of the given number of megabytes.
+    pub(crate) fn new(megabytes: usize) -> Self {
+        SearchState {
+            tt: TranspositionTable::new(megabytes),
+            megabytes,
+            ordering: Box::new(MoveOrdering::new()),
+            correction: Box::new(CorrectionHistory::new()),
+        }
+    }
+
+    /// Forgets everything learnt, so that a new game starts as the first one did.
+    pub(crate) fn clear(&mut self) {
+        self.tt.clear();
+        *self.ordering = MoveOrdering::new();
+        *self.correction = CorrectionHistory::new();
+    }
+
+    /// Forgets the positions stored in the transposition table, keeping the move ordering and
+    /// the correction history.
+    pub(crate) fn clear_tt(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Replaces the transposition table with an empty one of the given number of megabytes,
+    /// unless it already has that size.
+    pub(crate) fn resize(&mut self, megabytes: usize) {
+        if megabytes != self.megabytes {
+            self.tt = TranspositionTable::new(megabytes);
+            self.megabytes = megabytes;
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn tt_capacity(&self) -> usize {
+        self.tt.capacity()
+    }
+
+    /// Same as [`search_with_progress`], but starting from what earlier searches learnt and
+    /// keeping what this one learns. With more than one thread, helpers search alongside as
+    /// in [`search_parallel`].
+    ///
+    /// Each iteration searches the `multipv` best lines, as [`search_multipv`] does, all of
+    /// which are reported with their number counted from 1 and returned, best first.
+    pub(crate) fn search(
+        &mut self,
+        board: &Board,
+        limits: &SearchLimits,
+        stop: &StopToken,
+        threads: usize,
+        multipv: usize,
+        progress: &mut dyn FnMut(usize, &SearchResult),
+    ) -> Vec<SearchResult> {
+        let tt = &self.tt;
+        let ordering = &mut self.ordering;
+        let correction = &mut self.correction;
+        let (mut lines, helper_nodes) = with_helpers(board, threads, tt, || {
+            let mut searcher = Searcher::new(board, stop, None).with_tt(tt);
+            std::mem::swap(&mut searcher.ordering, ordering);
+            std::mem::swap(&mut searcher.correction, correction);
+            searcher.multipv = multipv.max(1);
+            searcher.progress = Some(progress);
+            let lines = searcher.search_lines(board, limits);
+            *ordering = searcher.ordering;
+            *correction = searcher.correction;
+            lines
+        });
+        for line in &mut lines {
+            line.nodes += helper_nodes;
+        }
+        lines
+    }
+}
+
+/// Keeps the root moves that are among `only`, unless `only` has no legal move. Moves of `only`
+/// are compared after resolving their notation, so that castling may be given as `e1g1`.
+fn restrict_root_moves(board: &Board, moves: &mut MoveList, only: &[Move]) {
+    let only: Vec<Move> = only
+        .iter()
+        .filter_map(|&mov| board.validate_move(board.active(), mov).ok())
+        .collect();
+    if !only.is_empty() {
+        moves.retain(|mov| only.contains(mov));
+    }
+}
+
+/// Search running on a thread of its own, so that the caller stays responsive and can end it
+/// at any time. Stopping never loses the work done: [`SearchHandle::join`] returns the best
+/// move of the last completed iteration, or the first legal move if none was completed, as
+/// with [`search_with_limits`].
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::search::{SearchHandle, SearchLimits, StopReason};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// // Without limits the search only ends when stopped
+/// let handle = SearchHandle::spawn(&Board::default(), SearchLimits::default());
+/// thread::sleep(Duration::from_millis(50));
+/// handle.stop();
+/// let result = handle.join();
+/// assert!(result.best_move.is_some());
+/// assert_eq!(result.stop_reason, StopReason::Stopped);
+/// ```
+#[derive(Debug)]
+pub struct SearchHandle {
+    stop: StopToken,
+    thread: thread::JoinHandle<SearchResult>,
+}
+
+impl SearchHandle {
+    /// Starts searching the position within the limits on a new thread.
+    pub fn spawn(board: &Board, limits: SearchLimits) -> Self {
+        Self::spawn_with_progress(board, limits, |_| {})
+    }
+
+    /// Same as [`SearchHandle::spawn`], but calls `progress` on the search thread after each
+    /// completed iteration, as [`search_with_progress`] does.
+    pub fn spawn_with_progress(
+        board: &Board,
+        limits: SearchLimits,
+        progress: impl FnMut(&SearchResult) + Send + 'static,
+    ) -> Self {
+        let stop = StopToken::new();
+        let board = board.clone();
+        let token = stop.clone();
+        let thread = thread::spawn(move || search_with_progress(&board, &limits, &token, progress));
+        SearchHandle { stop, thread }
+    }
+
+    /// Requests the search to stop. It returns within a few nodes, which [`SearchHandle::join`]
+    /// waits for.
+    pub fn stop(&self) {
+        self.stop.stop()
+    }
+
+    /// Returns a token stopping the search, for stopping it from another thread than the one
+    /// holding the handle.
+    pub fn stop_token(&self) -> StopToken {
+        self.stop.clone()
+    }
+
+    /// Tells a search started with [`SearchLimits::ponder`] that the expected move was played,
+    /// so that it finishes within its time control.
+    ///
+    /// ```
+    /// use shax::board::Board;
+    /// use shax::search::{SearchHandle, StopReason};
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let handle = SearchHandle::spawn(&Board::default(), "ponder movetime 10".parse().unwrap());
+    /// thread::sleep(Duration::from_millis(50));
+    /// assert!(!handle.is_finished());
+    /// handle.ponderhit();
+    /// let result = handle.join();
+    /// assert!(result.time >= Duration::from_millis(50));
+    /// assert_eq!(result.stop_reason, StopReason::Time);
+    /// ```
+    pub fn ponderhit(&self) {
+        self.stop.ponderhit()
+    }
+
+    /// Returns [`true`] once the search has ended, so that [`SearchHandle::join`] does not
+    /// block.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Waits for the search to end and returns its result.
+    pub fn join(self) -> SearchResult {
+        self.thread.join().unwrap()
+    }
+}
+
+/// Searches the position to a fixed depth with alpha-beta pruning and a quiescence search of
+/// captures and promotions at the leaves. A depth of 0 is searched as depth 1.
+///
+/// Positions where the side to move can repeat an earlier position of the search or a
+/// position that occurred twice in the game are scored as draws. The 50-move rule is not
+/// detected inside the search tree.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::notation::Move;
+/// use shax::search::{search, Score, StopReason};
+///
+/// // Back rank mate
+/// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+/// let result = search(&board, 3);
+/// assert_eq!(result.best_move, Some(Move::from_lan("a1a8").unwrap()));
+/// assert_eq!(result.score, Score::Mate(1));
+/// assert_eq!(result.pv, [Move::from_lan("a1a8").unwrap()]);
+/// assert_eq!(result.stop_reason, StopReason::Depth);
+/// ```
+pub fn search(board: &Board, depth: usize) -> SearchResult {
+    try_search(board, depth, &StopToken::new())
+}
+
+/// Same as [`search`], but can be cancelled through the [`StopToken`]. A cancelled search
+/// returns the best of the root moves searched completely, or the first legal move if none
+/// was, with [`StopReason::Stopped`].
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(depth))
+)]
+pub fn try_search(board: &Board, depth: usize, stop: &StopToken) -> SearchResult {
+    let start = Instant::now();
+    let depth = depth.clamp(1, MAX_PLY);
+    let mut searcher = Searcher::new(board, stop, None);
+    let moves = searcher.root_moves(board);
+    let (iteration, stopped) = searcher.search_root(&moves, depth);
+    searcher.finish(
+        board,
+        &moves,
+        iteration,
+        depth,
+        start,
+        stopped.unwrap_or(StopReason::Depth),
+    )
+}
+
+/// Same as [`try_search`], but returns the `multipv` best lines, the equivalent of the UCI
+/// `MultiPV` option, best first. `0` is treated as `1`.
+///
+/// The root is searched once per line, each pass excluding the first moves of the lines
+/// already found, so the scores of the lines are exact and in order. Fewer lines are returned
+/// if there are fewer legal moves, or if the search is stopped: the lines found before are
+/// kept, and the first line always has a move as with [`try_search`]. The node count and time
+/// of each line are those of the search up to the line.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::notation::Move;
+/// use shax::search::search_multipv;
+/// use shax::stop::StopToken;
+///
+/// let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+/// let lines = search_multipv(&board, 2, 3, &StopToken::new());
+/// assert_eq!(lines.len(), 3);
+/// assert_eq!(lines[0].best_move, Some(Move::from_lan("d2d5").unwrap()));
+/// for (k, line) in lines.iter().enumerate() {
+///     println!("{}", line.info(k + 1));
+/// }
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(depth, multipv))
+)]
+pub fn search_multipv(
+    board: &Board,
+    depth: usize,
+    multipv: usize,
+    stop: &StopToken,
+) -> Vec<SearchResult> {
+    let start = Instant::now();
+    let depth = depth.clamp(1, MAX_PLY);
+    let mut searcher = Searcher::new(board, stop, None);
+    let mut moves = searcher.root_moves(board);
+    let mut lines = Vec::with_capacity(multipv.max(1));
+
+    for index in 0..multipv.max(1).min(moves.len().max(1)) {
+        let (iteration, stopped) = searcher.search_root(&moves[index..], depth);
+        if index > 0 && iteration.best_move.is_none() {
+            break;
+        }
+        let result = searcher.finish(
+            board,
+            &moves[index..],
+            iteration,
+            depth,
+            start,
+            stopped.unwrap_or(StopReason::Depth),
+        );
+        if let Some(best) = result.best_move {
+            let position = moves.iter().position(|&mov| mov == best).unwrap();
+            moves[index..=position].rotate_right(1);
+        }
+        lines.push(result);
+        if stopped.is_some() {
+            break;
+        }
+    }
+    lines
+}
+
+/// Searches the position with iterative deepening: depth 1, 2 and so on up to `max_depth`,
+/// until the time allotted by the [`TimeControl`] runs out or the [`StopToken`] is stopped.
+///
+/// No new iteration is started once a share of the remaining time has been used, and an
+/// iteration still running when the hard limit is reached is aborted. The result is always
+/// the one of the last completed iteration, so an aborted iteration never degrades the move.
+/// The search starts each iteration with the best move of the previous one.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::search::{search_timed, StopReason, TimeControl};
+/// use shax::stop::StopToken;
+/// use std::time::Duration;
+///
+/// let board = Board::default();
+/// let time = TimeControl {
+///     movetime: Some(Duration::from_millis(50)),
+///     ..TimeControl::default()
+/// };
+/// let result = search_timed(&board, 64, &time, &StopToken::new());
+/// assert!(result.best_move.is_some());
+/// assert_eq!(result.stop_reason, StopReason::Time);
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(max_depth))
+)]
+pub fn search_timed(
+    board: &Board,
+    max_depth: usize,
+    time: &TimeControl,
+    stop: &StopToken,
+) -> SearchResult {
+    let limits = SearchLimits {
+        depth: Some(max_depth),
+        time: *time,
+        ..SearchLimits::default()
+    };
+    search_with_limits(board, &limits, stop)
+}
+
+/// Same as [`search_timed`], but searches with `threads` threads sharing the transposition
+/// table (Lazy SMP), the equivalent of the UCI `Threads` option. `0` is treated as `1`.
+///
+/// The calling thread runs the main search and decides the result. The helper threads search
+/// the same position from different depths and with differently ordered root moves, filling
+/// the table with results the main search then finds. They are stopped as soon as the main
+/// search ends, whether it completed, ran out of time or was stopped through the token. The
+/// nodes of the result are those of every thread.
+///
+/// The table can be kept between searches, so that the next search starts with what was
+/// learnt.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::search::{search_parallel, TimeControl};
+/// use shax::stop::StopToken;
+/// use shax::tt::TranspositionTable;
+///
+/// let board = Board::default();
+/// let table = TranspositionTable::new(16);
+/// let result = search_parallel(&board, 4, &TimeControl::default(), &StopToken::new(), 2, &table);
+/// assert!(result.best_move.is_some());
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(max_depth, threads))
+)]
+pub fn search_parallel(
+    board: &Board,
+    max_depth: usize,
+    time: &TimeControl,
+    stop: &StopToken,
+    threads: usize,
+    tt: &TranspositionTable,
+) -> SearchResult {
+    let (mut result, helper_nodes) = with_helpers(board, threads, tt, || {
+        let limits = SearchLimits {
+            depth: Some(max_depth),
+            time: *time,
+            ..SearchLimits::default()
+        };
+        Searcher::new(board, stop, None)
+            .with_tt(tt)
+            .search_limits(board, &limits)
+    });
+    result.nodes += helper_nodes;
+    result
+}
+
+/// Runs the main search `main` on the calling thread, while `threads - 1` helper threads
+/// search the same position into the table, from different depths and with differently
+/// ordered root moves. The helpers are stopped once the main search returns. Returns the
+/// result of the main search with the nodes of the helpers.
+fn with_helpers<R>(
+    board: &Board,
+    threads: usize,
+    tt: &TranspositionTable,
+    main: impl FnOnce() -> R,
+) -> (R, u64) {
+    // Separate from the caller's token, so that stopping the helpers never stops the caller
+    let helpers_stop = StopToken::new();
+    tt.new_search();
+
+    thread::scope(|scope| {
+        let helpers: Vec<_> = (1..threads.max(1))
+            .map(|index| {
+                let helpers_stop = &helpers_stop;
+                scope.spawn(move || {
+                    let mut searcher = Searcher::new(board, helpers_stop, None).with_tt(tt);
+                    let mut moves = searcher.root_moves(board);
+                    if !moves.is_empty() {
+                        let len = moves.len();
+                        moves.rotate_left(index % len);
+                    }
+                    searcher.iterate(&mut moves, 1 + index % 2, MAX_PLY);
+                    searcher.nodes
+                })
+            })
+            .collect();
+
+        let result = main();
+        helpers_stop.stop();
+        let nodes = helpers
+            .into_iter()
+            .map(|helper| helper.join().unwrap())
+            .sum();
+        (result, nodes)
+    })
+}
+
+/// Forced mate found by [`solve_mate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MateSolution {
+    /// Number of moves of the side to move until mate.
+    pub moves: usize,
+    /// Mating line, alternating the attacker's moves and the defences that delay the mate
+    /// the longest.
+    pub line: Vec<Move>,
+}
+
+/// Proves a forced mate for the side to move within `max_plies` plies, or refutes it.
+///
+/// Unlike [`search`], the solver is exhaustive and uses no evaluation: every attacking move
+/// is tried against every defence, with checking moves first, and mates in 1, 2 and so on are
+/// tried in turn, so the mate returned is the shortest. [`None`] proves that no mate exists
+/// within `max_plies`. The time needed grows exponentially with the depth.
+///
+/// As in puzzles, draws by repetition and the 50-move rule are ignored.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::notation::Move;
+/// use shax::search::solve_mate;
+///
+/// let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+/// let solution = solve_mate(&board, 3).unwrap();
+/// assert_eq!(solution.moves, 1);
+/// assert_eq!(solution.line, [Move::from_lan("a1a8").unwrap()]);
+///
+/// // Luft: the back rank mate no longer works
+/// let board = Board::from_fen("6k1/5pp1/7p/8/8/8/8/R5K1 w - - 0 1").unwrap();
+/// assert_eq!(solve_mate(&board, 3), None);
+/// ```
+pub fn solve_mate(board: &Board, max_plies: usize) -> Option<MateSolution> {
+    if board.status().is_over() {
+        return None;
+    }
+    let mut position = board.position();
+    (1..=max_plies.min(MAX_PLY))
+        .step_by(2)
+        .find_map(|plies| mate_in(&mut position, plies))
+        .map(|line| MateSolution {
+            moves: line.len().div_ceil(2),
+            line,
+        })
+}
+
+/// Returns a line mating within `plies` plies for the side to move.
+fn mate_in(position: &mut Position, plies: usize) -> Option<Vec<Move>> {
+    let mut moves = MoveList::new();
+    position.generate_moves_into(&mut moves);
+    // Checks first: they are the likeliest to mate and the only moves that can on the last ply
+    moves.sort_by_key(|&mov| !position.gives_check(mov));
+    for &mov in &moves {
+        if plies == 1 && !position.gives_check(mov) {
+            break;
+        }
+        let undo = position.do_move(mov);
+        let defence = if plies == 1 {
+            position.is_checkmate().then(Vec::new)
+        } else {
+            defend(position, plies - 1)
+        };
+        position.undo_move(undo);
+        if let Some(mut line) = defence {
+            line.insert(0, mov);
+            return Some(line);
+        }
+    }
+    None
+}
+
+/// Returns the longest line of the defender, to move, if every defence is mated within
+/// `plies` plies.
+fn defend(position: &mut Position, plies: usize) -> Option<Vec<Move>> {
+    let mut moves = MoveList::new();
+    position.generate_moves_into(&mut moves);
+    if moves.is_empty() {
+        return position.is_check(position.active()).then(Vec::new);
+    }
+    let mut longest: Option<Vec<Move>> = None;
+    for &mov in &moves {
+        let undo = position.do_move(mov);
+        let line = mate_in(position, plies - 1);
+        position.undo_move(undo);
+        let mut line = line?;
+        if longest
+            .as_ref()
+            .is_none_or(|longest| line.len() + 1 > longest.len())
+        {
+            line.insert(0, mov);
+            longest = Some(line);
+        }
+    }
+    longest
+}
+
+/// Converts a score relative to the root into one relative to the position `ply` plies from
+/// it, as stored in the transposition table: mate scores count the plies from the position.
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE - MAX_PLY as i32 {
+        score + ply as i32
+    } else if score < -MATE + MAX_PLY as i32 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Inverse of [`score_to_tt`].
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE - MAX_PLY as i32 {
+        score - ply as i32
+    } else if score < -MATE + MAX_PLY as i32 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Depth of [`bench`] used by `shax bench` when none is given.
+pub const BENCH_DEPTH: usize = 5;
+
+/// Totals of a [`bench`] run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchResult {
+    pub positions: usize,
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+impl BenchResult {
+    /// Nodes searched per second.
+    pub fn nps(&self) -> u64 {
+        match self.time.as_micros() {
+            0 => 0,
+            micros => (self.nodes as u128 * 1_000_000 / micros) as u64,
+        }
+    }
+}
+
+/// Searches each of the [`positions::ALL`] to a fixed depth with a fresh search, calling
+/// `progress` after each, and returns the totals.
+///
+/// The node count only depends on the move generator, evaluation and search, not on the
+/// machine, so it is a signature of the build: a change meant to be a pure speedup must keep
+/// it, while the nodes per second measure the speed.
+///
+/// ```
+/// use shax::search::bench;
+///
+/// let result = bench(2, |_, _| {});
+/// assert_eq!(result.positions, shax::positions::ALL.len());
+/// assert_eq!(result.nodes, bench(2, |_, _| {}).nodes);
+/// ```
+pub fn bench(
+    depth: usize,
+    mut progress: impl FnMut(&positions::NamedPosition, &SearchResult),
+) -> BenchResult {
+    let mut total = BenchResult {
+        positions: 0,
+        nodes: 0,
+        time: Duration::ZERO,
+    };
+    for position in positions::ALL {
+        let result = search(&position.board(), depth);
+        total.positions += 1;
+        total.nodes += result.nodes;
+        total.time += result.time;
+        progress(&position, &result);
+    }
+    total
+}
+
+/// What switching off one evaluation term changes in a [`bench_ablation`] run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermAblation {
+    pub term: EvalTerms,
+    /// Number of bench positions whose best move differs from the one found with every term.
+    pub changed_moves: usize,
+    /// Nodes of the run without the term, to compare with those of [`bench`].
+    pub nodes: u64,
+}
+
+/// Searches the [`positions::ALL`] to a fixed depth as [`bench`] does, once with every
+/// evaluation term and then once per term with only that term switched off, and reports what
+/// each term changes in the search. Unlike [`ablation`](crate::eval::ablation), which compares
+/// static evaluations, this shows whether a term changes the moves played. See
+/// [`suite_ablation`](crate::epd::suite_ablation) for the same experiment on a test suite.
+///
+/// ```
+/// use shax::eval::EvalTerms;
+/// use shax::search::bench_ablation;
+///
+/// let report = bench_ablation(2);
+/// assert_eq!(report.len(), EvalTerms::all().iter().count());
+/// let material = report.iter().find(|t| t.term == EvalTerms::MATERIAL).unwrap();
+/// assert!(material.changed_moves > 0);
+/// ```
+pub fn bench_ablation(depth: usize) -> Vec<TermAblation> {
+    let limits = SearchLimits {
+        depth: Some(depth),
+        ..SearchLimits::default()
+    };
+    let run = |terms: EvalTerms| -> Vec<SearchResult> {
+        positions::ALL
+            .iter()
+            .map(|position| {
+                let board = position.board();
+                search_with_evaluator(&board, &limits, &StopToken::new(), terms, |_| {})
+            })
+            .collect()
+    };
+    let full = run(EvalTerms::all());
+    EvalTerms::all()
+        .iter()
+        .map(|term| {
+            let results = run(EvalTerms::all() - term);
+            TermAblation {
+                term,
+                changed_moves: full
+                    .iter()
+                    .zip(&results)
+                    .filter(|(full, result)| full.best_move != result.best_move)
+                    .count(),
+                nodes: results.iter().map(|result| result.nodes).sum(),
+            }
+        })
+        .collect()
+}
+
+/// Searches the position with iterative deepening until `max_nodes` nodes are searched,
+/// evaluating with `evaluator`. A search limited by nodes instead of time plays the same moves
+/// on every machine, which is what reference opponents need.
+pub(crate) fn search_nodes(
+    board: &Board,
+    max_nodes: u64,
+    evaluator: impl Evaluator,
+) -> SearchResult {
+    let start = Instant::now();
+    let stop = StopToken::new();
+    let mut searcher =
+        Searcher::with_evaluator(board, &stop, None, evaluator).with_max_nodes(max_nodes);
+    let mut moves = searcher.root_moves(board);
+    let (completed, depth, stop_reason) = searcher.iterate(&mut moves, 1, MAX_PLY);
+    let best = completed.into_iter().next().unwrap_or_default();
+    searcher.finish(board, &moves, best, depth, start, stop_reason)
+}
+
+/// Score of a position without legal moves, `ply` plies from the root.
+fn terminal_score(board: &Position, ply: usize) -> i32 {
+    if board.is_check(board.active()) {
+        -MATE + ply as i32
+    } else {
+        0
+    }
+}
+
+/// Best line found by a search of the root moves.
+#[derive(Default)]
+struct Iteration {
+    /// [`None`] if no root move was searched completely.
+    best_move: Option<Move>,
+    score: i32,
+    pv: Vec<Move>,
+}
+
+/// Triangular table of principal variations: row `ply` holds the best line found from that
+/// ply on. It is allocated once, so that the search does not allocate while it runs.
+struct PvTable {
+    lines: Vec<[Option<Move>; MAX_PLY]>,
+    lengths: [usize; MAX_PLY + 1],
+}
+
+impl PvTable {
+    fn new() -> Self {
+        PvTable {
+            lines: vec![[None; MAX_PLY]; MAX_PLY],
+            lengths: [0; MAX_PLY + 1],
+        }
+    }
+
+    /// Empties the line from `ply`, before the position at that ply is searched.
+    fn clear(&mut self, ply: usize) {
+        self.lengths[ply] = 0;
+    }
+
+    /// Sets the line from `ply` to `mov` followed by the line from the next ply.
+    fn update(&mut self, ply: usize, mov: Move) {
+        let child_length = self.lengths[ply + 1];
+        let (line, rest) = self.lines[ply..].split_first_mut().unwrap();
+        line[0] = Some(mov);
+        if let Some(child) = rest.first() {
+            line[1..=child_length].copy_from_slice(&child[..child_length]);
+        }
+        self.lengths[ply] = child_length + 1;
+    }
+
+    fn line(&self, ply: usize) -> Vec<Move> {
+        self.lines[ply][..self.lengths[ply]]
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+/// Callback receiving each line of a completed iteration with its number, counted from 1.
+type Progress<'a> = &'a mut dyn FnMut(usize, &SearchResult);
+
+/// Callback receiving each root move as the search starts on it.
+type CurrMoveReport<'a> = &'a mut dyn FnMut(&CurrMove);
+
+struct Searcher<'a, E = EvalTerms> {
+    board: Position,
+    stop: &'a StopToken,
+    deadline: Option<Instant>,
+    /// Time after which no new iteration is started.
+    soft_deadline: Option<Instant>,
+    /// Soft and hard time limits of a pondering search, which start running at the ponderhit.
+    ponder: Option<(Duration, Duration)>,
+    max_nodes: Option<u64>,
+    /// Number of moves within which a mate ends the iterative deepening.
+    mate: Option<usize>,
+    /// Limit that aborted the search, if any.
+    limit: Option<StopReason>,
+    /// Number of best lines searched by each iteration.
+    multipv: usize,
+    /// Receives each line of each completed iteration, timed from `start`.
+    progress: Option<Progress<'a>>,
+    /// Receives each root move of each iteration as it is searched, timed from `start`.
+    currmove: Option<CurrMoveReport<'a>>,
+    start: Instant,
+    evaluator: E,
+    nodes: u64,
+    seldepth: usize,
+    ordering: Box<MoveOrdering>,
+    correction: Box<CorrectionHistory>,
+    pv: PvTable,
+    /// Principal variation of the previous iteration, whose move at each ply is searched
+    /// first as the hash move.
+    previous_pv: Vec<Move>,
+    tt: Option<&'a TranspositionTable>,
+    /// Zobrist keys of the positions before the current one since the last capture or pawn
+    /// move, from the game and then the search path.
+    keys: Vec<u64>,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(board: &Board, stop: &'a StopToken, deadline: Option<Instant>) -> Self {
+        Searcher::with_evaluator(board, stop, deadline, EvalTerms::all())
+    }
+}
+
+impl<'a, E: Evaluator> Searcher<'a, E> {
+    fn with_evaluator(
+        board: &Board,
+        stop: &'a StopToken,
+        deadline: Option<Instant>,
+        evaluator: E,
+    ) -> Self {
+        Searcher {
+            board: board.position(),
+            stop,
+            deadline,
+            soft_deadline: None,
+            ponder: None,
+            max_nodes: None,
+            mate: None,
+            limit: None,
+            multipv: 1,
+            progress: None,
+            currmove: None,
+            start: Instant::now(),
+            evaluator,
+            nodes: 0,
+            seldepth: 0,
+            ordering: Box::new(MoveOrdering::new()),
+            correction: Box::new(CorrectionHistory::new()),
+            pv: PvTable::new(),
+            previous_pv: Vec::new(),
+            tt: None,
+            keys: board.history().to_vec(),
+        }
+    }
+
+    fn with_tt(mut self, tt: &'a TranspositionTable) -> Self {
+        self.tt = Some(tt);
+        self
+    }
+
+    fn with_max_nodes(mut self, max_nodes: u64) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Searches the position with iterative deepening within the limits, timed from now or,
+    /// when pondering, from the ponderhit.
+    fn search_limits(&mut self, board: &Board, limits: &SearchLimits) -> SearchResult {
+        self.search_lines(board, limits).swap_remove(0)
+    }
+
+    /// Same as [`Searcher::search_limits`], but returns the [`Searcher::multipv`] best lines,
+    /// best first. Fewer lines are returned if there are fewer legal moves.
+    fn search_lines(&mut self, board: &Board, limits: &SearchLimits) -> Vec<SearchResult> {
+        let start = Instant::now();
+        let budget = limits.time.budget(board.active());
+        self.start = start;
+        if limits.ponder {
+            self.ponder = budget;
+            self.check_ponderhit();
+        } else {
+            self.deadline = budget.map(|(_, hard)| start + hard);
+            self.soft_deadline = budget.map(|(soft, _)| start + soft);
+        }
+        self.max_nodes = limits.nodes;
+        self.mate = limits.mate;
+        let mut moves = self.root_moves(board);
+        restrict_root_moves(board, &mut moves, &limits.root_moves);
+
+        let mut max_depth = limits.depth.unwrap_or(MAX_PLY);
+        if let Some(mate) = limits.mate {
+            max_depth = max_depth.min(mate.max(1) * 2 - 1);
+        }
+        let (completed, depth, stop_reason) = self.iterate(&mut moves, 1, max_depth);
+
+        // A pondering search only gives its move once the opponent has played
+        while limits.ponder && !self.stop.is_ponderhit() && !self.stop.is_stopped() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        self.finish_lines(board, &moves, completed, depth, start, stop_reason)
+    }
+
+    /// Starts the time limits of a pondering search once the ponderhit has come.
+    fn check_ponderhit(&mut self) {
+        if self.stop.is_ponderhit() {
+            if let Some((soft, hard)) = self.ponder.take() {
+                let now = Instant::now();
+                self.deadline = Some(now + hard);
+                self.soft_deadline = Some(now + soft);
+            }
+        }
+    }
+
+    /// Returns the legal moves at the root in search order, none if the game is over.
+    fn root_moves(&self, board: &Board) -> MoveList {
+        let mut moves = MoveList::new();
+        if !board.status().is_over() {
+            board.generate_moves_into(&mut moves);
+            self.ordering.order(board, &mut moves, None, 0);
+        }
+        moves
+    }
+
+    /// Searches the root moves with iterative deepening from `start_depth` to `max_depth`. Each
+    /// iteration searches the root once per line of [`Searcher::multipv`], excluding the first
+    /// moves of the lines already found, and moves those first in order. No iteration is
+    /// started after the soft deadline. Returns the lines of the last completed iteration, best
+    /// first and none if no iteration was completed, its depth (at least 1) and why the search
+    /// ended.
+    fn iterate(
+        &mut self,
+        moves: &mut [Move],
+        start_depth: usize,
+        max_depth: usize,
+    ) -> (Vec<Iteration>, usize, StopReason) {
+        let max_depth = max_depth.clamp(1, MAX_PLY);
+        let mut completed = Vec::new();
+        let mut completed_depth = 0;
+        let mut stop_reason = StopReason::Depth;
+        'deepening: for depth in start_depth.min(max_depth)..=max_depth {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("iteration", depth).entered();
+            let mut lines = Vec::with_capacity(self.multipv);
+            for index in 0..self.multipv.min(moves.len()).max(1) {
+                let (iteration, stopped) = self.search_root(&moves[index..], depth);
+                if let Some(reason) = stopped {
+                    stop_reason = reason;
+                    break 'deepening;
+                }
+                if let Some(best) = iteration.best_move {
+                    let position = moves.iter().position(|&mov| mov == best).unwrap();
+                    moves[index..=position].rotate_right(1);
+                }
+                if index == 0 {
+                    self.previous_pv.clone_from(&iteration.pv);
+                }
+                lines.push(iteration);
+            }
+            let iteration = &lines[0];
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                nodes = self.nodes,
+                seldepth = self.seldepth,
+                score = %Score::from_internal(iteration.score),
+                best_move = ?iteration.best_move,
+                "iteration completed"
+            );
+            let mated = match Score::from_internal(iteration.score) {
+                Score::Mate(moves) => self
+                    .mate
+                    .is_some_and(|mate| (1..=mate as i32).contains(&moves)),
+                Score::Centipawns(_) => false,
+            };
+            if let Some(progress) = &mut self.progress {
+                for (index, line) in lines.iter().enumerate() {
+                    if line.best_move.is_none() {
+                        continue;
+                    }
+                    let report = SearchResult {
+                        best_move: line.best_move,
+                        score: Score::from_internal(line.score),
+                        depth,
+                        seldepth: self.seldepth,
+                        nodes: self.nodes,
+                        time: self.start.elapsed(),
+                        pv: line.pv.clone(),
+                        stop_reason: StopReason::Depth,
+                    };
+                    progress(index + 1, &report);
+                }
+            }
+            completed = lines;
+            completed_depth = depth;
+            if mated {
+                stop_reason = StopReason::Mate;
+                break;
+            }
+
+            self.check_ponderhit();
+            if self
+                .soft_deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+                && depth < max_depth
+            {
+                stop_reason = StopReason::Time;
+                break;
+            }
+        }
+        (completed, completed_depth.max(1), stop_reason)
+    }
+
+    /// Searches every root move to `depth`. If the search is stopped, the iteration holds the
+    /// best of the moves searched before, along with why it stopped.
+    fn search_root(&mut self, moves: &[Move], depth: usize) -> (Iteration, Option<StopReason>) {
+        let mut iteration = Iteration {
+            score: -INFINITY,
+            ..Iteration::default()
+        };
+        let mut stopped = None;
+        self.pv.clear(0);
+        for (index, &mov) in moves.iter().enumerate() {
+            if let Some(currmove) = &mut self.currmove {
+                currmove(&CurrMove {
+                    depth,
+                    mov,
+                    number: index + 1,
+                    time: self.start.elapsed(),
+                });
+            }
+            self.pv.clear(1);
+            self.keys.push(self.board.zobrist());
+            let undo = self.board.do_move(mov);
+            let score = self.negamax(depth - 1, 1, -INFINITY, -iteration.score);
+            self.board.undo_move(undo);
+            self.keys.pop();
+            match score {
+                Ok(score) if -score > iteration.score => {
+                    iteration.score = -score;
+                    iteration.best_move = Some(mov);
+                    self.pv.update(0, mov);
+                }
+                Ok(_) => {}
+                Err(Stopped) => {
+                    stopped = Some(self.limit.unwrap_or(StopReason::Stopped));
+                    break;
+                }
+            }
+        }
+        iteration.pv = self.pv.line(0);
+        (iteration, stopped)
+    }
+
+    /// Same as [`Searcher::finish`] for each line of a multi-PV search, the first line
+    /// standing in for the search as a whole.
+    fn finish_lines(
+        &self,
+        board: &Board,
+        moves: &[Move],
+        lines: Vec<Iteration>,
+        depth: usize,
+        start: Instant,
+        stop_reason: StopReason,
+    ) -> Vec<SearchResult> {
+        let mut lines = lines.into_iter();
+        let first = lines.next().unwrap_or_default();
+        let best = self.finish(board, moves, first, depth, start, stop_reason);
+        let others: Vec<SearchResult> = lines
+            .map(|line| SearchResult {
+                best_move: line.best_move,
+                score: Score::from_internal(line.score),
+                pv: line.pv,
+                ..best.clone()
+            })
+            .collect();
+        let mut results = vec![best];
+        results.extend(others);
+        results
+    }
+
+    fn finish(
+        &self,
+        board: &Board,
+        moves: &[Move],
+        mut iteration: Iteration,
+        depth: usize,
+        start: Instant,
+        stop_reason: StopReason,
+    ) -> SearchResult {
+        let score = if moves.is_empty() {
+            terminal_score(board, 0)
+        } else if iteration.best_move.is_none() {
+            iteration.best_move = Some(moves[0]);
+            iteration.pv = vec![moves[0]];
+            self.evaluator.eval(board)
+        } else {
+            iteration.score
+        };
+
+        SearchResult {
+            best_move: iteration.best_move,
+            score: Score::from_internal(score),
+            depth,
+            seldepth: self.seldepth,
+            nodes: self.nodes,
+            time: start.elapsed(),
+            pv: iteration.pv,
+            stop_reason,
+        }
+    }
+
+    /// Returns [`Err`] once stopping was requested, the deadline has passed or the node limit
+    /// is reached. The clock is only read every 1024 nodes.
+    fn check_stop(&mut self) -> Result<(), Stopped> {
+        self.stop.check()?;
+        if self.limit.is_some() {
+            return Err(Stopped);
+        }
+        if self
+            .max_nodes
+            .is_some_and(|max_nodes| self.nodes >= max_nodes)
+        {
+            self.limit = Some(StopReason::Nodes);
+            return Err(Stopped);
+        }
+        if self.ponder.is_some() && self.nodes.is_multiple_of(1024) {
+            self.check_ponderhit();
+        }
+        if let Some(deadline) = self.deadline {
+            if self.nodes.is_multiple_of(1024) && Instant::now() >= deadline {
+                self.limit = Some(StopReason::Time);
+                return Err(Stopped);
+            }
+        }
+        Ok(())
+    }
+
+    fn negamax(
+        &mut self,
+        depth: usize,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+    ) -> Result<i32, Stopped> {
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(ply, alpha, beta);
+        }
+        self.check_stop()?;
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        // The side to move can at least draw by repeating a position
+        if alpha < 0 && self.has_upcoming_repetition(ply) {
+            alpha = 0;
+            if alpha >= beta {
+                return Ok(alpha);
+            }
+        }
+
+        let key = self.board.zobrist();
+        let entry = self.tt.and_then(|tt| tt.probe(key));
+        if let Some(entry) = entry.filter(|entry| entry.depth as usize >= depth) {
+            let score = score_from_tt(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return Ok(score.clamp(alpha, beta)),
+                Bound::Lower if score >= beta => return Ok(beta),
+                Bound::Upper if score <= alpha => return Ok(alpha),
+                _ => {}
+            }
+        }
+
+        let mut moves = MoveList::new();
+        self.board.generate_moves_into(&mut moves);
+        if moves.is_empty() {
+            return Ok(terminal_score(&self.board, ply));
+        }
+
+        // Static evaluation before and after correction, meaningless in check
+        let eval = (!self.board.is_check(self.board.active())).then(|| {
+            let eval = self.evaluator.eval(&self.board);
+            (eval, self.correction.correct(&self.board, eval))
+        });
+
+        // Reverse futility pruning: far enough above beta, the side to move is not expected to
+        // fall below it within a few plies. Not in check, near mate scores or without pieces,
+        // where zugzwang makes standing still no bound.
+        if let Some((_, static_eval)) = eval {
+            if depth < RFP_MARGINS.len()
+                && beta.abs() < MATE - MAX_PLY as i32
+                && self.has_pieces()
+                && static_eval - RFP_MARGINS[depth] >= beta
+            {
+                return Ok(beta);
+            }
+        }
+
+        let hash_move = entry
+            .and_then(|entry| entry.best_move)
+            .filter(|mov| moves.contains(mov))
+            .or_else(|| self.previous_pv.get(ply).copied());
+        self.ordering.order(&self.board, &mut moves, hash_move, ply);
+
+        // A node the table knows no move for is searched with a poor move order, and was not
+        // worth storing in earlier searches: search it one ply shallower (internal iterative
+        // reduction), which also leaves a move in the table for when it is searched again
+        let depth = if hash_move.is_none() && self.tt.is_some() && depth >= IIR_DEPTH {
+            depth - 1
+        } else {
+            depth
+        };
+
+        let mut best_move = None;
+        for &mov in &moves {
+            self.pv.clear(ply + 1);
+            self.keys.push(key);
+            let undo = self.board.do_move(mov);
+            let score = self.negamax(depth - 1, ply + 1, -beta, -alpha);
+            self.board.undo_move(undo);
+            self.keys.pop();
+            let score = -score?;
+
+            if score >= beta {
+                self.ordering.record_cutoff(&self.board, mov, ply, depth);
+                self.update_correction(eval, Some(mov), beta, depth, Bound::Lower);
+                self.store(key, Some(mov), beta, depth, ply, Bound::Lower);
+                return Ok(beta);
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mov);
+                self.pv.update(ply, mov);
+            }
+        }
+        let bound = if best_move.is_some() {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.update_correction(eval, best_move, alpha, depth, bound);
+        self.store(key, best_move, alpha, depth, ply, bound);
+        Ok(alpha)
+    }
+
+    /// Teaches the correction history the score the search found for the position against
+    /// its static evaluation `eval`, before and after correction, when the bound tells which
+    /// way the evaluation erred. Tactical best moves and mate scores say nothing about the
+    /// evaluation of the position and are left out.
+    fn update_correction(
+        &mut self,
+        eval: Option<(i32, i32)>,
+        best_move: Option<Move>,
+        score: i32,
+        depth: usize,
+        bound: Bound,
+    ) {
+        let Some((eval, corrected)) = eval else {
+            return;
+        };
+        let informative = match bound {
+            Bound::Exact => true,
+            Bound::Lower => score > corrected,
+            Bound::Upper => score < corrected,
+        };
+        if informative
+            && score.abs() < MATE - MAX_PLY as i32
+            && !best_move.is_some_and(|mov| self.is_tactical(mov))
+        {
+            self.correction.update(&self.board, depth, eval, score);
+        }
+    }
+
+    /// Returns whether the side to move can repeat a position with a single move. A position
+    /// of the search path is a draw once repeated, while a position from before the root must
+    /// already have occurred twice, so that repeating it draws the game itself.
+    fn has_upcoming_repetition(&self, ply: usize) -> bool {
+        self.board.game_cycles(&self.keys).any(|(plies, color)| {
+            let index = self.keys.len() - plies;
+            plies < ply
+                || (color == self.board.active() && self.keys[..index].contains(&self.keys[index]))
+        })
+    }
+
+    /// Returns whether the side to move has a piece other than pawns and the king.
+    fn has_pieces(&self) -> bool {
+        let active = self.board.active();
+        [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+            .into_iter()
+            .any(|piece| self.board.bitboard(active, piece) != 0)
+    }
+
+    fn store(
+        &self,
+        key: u64,
+        best_move: Option<Move>,
+        score: i32,
+        depth: usize,
+        ply: usize,
+        bound: Bound,
+    ) {
+        if let Some(tt) = self.tt {
+            tt.store(
+                key,
+                Entry {
+                    best_move,
+                    score: score_to_tt(score, ply),
+                    depth: depth as u8,
+                    bound,
+                },
+            );
+        }
+    }
+
+    /// Searches captures and promotions until the position is quiet, so that the static
+    /// evaluation is not taken in the middle of an exchange.
+    fn quiescence(&mut self, ply: usize, mut alpha: i32, beta: i32) -> Result<i32, Stopped> {
+        self.check_stop()?;
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        let mut moves = MoveList::new();
+        self.board.generate_moves_into(&mut moves);
+        if moves.is_empty() {
+            return Ok(terminal_score(&self.board, ply));
+        }
+
+        let eval = self.evaluator.eval(&self.board);
+        let stand_pat = self.correction.correct(&self.board, eval);
+        if stand_pat >= beta || ply >= MAX_PLY {
+            return Ok(stand_pat.min(beta));
+        }
+        alpha = alpha.max(stand_pat);
+        self.ordering.order(&self.board, &mut moves, None, ply);
+
+        for &mov in &moves {
+            if !self.is_tactical(mov) {
+                continue;
+            }
+            let undo = self.board.do_move(mov);
+            let score = self.quiescence(ply + 1, -beta, -alpha);
+            self.board.undo_move(undo);
+            let score = -score?;
+
+            if score >= beta {
+                return Ok(beta);
+            }
+            alpha = alpha.max(score);
+        }
+        Ok(alpha)
+    }
+
+    fn is_tactical(&self, mov: Move) -> bool {
+        matches!(mov, Move::Promotion(_)) || self.board.captured_by(mov).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::GameStatus;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_score_from_internal() {
+        assert_eq!(Score::from_internal(35), Score::Centipawns(35));
+        assert_eq!(Score::from_internal(MATE - 1), Score::Mate(1));
+        assert_eq!(Score::from_internal(MATE - 3), Score::Mate(2));
+        assert_eq!(Score::from_internal(-MATE), Score::Mate(0));
+        assert_eq!(Score::from_internal(-MATE + 2), Score::Mate(-1));
+        assert_eq!(Score::Mate(-1).to_string(), "mate -1");
+    }
+
+    #[test]
+    fn test_wins_hanging_queen() {
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let result = search(&board, 2);
+        assert_eq!(result.best_move, Some(Move::from_lan("d2d5").unwrap()));
+        assert!(matches!(result.score, Score::Centipawns(cp) if cp > 300));
+        assert!(result.seldepth >= result.depth);
+        assert!(result.nodes > 0);
+    }
+
+    #[test]
+    fn test_avoids_defended_capture() {
+        // taking the pawn loses the queen to the rook on d8
+        let board = Board::from_fen("3rk3/8/8/3p4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+        let result = search(&board, 1);
+        assert_ne!(result.best_move, Some(Move::from_lan("d2d5").unwrap()));
+    }
+
+    #[test]
+    fn test_pv_is_playable() {
+        let board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+                .unwrap();
+        let result = search(&board, 3);
+        assert_eq!(result.pv.first().copied(), result.best_move);
+        let mut replay = board.clone();
+        for &mov in &result.pv {
+            replay.make_move(mov).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_terminal_positions() {
+        let mated = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert!(matches!(mated.status(), GameStatus::Checkmate { .. }));
+        let result = search(&mated, 2);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, Score::Mate(0));
+
+        let stalemate = Board::from_fen("7k/5K2/6Q1/8/8/8/8/8 b - - 0 1").unwrap();
+        let result = search(&stalemate, 2);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, Score::Centipawns(0));
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let seconds = Duration::from_secs;
+        let time = TimeControl {
+            wtime: Some(seconds(60)),
+            btime: Some(seconds(1)),
+            winc: Some(seconds(1)),
+            ..TimeControl::default()
+        };
+        let (soft, hard) = time.budget(Color::White).unwrap();
+        assert!(soft > seconds(2) && soft < seconds(3), "{soft:?}");
+        assert_eq!(hard, soft * 4);
+        let (soft, hard) = time.budget(Color::Black).unwrap();
+        assert!(soft < Duration::from_millis(50) && hard <= seconds(1) / 2);
+
+        assert_eq!(TimeControl::default().budget(Color::White), None);
+        let movetime = TimeControl {
+            movetime: Some(seconds(5)),
+            ..time
+        };
+        assert_eq!(
+            movetime.budget(Color::Black),
+            Some((seconds(5), seconds(5)))
+        );
+    }
+
+    #[test]
+    fn test_search_moves() {
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K2R w K - 0 1").unwrap();
+        let stop = StopToken::new();
+        let castle: SearchLimits = "depth 2 searchmoves e1g1 a1a2".parse().unwrap();
+        let result = search_with_limits(&board, &castle, &stop);
+        assert_eq!(
+            result.best_move.map(|mov| mov.to_lan()),
+            Some("e1g1".into())
+        );
+
+        // Without a legal move among them, every move is searched
+        let illegal: SearchLimits = "depth 2 searchmoves a1a2".parse().unwrap();
+        let result = search_with_limits(&board, &illegal, &stop);
+        assert_eq!(result.best_move, search(&board, 2).best_move);
+
+        assert_eq!(
+            "depth".parse::<SearchLimits>(),
+            Err(ParseGoError::MissingValue("depth".into()))
+        );
+        assert_eq!(
+            "searchmoves e2e4 mat 2".parse::<SearchLimits>(),
+            Err(ParseGoError::UnknownToken("mat".into()))
+        );
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let stop = StopToken::new();
+        let nodes: SearchLimits = "nodes 2000".parse().unwrap();
+        let result = search_with_limits(&Board::default(), &nodes, &stop);
+        assert_eq!(result.stop_reason, StopReason::Nodes);
+        assert!(result.best_move.is_some());
+
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mate: SearchLimits = "mate 3".parse().unwrap();
+        let result = search_with_limits(&board, &mate, &stop);
+        assert_eq!(result.stop_reason, StopReason::Mate);
+        assert_eq!(result.depth, 1);
+
+        let limits: SearchLimits = "btime -20 movestogo 10 movetime 500".parse().unwrap();
+        assert_eq!(limits.time.btime, Some(Duration::ZERO));
+        assert_eq!(limits.time.movestogo, Some(10));
+        assert_eq!(limits.time.movetime, Some(Duration::from_millis(500)));
+        assert_eq!(
+            "nodes many".parse::<SearchLimits>(),
+            Err(ParseGoError::BadValue {
+                keyword: "nodes".into(),
+                value: "many".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_ponder() {
+        let limits: SearchLimits = "ponder depth 2 wtime 1000 btime 1000".parse().unwrap();
+        assert!(limits.ponder);
+
+        // The depth is reached long before the ponderhit, which the move waits for
+        let handle = SearchHandle::spawn(&Board::default(), limits.clone());
+        thread::sleep(Duration::from_millis(100));
+        assert!(!handle.is_finished());
+        handle.ponderhit();
+        let result = handle.join();
+        assert_eq!(result.depth, 2);
+        assert_eq!(result.stop_reason, StopReason::Depth);
+
+        // A stop ends it as well, and a ponderhit given before the start counts
+        let stop = StopToken::new();
+        stop.stop();
+        let result = search_with_limits(&Board::default(), &limits, &stop);
+        assert!(result.best_move.is_some());
+        stop.reset();
+        stop.ponderhit();
+        let result = search_with_limits(&Board::default(), &limits, &stop);
+        assert_eq!(result.depth, 2);
+    }
+
+    #[test]
+    fn test_search_progress() {
+        let limits: SearchLimits = "depth 3".parse().unwrap();
+        let mut reports = Vec::new();
+        let result =
+            search_with_progress(&Board::default(), &limits, &StopToken::new(), |report| {
+                reports.push(report.clone())
+            });
+        assert_eq!(reports.len(), 3);
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[0].nodes <= pair[1].nodes));
+        let last = reports.last().unwrap();
+        assert_eq!(last.best_move, result.best_move);
+        assert_eq!(last.pv, result.pv);
+        assert_eq!(last.nodes, result.nodes);
+
+        // A finished game has no iteration to report
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        let mut called = false;
+        search_with_progress(&board, &limits, &StopToken::new(), |_| called = true);
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_search_with_currmove() {
+        let board = Board::default();
+        let limits: SearchLimits = "depth 3 searchmoves e2e4 d2d4".parse().unwrap();
+        let mut reports = Vec::new();
+        let result = search_with_currmove(
+            &board,
+            &limits,
+            &StopToken::new(),
+            |_| {},
+            |report| {
+                reports.push((report.depth, report.number, report.mov.to_lan()));
+            },
+        );
+        assert_eq!(reports.len(), 3 * 2);
+        assert!(reports
+            .iter()
+            .all(|(_, _, lan)| lan == "e2e4" || lan == "d2d4"));
+        // The best move of an iteration is searched first by the next one
+        let best = result.best_move.unwrap().to_lan();
+        assert_eq!(reports[4], (3, 1, best));
+        assert_eq!(reports[5].1, 2);
+    }
+
+    #[test]
+    fn test_search_with_evaluator() {
+        struct Counting(Cell<u64>);
+
+        impl Evaluator for Counting {
+            fn eval(&self, board: &Position) -> i32 {
+                self.0.set(self.0.get() + 1);
+                EvalTerms::all().eval(board)
+            }
+        }
+
+        let limits: SearchLimits = "depth 3".parse().unwrap();
+        let stop = StopToken::new();
+        let counting = Counting(Cell::new(0));
+        let result = search_with_evaluator(&Board::default(), &limits, &stop, &counting, |_| {});
+        assert!(counting.0.get() > 0);
+
+        // The built-in evaluation searches exactly as the default search
+        let default = search_with_limits(&Board::default(), &limits, &stop);
+        assert_eq!(result.nodes, default.nodes);
+        assert_eq!(result.pv, default.pv);
+    }
+
+    #[test]
+    fn test_search_handle() {
+        let limits: SearchLimits = "depth 2".parse().unwrap();
+        let handle = SearchHandle::spawn(&Board::default(), limits);
+        let result = handle.join();
+        assert_eq!(result.depth, 2);
+        assert_eq!(result.stop_reason, StopReason::Depth);
+
+        // Stopped before the first iteration completes, the search still has a move
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let handle = SearchHandle::spawn(&board, SearchLimits::default());
+        handle.stop_token().stop();
+        let result = handle.join();
+        assert!(result.best_move.is_some());
+        assert_eq!(result.stop_reason, StopReason::Stopped);
+    }
+
+    #[test]
+    fn test_upcoming_repetition() {
+        let mut board = Board::default();
+        let stop = StopToken::new();
+        for (round, expected) in [(1, false), (2, true)] {
+            for mov in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+            }
+            for mov in ["g1f3", "g8f6"] {
+                board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+            }
+            // White can play f3g1 back into a position seen once per round
+            let searcher = Searcher::new(&board, &stop, None);
+            assert_eq!(
+                searcher.has_upcoming_repetition(0),
+                expected,
+                "round {round}"
+            );
+            assert!(searcher.has_upcoming_repetition(MAX_PLY));
+            board.make_move(Move::from_lan("f3g1").unwrap()).unwrap();
+            board.make_move(Move::from_lan("f6g8").unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_internal_iterative_reduction() {
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let stop = StopToken::new();
+        let tt = TranspositionTable::new(1);
+        let mut searcher = Searcher::new(&board, &stop, None).with_tt(&tt);
+        searcher.negamax(IIR_DEPTH, 1, -INFINITY, INFINITY).unwrap();
+        let entry = tt.probe(board.zobrist()).unwrap();
+        assert_eq!(entry.depth as usize, IIR_DEPTH - 1);
+
+        // The second search finds the move of the first one and searches at full depth
+        searcher.negamax(IIR_DEPTH, 1, -INFINITY, INFINITY).unwrap();
+        let entry = tt.probe(board.zobrist()).unwrap();
+        assert_eq!(entry.depth as usize, IIR_DEPTH);
+        assert_eq!(entry.best_move, Some(Move::from_lan("d2d5").unwrap()));
+    }
+
+    #[test]
+    fn test_reverse_futility_pruning() {
+        let stop = StopToken::new();
+        let nodes = |fen, depth| {
+            let board = Board::from_fen(fen).unwrap();
+            let mut searcher = Searcher::new(&board, &stop, None);
+            assert_eq!(searcher.negamax(depth, 1, -INFINITY, 0), Ok(0));
+            searcher.nodes
+        };
+        // A queen up, White needs no search to stay above 0
+        assert_eq!(nodes("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1", 1), 1);
+        assert!(nodes("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1", RFP_MARGINS.len()) > 1);
+        // Unless in check or left with pawns only
+        assert!(nodes("4k3/8/8/8/8/8/3Q4/4K2r w - - 0 1", 1) > 1);
+        assert!(nodes("4k3/8/8/8/8/8/PPPPPPPP/4K3 w - - 0 1", 1) > 1);
+    }
+
+    #[test]
+    fn test_iterative_deepening() {
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let result = search_timed(&board, 3, &TimeControl::default(), &StopToken::new());
+        assert_eq!(result.depth, 3);
+        assert_eq!(result.stop_reason, StopReason::Depth);
+        assert_eq!(result.best_move, search(&board, 3).best_move);
+    }
+
+    #[test]
+    fn test_iterative_deepening_stops_on_time() {
+        let time = TimeControl {
+            movetime: Some(Duration::from_millis(30)),
+            ..TimeControl::default()
+        };
+        let start = Instant::now();
+        let result = search_timed(&Board::default(), MAX_PLY, &time, &StopToken::new());
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(result.stop_reason, StopReason::Time);
+        assert!(result.best_move.is_some());
+        assert_eq!(result.pv.first().copied(), result.best_move);
+    }
+
+    #[test]
+    fn test_stopped_search_returns_a_move() {
+        let stop = StopToken::new();
+        stop.stop();
+        let result = try_search(&Board::default(), 4, &stop);
+        assert_eq!(result.stop_reason, StopReason::Stopped);
+        assert!(result.best_move.is_some());
+        assert_eq!(result.pv.len(), 1);
+    }
+
+    #[test]
+    fn test_parallel_search() {
+        let table = TranspositionTable::new(4);
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let result = search_parallel(
+            &board,
+            4,
+            &TimeControl::default(),
+            &StopToken::new(),
+            4,
+            &table,
+        );
+        assert_eq!(result.best_move, Some(Move::from_lan("d2d5").unwrap()));
+        assert_eq!(result.depth, 4);
+        assert_eq!(result.stop_reason, StopReason::Depth);
+
+        // Same answers as without the table, and the mate distance is kept through it
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        for _ in 0..2 {
+            let result = search_parallel(
+                &board,
+                4,
+                &TimeControl::default(),
+                &StopToken::new(),
+                2,
+                &table,
+            );
+            assert_eq!(result.best_move, Some(Move::from_lan("a1a8").unwrap()));
+            assert_eq!(result.score, Score::Mate(1));
+        }
+    }
+
+    #[test]
+    fn test_stopped_parallel_search_returns_a_move() {
+        let table = TranspositionTable::new(1);
+        let stop = StopToken::new();
+        stop.stop();
+        let result = search_parallel(
+            &Board::default(),
+            64,
+            &TimeControl::default(),
+            &stop,
+            3,
+            &table,
+        );
+        assert_eq!(result.stop_reason, StopReason::Stopped);
+        assert!(result.best_move.is_some());
+        // The caller's token is left as it was
+        assert!(stop.is_stopped());
+
+        let time = TimeControl {
+            movetime: Some(Duration::from_millis(50)),
+            ..TimeControl::default()
+        };
+        let result = search_parallel(&Board::default(), 64, &time, &StopToken::new(), 3, &table);
+        assert_eq!(result.stop_reason, StopReason::Time);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn test_multipv() {
+        let board = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let lines = search_multipv(&board, 3, 4, &StopToken::new());
+        let single = search(&board, 3);
+        assert_eq!(lines[0].best_move, single.best_move);
+        assert_eq!(lines[0].score, single.score);
+
+        let internal = |score| match score {
+            Score::Centipawns(cp) => cp,
+            Score::Mate(moves) => moves.signum() * MATE - moves,
+        };
+        let mut first_moves: Vec<Move> = lines.iter().map(|line| line.pv[0]).collect();
+        for pair in lines.windows(2) {
+            assert!(internal(pair[0].score) >= internal(pair[1].score));
+            assert!(pair[0].nodes <= pair[1].nodes);
+        }
+        first_moves.dedup();
+        assert_eq!(first_moves.len(), 4);
+
+        // Fewer lines than requested with fewer legal moves
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(search_multipv(&board, 2, 5, &StopToken::new()).len(), 1);
+
+        let mated = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        let lines = search_multipv(&mated, 2, 3, &StopToken::new());
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].best_move, None);
+
+        let stop = StopToken::new();
+        stop.stop();
+        let lines = search_multipv(&Board::default(), 4, 3, &stop);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].stop_reason, StopReason::Stopped);
+        assert!(lines[0].best_move.is_some());
+    }
+
+    #[test]
+    fn test_solve_mate() {
+        for fen in [
+            "7k/8/8/5K2/8/8/8/6Q1 w - - 0 1",
+            "k7/8/2K5/8/8/8/8/7R w - - 0 1",
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let solution = solve_mate(&board, 5).unwrap();
+            let depth = solution.moves * 2 - 1;
+            assert_eq!(
+                search(&board, depth).score,
+                Score::Mate(solution.moves as i32)
+            );
+            assert_eq!(solve_mate(&board, solution.moves * 2 - 2), None);
+
+            let mut played = board.clone();
+            for &mov in &solution.line {
+                played.make_move(mov).unwrap();
+            }
+            assert!(played.is_checkmate());
+        }
+
+        // A bare king cannot mate
+        let board = Board::from_fen("7k/5K2/8/8/8/8/8/6Q1 b - - 0 1").unwrap();
+        assert_eq!(solve_mate(&board, 3), None);
+        let mated = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(solve_mate(&mated, 3), None);
+    }
+}
@@ -0,0 +1,190 @@
+//! [`serde`] support, enabled by the `serde` feature. Every type is represented by the text
+//! used in FEN and LAN, so serialized positions and moves stay readable and compact:
+//!
+//! | Type               | Representation                                   |
+//! |--------------------|--------------------------------------------------|
+//! | [`Board`]          | FEN, e.g. `"8/8/8/8/8/8/8/K1k5 w - - 0 1"`       |
+//! | [`Move`]           | LAN, e.g. `"e2e4"` or `"e7e8q"`                  |
+//! | [`Square`]         | `"e4"`                                           |
+//! | [`Color`]          | `"w"` or `"b"`                                   |
+//! | [`Piece`]          | `"p"`, `"n"`, `"b"`, `"r"`, `"q"` or `"k"`       |
+//! | [`CastlingRights`] | `"KQkq"` or any subset of it, `"-"` if empty     |
+//!
+//! A board is restored from its FEN, so the repetition history and the played moves are not
+//! preserved.
+//!
+//! The `serde-structural` feature derives the implementations of [`Move`] and [`Square`]
+//! instead, encoding moves by their variant and fields (`{"Regular":{"src":"E2","dst":"E4"}}`
+//! in JSON), for formats where a fixed structure is preferred over parsing text.
+
+use crate::board::Board;
+use crate::notation::{CastlingRights, Color, Piece};
+#[cfg(not(feature = "serde-structural"))]
+use crate::notation::{Move, Square};
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// Deserializes a value from a string with the given parser.
+struct TextVisitor<T, F> {
+    expecting: &'static str,
+    parse: F,
+    value: PhantomData<T>,
+}
+
+impl<T, F, E> Visitor<'_> for TextVisitor<T, F>
+where
+    F: FnOnce(&str) -> Result<T, E>,
+    E: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<D: de::Error>(self, text: &str) -> Result<T, D> {
+        (self.parse)(text).map_err(D::custom)
+    }
+}
+
+fn deserialize_text<'de, D, T, E>(
+    deserializer: D,
+    expecting: &'static str,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    E: fmt::Display,
+{
+    deserializer.deserialize_str(TextVisitor {
+        expecting,
+        parse,
+        value: PhantomData,
+    })
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_fen())
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_text(deserializer, "a FEN string", Board::from_fen)
+    }
+}
+
+#[cfg(not(feature = "serde-structural"))]
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_lan())
+    }
+}
+
+#[cfg(not(feature = "serde-structural"))]
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_text(deserializer, "a move in LAN such as \"e2e4\"", |text| {
+            Move::from_lan_strict(text).map_err(|err| format!("{err}"))
+        })
+    }
+}
+
+#[cfg(not(feature = "serde-structural"))]
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(not(feature = "serde-structural"))]
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_text(deserializer, "a square such as \"e4\"", str::parse)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Color::White => "w",
+            Color::Black => "b",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_text(deserializer, "\"w\" or \"b\"", |text| match text {
+            "w" => Ok(Color::White),
+            "b" => Ok(Color::Black),
+            _ => Err(format!("unknown color {text:?}")),
+        })
+    }
+}
+
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Piece::Pawn => "p",
+            Piece::Rook => "r",
+            Piece::Knight => "n",
+            Piece::Bishop => "b",
+            Piece::Queen => "q",
+            Piece::King => "k",
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_text(deserializer, "one of \"pnbrqk\"", |text| match text {
+            "p" => Ok(Piece::Pawn),
+            "r" => Ok(Piece::Rook),
+            "n" => Ok(Piece::Knight),
+            "b" => Ok(Piece::Bishop),
+            "q" => Ok(Piece::Queen),
+            "k" => Ok(Piece::King),
+            _ => Err(format!("unknown piece {text:?}")),
+        })
+    }
+}
+
+const CASTLING_CHARS: [(char, CastlingRights); 4] = [
+    ('K', CastlingRights::WHITE_KINGSIDE),
+    ('Q', CastlingRights::WHITE_QUEENSIDE),
+    ('k', CastlingRights::BLACK_KINGSIDE),
+    ('q', CastlingRights::BLACK_QUEENSIDE),
+];
+
+impl Serialize for CastlingRights {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text: String = CASTLING_CHARS
+            .iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .map(|(c, _)| c)
+            .collect();
+        serializer.serialize_str(if text.is_empty() { "-" } else { &text })
+    }
+}
+
+impl<'de> Deserialize<'de> for CastlingRights {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_text(deserializer, "castling rights such as \"KQkq\"", |text| {
+            if text == "-" {
+                return Ok(CastlingRights::empty());
+            }
+            text.chars().try_fold(CastlingRights::empty(), |rights, c| {
+                match CASTLING_CHARS.iter().find(|(char, _)| *char == c) {
+                    Some((_, flag)) if !rights.contains(*flag) => Ok(rights | *flag),
+                    _ => Err(format!("unexpected castling right {c:?}")),
+                }
+            })
+        })
+    }
+}
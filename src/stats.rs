@@ -0,0 +1,371 @@
+//! Statistics for interpreting match results: Elo difference with a 95% confidence interval
+//! and the likelihood of superiority (LOS).
+//!
+//! Results are counted either per game ([`Wdl`]) or per pair of games played from the same
+//! opening with colors swapped ([`Pentanomial`]). Pairing removes most of the variance caused
+//! by unbalanced openings, so the pentanomial error bars are the more accurate ones when games
+//! are played in pairs.
+//!
+//! ```
+//! use shax::notation::{Color, Winner};
+//! use shax::stats::Wdl;
+//!
+//! let mut wdl = Wdl::default();
+//! wdl.record(Winner::White, Color::White);
+//! wdl.record(Winner::Draw, Color::Black);
+//! wdl.record(Winner::White, Color::Black);
+//! assert_eq!(wdl, Wdl { wins: 1, draws: 1, losses: 1 });
+//! assert_eq!(wdl.elo().unwrap().elo, 0.0);
+//! ```
+
+use crate::notation::{Color, Winner};
+use std::fmt;
+
+/// Two-sided 95% quantile of the standard normal distribution.
+const Z_95: f64 = 1.959963984540054;
+
+/// Converts an expected score in `[0, 1]` to an Elo difference. Scores of 0 and 1 give
+/// infinite differences.
+///
+/// ```
+/// use shax::stats::elo_from_score;
+///
+/// assert_eq!(elo_from_score(0.5), 0.0);
+/// assert!((elo_from_score(0.75) - 190.85).abs() < 0.01);
+/// ```
+pub fn elo_from_score(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Elo difference with the half width of its 95% confidence interval. The margin is infinite
+/// when the interval reaches a score of 0 or 1, which takes only a few lopsided games.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub margin: f64,
+}
+
+/// Formats the estimate as `+147.2 +/- 66.0`.
+impl fmt::Display for EloEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let precision = f.precision().unwrap_or(1);
+        write!(
+            f,
+            "{:+.precision$} +/- {:.precision$}",
+            self.elo, self.margin
+        )
+    }
+}
+
+/// Win, draw and loss counts of a player.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Wdl {
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+
+impl Wdl {
+    /// Counts the result of a game in which the player had the given color.
+    pub fn record(&mut self, result: Winner, color: Color) {
+        match points(result, color) {
+            2 => self.wins += 1,
+            1 => self.draws += 1,
+            _ => self.losses += 1,
+        }
+    }
+
+    pub fn games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Points scored per game, or [`None`] if no games were played.
+    pub fn score(&self) -> Option<f64> {
+        let games = self.games();
+        (games > 0).then(|| (self.wins as f64 + self.draws as f64 / 2.0) / games as f64)
+    }
+
+    /// Elo difference to the opponent, or [`None`] if no games were played.
+    pub fn elo(&self) -> Option<EloEstimate> {
+        estimate(&[(self.losses, 0.0), (self.draws, 0.5), (self.wins, 1.0)])
+    }
+
+    /// Likelihood of superiority: the probability that the player is stronger than the
+    /// opponent, given the decisive games. [`None`] if no game was decisive.
+    ///
+    /// ```
+    /// use shax::stats::Wdl;
+    ///
+    /// let wdl = Wdl { wins: 60, draws: 20, losses: 20 };
+    /// assert!(wdl.los().unwrap() > 0.9999);
+    /// let even = Wdl { wins: 10, draws: 80, losses: 10 };
+    /// assert!((even.los().unwrap() - 0.5).abs() < 1e-6);
+    /// ```
+    pub fn los(&self) -> Option<f64> {
+        let decisive = self.wins + self.losses;
+        (decisive > 0).then(|| {
+            let z = (self.wins as f64 - self.losses as f64) / (2.0 * decisive as f64).sqrt();
+            0.5 * (1.0 + erf(z))
+        })
+    }
+}
+
+/// Counts of game pairs by the points a player scored in them, from 0 (two losses) to 2 (two
+/// wins) in steps of a half point. The two games of a pair are played from the same opening
+/// with colors swapped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pentanomial {
+    pub counts: [u64; 5],
+}
+
+impl Pentanomial {
+    /// Counts a pair from the results of the game where the player had white and the game
+    /// where it had black.
+    pub fn record_pair(&mut self, as_white: Winner, as_black: Winner) {
+        let points = points(as_white, Color::White) + points(as_black, Color::Black);
+        self.counts[points] += 1;
+    }
+
+    pub fn pairs(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Totals of the games in the pairs. Pairs that scored one point are counted as two draws,
+    /// since a win and a loss cannot be told apart from them.
+    pub fn wdl(&self) -> Wdl {
+        let [zero, half, one, one_and_half, two] = self.counts;
+        Wdl {
+            wins: 2 * two + one_and_half,
+            draws: half + 2 * one + one_and_half,
+            losses: 2 * zero + half,
+        }
+    }
+
+    /// Points scored per game, or [`None`] if no pairs were played.
+    pub fn score(&self) -> Option<f64> {
+        let pairs = self.pairs();
+        (pairs > 0).then(|| {
+            let points: u64 = self.counts.iter().zip(0..).map(|(&n, i)| n * i).sum();
+            points as f64 / 4.0 / pairs as f64
+        })
+    }
+
+    /// Elo difference to the opponent, or [`None`] if no pairs were played.
+    ///
+    /// ```
+    /// use shax::stats::Pentanomial;
+    ///
+    /// let pentanomial = Pentanomial { counts: [5, 20, 50, 30, 10] };
+    /// let elo = pentanomial.elo().unwrap();
+    /// assert_eq!(format!("{elo}"), "+30.3 +/- 30.9");
+    /// ```
+    pub fn elo(&self) -> Option<EloEstimate> {
+        estimate(&self.weighted_scores())
+    }
+
+    /// Likelihood of superiority, or [`None`] if no pairs were played or all of them scored
+    /// the same.
+    pub fn los(&self) -> Option<f64> {
+        let (mean, error) = mean_and_error(&self.weighted_scores())?;
+        (error > 0.0).then(|| 0.5 * (1.0 + erf((mean - 0.5) / error / 2f64.sqrt())))
+    }
+
+    fn weighted_scores(&self) -> [(u64, f64); 5] {
+        let [zero, half, one, one_and_half, two] = self.counts;
+        [
+            (zero, 0.0),
+            (half, 0.25),
+            (one, 0.5),
+            (one_and_half, 0.75),
+            (two, 1.0),
+        ]
+    }
+}
+
+/// Points of a game for a player of the given color, counted in half points.
+fn points(result: Winner, color: Color) -> usize {
+    match result {
+        Winner::Draw => 1,
+        winner if winner == color.into() => 2,
+        _ => 0,
+    }
+}
+
+/// Mean score and its standard error from counts of samples with the given scores.
+fn mean_and_error(samples: &[(u64, f64)]) -> Option<(f64, f64)> {
+    let total: u64 = samples.iter().map(|&(count, _)| count).sum();
+    if total == 0 {
+        return None;
+    }
+    let n = total as f64;
+    let mean = samples.iter().map(|&(c, s)| c as f64 * s).sum::<f64>() / n;
+    let variance = samples
+        .iter()
+        .map(|&(c, s)| c as f64 * (s - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    Some((mean, (variance / n).sqrt()))
+}
+
+/// Elo estimate from the mean score. The bounds of the score interval are clamped to `[0, 1]`,
+/// and the margin is infinite if either of them reaches 0 or 1, as happens with lopsided
+/// results.
+fn estimate(samples: &[(u64, f64)]) -> Option<EloEstimate> {
+    let (mean, error) = mean_and_error(samples)?;
+    let low = elo_from_score((mean - Z_95 * error).clamp(0.0, 1.0));
+    let high = elo_from_score((mean + Z_95 * error).clamp(0.0, 1.0));
+    let margin = if low.is_finite() && high.is_finite() {
+        (high - low) / 2.0
+    } else {
+        f64::INFINITY
+    };
+    Some(EloEstimate {
+        elo: elo_from_score(mean),
+        margin,
+    })
+}
+
+/// Error function, with an absolute error below 1.5e-7 (Abramowitz and Stegun 7.1.26).
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    y.copysign(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_wdl_elo() {
+        let elo = Wdl {
+            wins: 60,
+            draws: 20,
+            losses: 20,
+        }
+        .elo()
+        .unwrap();
+        assert_close(elo.elo, 147.19071411783776);
+        assert_close(elo.margin, 66.01338122038528);
+        assert_eq!(elo.to_string(), "+147.2 +/- 66.0");
+
+        let elo = Wdl {
+            wins: 10,
+            draws: 80,
+            losses: 10,
+        }
+        .elo()
+        .unwrap();
+        assert_close(elo.elo, 0.0);
+        assert_close(elo.margin, 30.531864321710678);
+
+        assert_eq!(Wdl::default().elo(), None);
+        assert_eq!(Wdl::default().los(), None);
+    }
+
+    #[test]
+    fn test_lopsided_elo() {
+        for wdl in [
+            Wdl {
+                wins: 9,
+                draws: 0,
+                losses: 1,
+            },
+            Wdl {
+                wins: 8,
+                draws: 2,
+                losses: 0,
+            },
+        ] {
+            let elo = wdl.elo().unwrap();
+            assert!(elo.elo.is_finite() && elo.elo > 0.0);
+            assert_eq!(elo.margin, f64::INFINITY);
+        }
+
+        let elo = Wdl {
+            wins: 10,
+            draws: 0,
+            losses: 0,
+        }
+        .elo()
+        .unwrap();
+        assert_eq!(elo.elo, f64::INFINITY);
+        assert_eq!(elo.margin, f64::INFINITY);
+
+        let elo = Wdl {
+            wins: 0,
+            draws: 0,
+            losses: 10,
+        }
+        .elo()
+        .unwrap();
+        assert_eq!(elo.elo, f64::NEG_INFINITY);
+        assert_eq!(elo.margin, f64::INFINITY);
+
+        let elo = Pentanomial {
+            counts: [0, 0, 1, 2, 7],
+        }
+        .elo()
+        .unwrap();
+        assert!(elo.elo.is_finite());
+        assert_eq!(elo.margin, f64::INFINITY);
+    }
+
+    #[test]
+    fn test_wdl_los() {
+        let wdl = Wdl {
+            wins: 60,
+            draws: 20,
+            losses: 20,
+        };
+        assert!((wdl.los().unwrap() - 0.9999961278917845).abs() < 1e-6);
+        let wdl = Wdl {
+            wins: 20,
+            draws: 20,
+            losses: 60,
+        };
+        assert!(wdl.los().unwrap() < 1e-5);
+    }
+
+    #[test]
+    fn test_pentanomial() {
+        let pentanomial = Pentanomial {
+            counts: [5, 20, 50, 30, 10],
+        };
+        let elo = pentanomial.elo().unwrap();
+        assert_close(elo.elo, 30.288285575247322);
+        assert_close(elo.margin, 30.8728696143341);
+        assert!((pentanomial.los().unwrap() - 0.9736767088816152).abs() < 1e-6);
+        assert_eq!(pentanomial.pairs(), 115);
+        assert_eq!(pentanomial.wdl().games(), 230);
+        assert_close(
+            pentanomial.score().unwrap(),
+            pentanomial.wdl().score().unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_record_pair() {
+        let mut pentanomial = Pentanomial::default();
+        pentanomial.record_pair(Winner::White, Winner::Black);
+        pentanomial.record_pair(Winner::White, Winner::White);
+        pentanomial.record_pair(Winner::Draw, Winner::Black);
+        pentanomial.record_pair(Winner::Black, Winner::White);
+        assert_eq!(pentanomial.counts, [1, 0, 1, 1, 1]);
+        assert_eq!(pentanomial.score(), Some(0.5625));
+    }
+
+    #[test]
+    fn test_erf() {
+        assert!(erf(0.0).abs() < 1.5e-7);
+        assert!((erf(1.0) - 0.8427007929497149).abs() < 1.5e-7);
+        assert!((erf(-0.5) + 0.5204998778130465).abs() < 1.5e-7);
+    }
+}
@@ -0,0 +1,84 @@
+use alloc::sync::Arc;
+use core::error;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Shared flag for cooperatively cancelling long-running work such as perft.
+///
+/// Clones share the same flag, so one clone can be handed to the worker and another kept by
+/// the caller (e.g. a UI thread) to request cancellation.
+///
+/// ```
+/// use shax::board::Board;
+/// use shax::stop::{StopToken, Stopped};
+///
+/// let token = StopToken::new();
+/// let board = Board::default();
+/// assert_eq!(board.try_perft(2, &token), Ok(400));
+///
+/// token.stop();
+/// assert_eq!(board.try_perft(2, &token), Err(Stopped));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StopToken(Arc<Flags>);
+
+#[derive(Debug, Default)]
+struct Flags {
+    stopped: AtomicBool,
+    ponderhit: AtomicBool,
+}
+
+impl StopToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests every holder of the token to stop as soon as possible.
+    pub fn stop(&self) {
+        self.0.stopped.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns [`true`] if stopping was requested.
+    pub fn is_stopped(&self) -> bool {
+        self.0.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Tells a search pondering on the opponent's time that the expected move was played, the
+    /// equivalent of the UCI `ponderhit` command: the search keeps going, now within its time
+    /// control. Work that does not ponder ignores it.
+    pub fn ponderhit(&self) {
+        self.0.ponderhit.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns [`true`] if [`StopToken::ponderhit`] was called.
+    pub fn is_ponderhit(&self) -> bool {
+        self.0.ponderhit.load(Ordering::Relaxed)
+    }
+
+    /// Clears the stop request and ponderhit so the token can be reused for new work.
+    pub fn reset(&self) {
+        self.0.stopped.store(false, Ordering::Relaxed);
+        self.0.ponderhit.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns [`Err`] with [`Stopped`] if stopping was requested.
+    pub fn check(&self) -> Result<(), Stopped> {
+        if self.is_stopped() {
+            Err(Stopped)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Error returned by work that was cancelled through a [`StopToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stopped;
+
+impl fmt::Display for Stopped {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "stopped")
+    }
+}
+
+impl error::Error for Stopped {}
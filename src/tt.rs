@@ -0,0 +1,260 @@
+//! Transposition table shared by the threads of a search.
+//!
+//! The table is lockless: each entry is two atomic words, the data and the key XORed with the
+//! data. An entry torn by two threads writing at once fails the key check on the next probe,
+//! so it is read as a miss instead of returning another position's data.
+//!
+//! Entries are stamped with the generation of the search that stored them. A search keeps the
+//! deeper of two entries competing for a slot, except against entries of earlier searches,
+//! which it always replaces: the table is kept from one search to the next without filling up
+//! with deep results for positions the game has left behind.
+
+use crate::notation::Move;
+use crate::wire::{decode_move, encode_move};
+use core::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+/// Generations wrap around after this many searches.
+const GENERATIONS: u8 = 64;
+
+/// Kind of bound the stored score is for the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bound {
+    /// The score is exact.
+    Exact,
+    /// The search failed high: the score is at least the stored one.
+    Lower,
+    /// The search failed low: the score is at most the stored one.
+    Upper,
+}
+
+/// Result of an earlier search of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Entry {
+    pub(crate) best_move: Option<Move>,
+    pub(crate) score: i32,
+    pub(crate) depth: u8,
+    pub(crate) bound: Bound,
+}
+
+impl Entry {
+    /// Packs the entry of the generation into a word: the move in bits 0-15, the score in bits
+    /// 16-31, the depth in bits 32-39, the bound in bits 40-41 and the generation in bits 42-47.
+    fn pack(&self, generation: u8) -> u64 {
+        // A move from a square to itself is never legal, so code 0 stands for no move
+        let mov = self.best_move.map_or(0, encode_move) as u64;
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        mov | (self.score as i16 as u16 as u64) << 16
+            | (self.depth as u64) << 32
+            | bound << 40
+            | (generation as u64) << 42
+    }
+
+    fn generation(data: u64) -> u8 {
+        (data >> 42) as u8 & (GENERATIONS - 1)
+    }
+
+    fn unpack(data: u64) -> Option<Self> {
+        let mov = data as u16;
+        let bound = match data >> 40 & 0x3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            2 => Bound::Upper,
+            _ => return None,
+        };
+        Some(Entry {
+            best_move: if mov == 0 {
+                None
+            } else {
+                Some(decode_move(mov).ok()?)
+            },
+            score: (data >> 16) as u16 as i16 as i32,
+            depth: (data >> 32) as u8,
+            bound,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Slot {
+    /// Zobrist key XORed with the data.
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Hash table of positions already searched, keyed by their Zobrist key. It can be kept
+/// between searches and shared by any number of threads.
+///
+/// ```
+/// use shax::tt::TranspositionTable;
+///
+/// let table = TranspositionTable::new(16);
+/// assert_eq!(table.capacity(), 1 << 20);
+/// ```
+pub struct TranspositionTable {
+    slots: Box<[Slot]>,
+    /// Generation of the current search.
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// Creates a table using at most the given number of megabytes, rounded down to a power
+    /// of two number of entries. At least one entry is allocated.
+    pub fn new(megabytes: usize) -> Self {
+        let entries = (megabytes * 1024 * 1024 / size_of::<Slot>()).max(1);
+        let entries = 1 << entries.ilog2();
+        TranspositionTable {
+            slots: (0..entries).map(|_| Slot::default()).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    /// Returns the number of entries the table holds.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Forgets every stored position, e.g. before a new game.
+    pub fn clear(&self) {
+        self.generation.store(0, Ordering::Relaxed);
+        for slot in self.slots.iter() {
+            slot.check.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Starts a new generation, so that the entries stored so far give way to those of the next
+    /// search. Searches call it as they start.
+    pub(crate) fn new_search(&self) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation
+            .store((generation + 1) % GENERATIONS, Ordering::Relaxed);
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        &self.slots[key as usize & (self.slots.len() - 1)]
+    }
+
+    pub(crate) fn probe(&self, key: u64) -> Option<Entry> {
+        let slot = self.slot(key);
+        let data = slot.data.load(Ordering::Relaxed);
+        if slot.check.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        Entry::unpack(data)
+    }
+
+    /// Stores the entry, unless the slot holds a deeper entry of the current search. An exact
+    /// score always replaces an entry of the same position.
+    pub(crate) fn store(&self, key: u64, entry: Entry) {
+        let slot = self.slot(key);
+        let generation = self.generation.load(Ordering::Relaxed);
+        let old = slot.data.load(Ordering::Relaxed);
+        let same_key = slot.check.load(Ordering::Relaxed) ^ old == key;
+        let keep = Entry::unpack(old).is_some_and(|old_entry| {
+            Entry::generation(old) == generation
+                && old_entry.depth > entry.depth
+                && !(same_key && entry.bound == Bound::Exact)
+        });
+        if keep {
+            return;
+        }
+        let data = entry.pack(generation);
+        slot.check.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn entry(score: i32) -> Entry {
+        Entry {
+            best_move: Some(Move::from_lan("e7e8n").unwrap()),
+            score,
+            depth: 7,
+            bound: Bound::Lower,
+        }
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let table = TranspositionTable::new(1);
+        assert_eq!(table.probe(42), None);
+        table.store(42, entry(-31_990));
+        assert_eq!(table.probe(42), Some(entry(-31_990)));
+
+        // Another key mapping to the same slot replaces the entry
+        let other = 42 + table.capacity() as u64;
+        table.store(other, entry(5));
+        assert_eq!(table.probe(42), None);
+        assert_eq!(table.probe(other), Some(entry(5)));
+
+        let empty = Entry {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            bound: Bound::Exact,
+        };
+        table.store(7, empty);
+        assert_eq!(table.probe(7), Some(empty));
+
+        table.clear();
+        assert_eq!(table.probe(other), None);
+    }
+
+    #[test]
+    fn test_replacement() {
+        let table = TranspositionTable::new(1);
+        let other = 42 + table.capacity() as u64;
+        let shallow = Entry {
+            depth: 3,
+            ..entry(5)
+        };
+        table.store(42, entry(-31_990));
+
+        // Deeper entries of the current search are kept, unless replaced by an exact score
+        table.store(other, shallow);
+        table.store(42, shallow);
+        assert_eq!(table.probe(42), Some(entry(-31_990)));
+        let exact = Entry {
+            bound: Bound::Exact,
+            ..shallow
+        };
+        table.store(42, exact);
+        assert_eq!(table.probe(42), Some(exact));
+
+        // Entries of earlier searches can still be probed, but give way to any new one
+        table.store(42, entry(-31_990));
+        table.new_search();
+        assert_eq!(table.probe(42), Some(entry(-31_990)));
+        table.store(other, shallow);
+        assert_eq!(table.probe(42), None);
+        assert_eq!(table.probe(other), Some(shallow));
+    }
+
+    #[test]
+    fn test_concurrent_writes_are_never_mixed_up() {
+        // Every thread stores entries whose score is derived from the key, into few slots
+        let table = TranspositionTable::new(0);
+        thread::scope(|scope| {
+            for thread in 0..4u64 {
+                let table = &table;
+                scope.spawn(move || {
+                    for i in 0..20_000u64 {
+                        let key = (i * 4 + thread).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+                        table.store(key, entry((key % 1000) as i32));
+                        if let Some(found) = table.probe(key) {
+                            assert_eq!(found, entry((key % 1000) as i32));
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
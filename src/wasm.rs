@@ -0,0 +1,101 @@
+//! WebAssembly bindings, enabled by the `wasm` feature, so that a browser GUI can use shax as
+//! its rules engine. The crate is a plain library so that `no_std` builds stay possible, so build
+//! the WebAssembly module as a `cdylib` explicitly and generate the JavaScript glue with the
+//! `wasm-bindgen` CLI:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features wasm --target wasm32-unknown-unknown --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/shax.wasm
+//! ```
+//!
+//! and use it from JavaScript:
+//!
+//! ```js
+//! import init, { Board } from "./pkg/shax.js";
+//!
+//! await init();
+//!
+//! const board = new Board();
+//! board.legalMoves(); // ["a2a3", "a2a4", ...]
+//! board.makeMove("e2e4");
+//! board.fen(); // "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+//! Board.fromFen("8/8/8/8/8/8/8/K1k5 w - - 0 1");
+//! ```
+//!
+//! Moves are exchanged as LAN strings (see [`Move::to_lan`]) and errors are thrown as
+//! JavaScript `Error`s carrying the message of the underlying Rust error.
+
+use crate::board::Board;
+use crate::movelist::MoveList;
+use crate::notation::{Color, Move};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::*;
+
+/// A chess game, exported to JavaScript as `Board`.
+#[wasm_bindgen(js_name = Board)]
+pub struct WasmBoard {
+    board: Board,
+}
+
+#[wasm_bindgen(js_class = Board)]
+impl WasmBoard {
+    /// Creates a board with the standard starting position.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> WasmBoard {
+        WasmBoard {
+            board: Board::default(),
+        }
+    }
+
+    /// Creates a board from a FEN string.
+    #[wasm_bindgen(js_name = fromFen)]
+    pub fn from_fen(fen: &str) -> Result<WasmBoard, JsError> {
+        let board = Board::from_fen(fen).map_err(|err| JsError::new(&err.to_string()))?;
+        Ok(WasmBoard { board })
+    }
+
+    pub fn fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    /// `"w"` or `"b"`.
+    pub fn turn(&self) -> String {
+        match self.board.active() {
+            Color::White => "w",
+            Color::Black => "b",
+        }
+        .into()
+    }
+
+    /// Legal moves of the side to move in LAN, empty once the game is over.
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        if self.board.status().is_over() {
+            return Vec::new();
+        }
+        let mut moves = MoveList::new();
+        self.board.generate_moves_into(&mut moves);
+        moves.iter().map(Move::to_lan).collect()
+    }
+
+    /// Plays a move given in LAN, throwing if it cannot be parsed or is illegal.
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, lan: &str) -> Result<(), JsError> {
+        let mov = Move::from_lan(lan).map_err(|err| JsError::new(&err.to_string()))?;
+        self.board
+            .make_move(mov)
+            .map_err(|err| JsError::new(&err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = isCheck)]
+    pub fn is_check(&self) -> bool {
+        self.board.is_check(self.board.active())
+    }
+
+    #[wasm_bindgen(js_name = isGameOver)]
+    pub fn is_game_over(&self) -> bool {
+        self.board.status().is_over()
+    }
+}
@@ -0,0 +1,350 @@
+//! Compact binary encoding of a position together with its legal moves, meant for sending
+//! the game state to clients on every tick instead of FEN and SAN strings.
+//!
+//! Layout of a [`VERSION`] 3 packet (all integers are little-endian):
+//!
+//! | Bytes | Content                                                             |
+//! |-------|---------------------------------------------------------------------|
+//! | 1     | Format version                                                      |
+//! | 8     | Occupancy bitboard                                                  |
+//! | n / 2 | One nibble (`color << 3 \| piece`) per occupied square, A1 first    |
+//! | 1     | Active color (bit 0), castling rights (bits 1-4)                    |
+//! | 1     | Game status                                                         |
+//! | 1     | En passant square, or `0xff` if none                                |
+//! | 1     | Halfmove clock                                                      |
+//! | 1     | Number of legal moves                                               |
+//! | 2 * m | Legal moves encoded with [`encode_move`]                            |
+//!
+//! ```
+//! use shax::board::Board;
+//! use shax::wire;
+//!
+//! let board = Board::default();
+//! let packet = wire::encode(&board);
+//! assert_eq!(packet.len(), 70);
+//!
+//! let (decoded, moves) = wire::decode(&packet).unwrap();
+//! assert_eq!(format!("{decoded:#?}"), format!("{board:#?}"));
+//! assert_eq!(moves.len(), 20);
+//! ```
+
+use crate::board::{is_valid_en_passant, Board};
+use crate::notation::{
+    CastlingMove, CastlingRights, Color, GameStatus, Move, Piece, PromotionMove, PromotionPiece,
+    RegularMove, Square,
+};
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+/// Current version of the wire format, written as the first byte of every packet.
+pub const VERSION: u8 = 3;
+
+const NO_SQUARE: u8 = 0xff;
+
+const KIND_REGULAR: u16 = 0;
+const KIND_PROMOTION: u16 = 1;
+const KIND_CASTLING: u16 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    TrailingBytes,
+    BadPiece(u8),
+    BadSquare(u8),
+    BadStatus(u8),
+    BadMove(u16),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported wire format version {version}")
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of packet"),
+            Self::TrailingBytes => write!(f, "trailing bytes after packet"),
+            Self::BadPiece(nibble) => write!(f, "bad piece code {nibble:#x}"),
+            Self::BadSquare(square) => write!(f, "bad square index {square}"),
+            Self::BadStatus(status) => write!(f, "bad game status {status}"),
+            Self::BadMove(mov) => write!(f, "bad move code {mov:#06x}"),
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+/// Encodes a move as 16 bits: source square (bits 0-5), destination square (bits 6-11),
+/// promotion piece (bits 12-13) and move kind (bits 14-15).
+///
+/// ```
+/// use shax::notation::Move;
+/// use shax::wire::{decode_move, encode_move};
+///
+/// let mov = Move::from_lan("e7e8q").unwrap();
+/// assert_eq!(decode_move(encode_move(mov)), Ok(mov));
+/// ```
+pub fn encode_move(mov: Move) -> u16 {
+    let (src, dst, piece, kind) = match mov {
+        Move::Regular(regular) => (regular.src, regular.dst, 0, KIND_REGULAR),
+        Move::Promotion(promotion) => (
+            promotion.src,
+            promotion.dst,
+            promotion.piece as u16 - PromotionPiece::Rook as u16,
+            KIND_PROMOTION,
+        ),
+        Move::Castling(castling) => (castling.src, castling.dst, 0, KIND_CASTLING),
+    };
+    src as u16 | (dst as u16) << 6 | piece << 12 | kind << 14
+}
+
+/// Decodes a move encoded with [`encode_move`].
+pub fn decode_move(code: u16) -> Result<Move, DecodeError> {
+    let src = Square::from_index((code & 0x3f) as usize).unwrap();
+    let dst = Square::from_index((code >> 6 & 0x3f) as usize).unwrap();
+    let piece = (code >> 12 & 0x3) as usize;
+    match code >> 14 {
+        KIND_REGULAR if piece == 0 => Ok(Move::Regular(RegularMove { src, dst })),
+        KIND_PROMOTION => Ok(Move::Promotion(PromotionMove {
+            src,
+            dst,
+            piece: PromotionPiece::from_repr(piece as u8 + PromotionPiece::Rook as u8).unwrap(),
+        })),
+        KIND_CASTLING if piece == 0 => Ok(Move::Castling(CastlingMove { src, dst })),
+        _ => Err(DecodeError::BadMove(code)),
+    }
+}
+
+/// Encodes the position and the legal moves of the active color.
+///
+/// The repetition history is not transmitted, so the packet is meant for displaying the game
+/// state rather than for continuing the game from the decoded board.
+pub fn encode(board: &Board) -> Vec<u8> {
+    let mut occupancy = 0u64;
+    let mut nibbles = Vec::with_capacity(32);
+    for square in 0..64 {
+        if let Some((color, piece)) = board.on_square(Square::from_index(square).unwrap()) {
+            occupancy |= 1 << square;
+            nibbles.push((color as u8) << 3 | piece as u8);
+        }
+    }
+
+    let moves: Vec<u16> = board.color_moves(board.active()).map(encode_move).collect();
+
+    let mut packet = Vec::with_capacity(14 + nibbles.len().div_ceil(2) + moves.len() * 2);
+    packet.push(VERSION);
+    packet.extend_from_slice(&occupancy.to_le_bytes());
+    packet.extend(
+        nibbles
+            .chunks(2)
+            .map(|pair| pair[0] | pair.get(1).map_or(0, |high| high << 4)),
+    );
+
+    let status = match board.status() {
+        GameStatus::Ongoing => 0,
+        GameStatus::Checkmate {
+            winner: Color::White,
+        } => 1,
+        GameStatus::Checkmate {
+            winner: Color::Black,
+        } => 2,
+        GameStatus::Stalemate => 3,
+        GameStatus::FivefoldRepetition => 4,
+        GameStatus::SeventyFiveMoves => 5,
+        GameStatus::InsufficientMaterial => 6,
+        GameStatus::ThreefoldRepetition => 7,
+        GameStatus::FiftyMoves => 8,
+        GameStatus::DeadPosition => 9,
+    };
+    packet.push(board.active() as u8 | board.castling().bits() << 1);
+    packet.push(status);
+    packet.push(board.en_passant().map_or(NO_SQUARE, |square| square as u8));
+    packet.push(board.halfmove_clock().min(u8::MAX as usize) as u8);
+
+    packet.push(moves.len() as u8);
+    for mov in moves {
+        packet.extend_from_slice(&mov.to_le_bytes());
+    }
+
+    packet
+}
+
+/// Decodes a packet produced by [`encode`] into a board and its list of legal moves.
+pub fn decode(packet: &[u8]) -> Result<(Board, Vec<Move>), DecodeError> {
+    let mut reader = Reader { packet };
+
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let occupancy = u64::from_le_bytes(reader.bytes::<8>()?);
+    let mut bitboards = [[0; 6]; 2];
+    let mut pair = 0;
+    for (i, square) in (0..64).filter(|sq| occupancy & (1 << sq) != 0).enumerate() {
+        let nibble = if i % 2 == 0 {
+            pair = reader.byte()?;
+            pair & 0xf
+        } else {
+            pair >> 4
+        };
+        let color = Color::from_repr(nibble >> 3);
+        let piece = Piece::from_repr(nibble & 0x7);
+        match (color, piece) {
+            (Some(color), Some(piece)) => bitboards[color as usize][piece as usize] |= 1 << square,
+            _ => return Err(DecodeError::BadPiece(nibble)),
+        }
+    }
+
+    let flags = reader.byte()?;
+    let active = Color::from_repr(flags & 1).unwrap();
+    let castling = CastlingRights::from_bits_truncate(flags >> 1 & 0xf);
+    let status = match reader.byte()? {
+        0 => GameStatus::Ongoing,
+        1 => GameStatus::Checkmate {
+            winner: Color::White,
+        },
+        2 => GameStatus::Checkmate {
+            winner: Color::Black,
+        },
+        3 => GameStatus::Stalemate,
+        4 => GameStatus::FivefoldRepetition,
+        5 => GameStatus::SeventyFiveMoves,
+        6 => GameStatus::InsufficientMaterial,
+        7 => GameStatus::ThreefoldRepetition,
+        8 => GameStatus::FiftyMoves,
+        9 => GameStatus::DeadPosition,
+        status => return Err(DecodeError::BadStatus(status)),
+    };
+
+    let en_passant = match reader.byte()? {
+        NO_SQUARE => None,
+        square => Some(
+            Square::from_index(square as usize)
+                .filter(|&square| is_valid_en_passant(&bitboards, active, square))
+                .ok_or(DecodeError::BadSquare(square))?,
+        ),
+    };
+    let halfclock = reader.byte()? as u16;
+
+    let count = reader.byte()? as usize;
+    let mut moves = Vec::with_capacity(count);
+    for _ in 0..count {
+        moves.push(decode_move(u16::from_le_bytes(reader.bytes::<2>()?))?);
+    }
+
+    if !reader.packet.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+
+    let board = Board::from_parts(bitboards, active, castling, en_passant, halfclock, status);
+    Ok((board, moves))
+}
+
+struct Reader<'a> {
+    packet: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let (&byte, rest) = self
+            .packet
+            .split_first()
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        self.packet = rest;
+        Ok(byte)
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        if self.packet.len() < N {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.packet.split_at(N);
+        self.packet = rest;
+        Ok(bytes.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::RulesProfile;
+
+    #[test]
+    fn test_encode_decode_move() {
+        for mov in [
+            "a1h8", "h8a1", "e1g1", "e8c8", "a7a8r", "b2b1n", "g7h8b", "h2g1q",
+        ] {
+            let mov = Move::from_lan(mov).unwrap();
+            assert_eq!(decode_move(encode_move(mov)), Ok(mov));
+        }
+        let castling = Move::Castling(CastlingMove {
+            src: Square::E8,
+            dst: Square::A8,
+        });
+        assert_eq!(decode_move(encode_move(castling)), Ok(castling));
+        assert_eq!(decode_move(0xf000), Err(DecodeError::BadMove(0xf000)));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let packet = encode(&Board::default());
+        assert_eq!(
+            decode(&packet[..packet.len() - 1]).unwrap_err(),
+            DecodeError::UnexpectedEnd
+        );
+        assert_eq!(
+            decode(&[&packet[..], &[0]].concat()).unwrap_err(),
+            DecodeError::TrailingBytes
+        );
+        assert_eq!(
+            decode(&[1]).unwrap_err(),
+            DecodeError::UnsupportedVersion(1)
+        );
+
+        // An en passant square without the pawn that would have pushed past it
+        let board = Board::from_fen("4k3/8/8/8/8/8/3P4/4K3 w - - 0 1").unwrap();
+        let mut packet = encode(&board);
+        let en_passant = packet.len() - 3 - 2 * board.color_moves(board.active()).count();
+        assert_eq!(packet[en_passant], NO_SQUARE);
+        packet[en_passant] = Square::E3 as u8;
+        assert_eq!(
+            decode(&packet).unwrap_err(),
+            DecodeError::BadSquare(Square::E3 as u8)
+        );
+    }
+
+    #[test]
+    fn test_decode_after_moves() {
+        let mut board = Board::default();
+        for mov in ["e2e4", "c7c5", "e4e5", "d7d5"] {
+            board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+        }
+        let (decoded, moves) = decode(&encode(&board)).unwrap();
+        assert_eq!(format!("{decoded:?}"), format!("{board:?}"));
+        assert_eq!(format!("{decoded:#?}"), format!("{board:#?}"));
+        assert!(moves.contains(&Move::from_lan("e5d6").unwrap()));
+    }
+
+    #[test]
+    fn test_decode_status() {
+        let mut board = Board::default();
+        for mov in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            board.make_move(Move::from_lan(mov).unwrap()).unwrap();
+        }
+        let (decoded, moves) = decode(&encode(&board)).unwrap();
+        assert_eq!(decoded.status(), board.status());
+        assert!(moves.is_empty());
+
+        let board = Board::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 1").unwrap();
+        let (decoded, _) = decode(&encode(&board)).unwrap();
+        assert_eq!(decoded.status(), GameStatus::InsufficientMaterial);
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 70")
+            .unwrap()
+            .with_rules(RulesProfile::Practical);
+        let (decoded, _) = decode(&encode(&board)).unwrap();
+        assert_eq!(decoded.status(), GameStatus::FiftyMoves);
+    }
+}
@@ -0,0 +1,82 @@
+/// Random keys for Zobrist hashing, generated at compile time with SplitMix64 so that keys
+/// are identical across builds and platforms.
+pub struct Keys {
+    pub pieces: [[[u64; 64]; 6]; 2],
+    pub black_to_move: u64,
+    pub castling: [u64; 16],
+    pub en_passant: [u64; 8],
+}
+
+pub const KEYS: Keys = generate();
+
+pub(crate) const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    (state, z ^ (z >> 31))
+}
+
+const fn generate() -> Keys {
+    let mut keys = Keys {
+        pieces: [[[0; 64]; 6]; 2],
+        black_to_move: 0,
+        castling: [0; 16],
+        en_passant: [0; 8],
+    };
+    let mut state = 0x5348_4158; // "SHAX"
+    let mut key;
+
+    let mut color = 0;
+    while color < 2 {
+        let mut piece = 0;
+        while piece < 6 {
+            let mut square = 0;
+            while square < 64 {
+                (state, key) = splitmix64(state);
+                keys.pieces[color][piece][square] = key;
+                square += 1;
+            }
+            piece += 1;
+        }
+        color += 1;
+    }
+
+    (state, key) = splitmix64(state);
+    keys.black_to_move = key;
+
+    // Castling keys are indexed by the whole rights bitset, with no rights hashing to zero
+    let mut rights = 1;
+    while rights < 16 {
+        (state, key) = splitmix64(state);
+        keys.castling[rights] = key;
+        rights += 1;
+    }
+
+    let mut file = 0;
+    while file < 8 {
+        (state, key) = splitmix64(state);
+        keys.en_passant[file] = key;
+        file += 1;
+    }
+
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_unique() {
+        let mut all: Vec<u64> = KEYS.pieces.iter().flatten().flatten().copied().collect();
+        all.push(KEYS.black_to_move);
+        all.extend(&KEYS.castling[1..]);
+        all.extend(&KEYS.en_passant);
+        let count = all.len();
+        all.sort_unstable();
+        all.dedup();
+        assert_eq!(all.len(), count);
+        assert!(all.iter().all(|&key| key != 0));
+    }
+}
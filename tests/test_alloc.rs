@@ -0,0 +1,111 @@
+//! Checks that the hot loops of move generation and search run without heap allocation, by
+//! counting the allocations made on the current thread.
+
+use shax::board::Board;
+use shax::movelist::MoveList;
+use shax::notation::Move;
+use shax::positions;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made on this thread while running `f`.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// Plays every line to `depth` with preallocated move lists, one per ply.
+fn walk(board: &mut Board, lists: &mut [MoveList]) -> u64 {
+    let Some((moves, rest)) = lists.split_first_mut() else {
+        return 1;
+    };
+    board.generate_moves_into(moves);
+    let mut nodes = 0;
+    for &mov in moves.iter() {
+        let undo = board.do_move(mov);
+        nodes += walk(board, rest);
+        board.undo_move(undo);
+    }
+    nodes
+}
+
+#[test]
+fn test_make_unmake_does_not_allocate() {
+    for position in positions::ALL {
+        let mut board = position.board();
+        let mut lists = [MoveList::new(), MoveList::new(), MoveList::new()];
+        let mut nodes = 0;
+        let count = allocations(|| nodes = walk(&mut board, &mut lists));
+        assert_eq!(count, 0, "{}", position.name);
+        assert_eq!(nodes, position.perft[2], "{}", position.name);
+    }
+}
+
+#[test]
+fn test_perft_does_not_allocate() {
+    let board = positions::KIWIPETE.board();
+    assert_eq!(allocations(|| assert_eq!(board.perft(3), 97862)), 0);
+}
+
+#[test]
+fn test_clone_from_reuses_memory() {
+    let mut board = Board::default();
+    for lan in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+        board.make_move(Move::from_lan(lan).unwrap()).unwrap();
+    }
+    let mut copy = board.clone();
+    let mut other = board.clone();
+    other.make_move(Move::from_lan("e2e4").unwrap()).unwrap();
+
+    // The first copy grows the history of `copy` to fit the longer one of `other`.
+    copy.clone_from(&other);
+    let count = allocations(|| {
+        for _ in 0..100 {
+            copy.clone_from(&other);
+            copy.clone_from(&board);
+        }
+    });
+    assert_eq!(count, 0);
+    assert_eq!(copy.to_fen(), board.to_fen());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_search_allocations_do_not_grow_with_depth() {
+    use shax::search::search;
+
+    let board = positions::KIWIPETE.board();
+    let shallow = allocations(|| {
+        search(&board, 2);
+    });
+    let deep = allocations(|| {
+        search(&board, 4);
+    });
+    assert_eq!(shallow, deep);
+}
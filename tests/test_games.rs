@@ -1,5 +1,5 @@
-use shax::board::Board;
-use shax::notation::{Move, Winner};
+use shax::board::{Board, RulesProfile};
+use shax::notation::{Color, GameStatus, Move, Piece, Square, Winner};
 
 fn make_move(board: &mut Board, mov: &str) {
     board
@@ -24,6 +24,12 @@ fn test_anderssen_vs_kieseritzky() {
         make_move(&mut board, mov)
     }
     assert_eq!(board.winner(), Some(Winner::White));
+    assert_eq!(
+        board.status(),
+        GameStatus::Checkmate {
+            winner: Color::White
+        }
+    );
 }
 
 #[test]
@@ -53,9 +59,184 @@ fn test_draw_repetition() {
     let mut board = Board::default();
     for mov in [
         "e2e4", "e7e5", "f1e2", "f8e7", "e2f1", "e7f8", "f1e2", "f8e7", "e2f1", "e7f8", "f1e2",
-        "f8e7", "e2f1", "e7f8", "f1e2", "f8e7", "e2f1", "e7f8", "f1e2", "f8e7", "e2f1", "e7f8",
+        "f8e7", "e2f1", "e7f8", "f1e2", "f8e7", "e2f1",
     ] {
+        assert!(!board.status().is_over());
         make_move(&mut board, mov)
     }
+    assert_eq!(board.winner(), None);
+    assert_eq!(board.repetition_count(), 4);
+    assert!(board.can_claim_draw());
+
+    // The fifth occurrence ends the game
+    make_move(&mut board, "e7f8");
+    assert_eq!(board.winner(), Some(Winner::Draw));
+    assert_eq!(board.status(), GameStatus::FivefoldRepetition);
+    assert!(!board.can_claim_draw());
+}
+
+#[test]
+fn test_draw_practical_rules() {
+    let mut board = Board::default().with_rules(RulesProfile::Practical);
+    for mov in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+        make_move(&mut board, mov)
+    }
+    assert_eq!(board.status(), GameStatus::Ongoing);
+    make_move(&mut board, "f6g8");
+    assert_eq!(board.status(), GameStatus::ThreefoldRepetition);
+
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 70").unwrap();
+    assert_eq!(board.status(), GameStatus::Ongoing);
+    let mut practical = board.clone().with_rules(RulesProfile::Practical);
+    make_move(&mut practical, "a1a2");
+    assert_eq!(practical.status(), GameStatus::FiftyMoves);
+
+    let mut fide = board.with_rules(RulesProfile::StrictFide);
+    make_move(&mut fide, "a1a2");
+    assert_eq!(fide.status(), GameStatus::Ongoing);
+    assert!(fide.can_claim_draw());
+}
+
+#[test]
+fn test_repetition_requires_same_castling_rights() {
+    let mut board = Board::default();
+    for mov in ["e2e4", "e7e5", "e1e2", "e8e7", "e2e1", "e7e8"] {
+        make_move(&mut board, mov)
+    }
+    // Same placement as after 2. ... e5, but both sides have lost their castling rights
+    assert_eq!(board.repetition_count(), 1);
+
+    for mov in ["e1e2", "e8e7", "e2e1", "e7e8"] {
+        make_move(&mut board, mov)
+    }
+    assert_eq!(board.repetition_count(), 2);
+}
+
+#[test]
+fn test_chess960_castling() {
+    let mut board =
+        Board::from_fen("1r3kr1/pppppppp/8/8/8/8/PPPPPPPP/1R3KR1 w GBgb - 0 1").unwrap();
+    assert!(board.is_chess960());
+    assert_eq!(
+        board.zobrist(),
+        Board::from_fen("1r3kr1/pppppppp/8/8/8/8/PPPPPPPP/1R3KR1 w KQkq - 0 1")
+            .unwrap()
+            .zobrist()
+    );
+
+    // The king takes its own rook, swapping squares with it
+    make_move(&mut board, "f1g1");
+    assert_eq!(
+        board.on_square(Square::G1),
+        Some((Color::White, Piece::King))
+    );
+    assert_eq!(
+        board.on_square(Square::F1),
+        Some((Color::White, Piece::Rook))
+    );
+
+    make_move(&mut board, "f8b8");
+    assert_eq!(
+        board.on_square(Square::C8),
+        Some((Color::Black, Piece::King))
+    );
+    assert_eq!(
+        board.on_square(Square::D8),
+        Some((Color::Black, Piece::Rook))
+    );
+    assert_eq!(board.on_square(Square::B8), None);
+}
+
+#[test]
+fn test_chess960_castling_through_rook_shield() {
+    // The rook on b1 shields c1 from the rook on a1 only until it moves to d1
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/rR3K2 w B - 0 1").unwrap();
+    assert_eq!(board.castling_moves(Color::White, Square::F1).count(), 0);
+    assert!(board.make_move(Move::from_lan("f1b1").unwrap()).is_err());
+}
+
+#[test]
+fn test_draw_insufficient_material() {
+    for fen in [
+        "8/8/4k3/8/8/3K4/8/8 w - - 0 1",
+        "8/8/4k3/8/8/3KN3/8/8 b - - 0 1",
+        "8/8/4k3/8/8/3KB3/8/8 b - - 0 1",
+        "8/8/4kb2/8/8/2BK4/8/8 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.status(), GameStatus::InsufficientMaterial, "{fen}");
+    }
+
+    for fen in [
+        "8/8/4kn2/8/8/3KN3/8/8 w - - 0 1",
+        "8/8/4k1b1/8/8/2BK4/8/8 w - - 0 1",
+        "8/8/4k3/8/8/3KNN2/8/8 w - - 0 1",
+        "8/8/4k3/8/8/3K4/7P/8 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.status(), GameStatus::Ongoing, "{fen}");
+    }
+
+    // Capturing the last pawn leaves a king and a knight against a king
+    let mut board = Board::from_fen("8/8/4k3/3P4/8/3K4/8/7N b - - 0 1").unwrap();
+    make_move(&mut board, "e6d5");
+    assert_eq!(board.status(), GameStatus::InsufficientMaterial);
+}
+
+#[test]
+fn test_draw_dead_position() {
+    // Locking the last pawns leaves the kings no way through
+    let board = Board::from_fen("8/8/1k6/p1p1p1p1/P1P1P1P1/8/5K2/8 w - - 0 1").unwrap();
+    assert_eq!(board.status(), GameStatus::DeadPosition);
     assert_eq!(board.winner(), Some(Winner::Draw));
+
+    let mut board = Board::from_fen("8/8/1k6/p1p1p1p1/2P1P1P1/P7/5K2/8 w - - 0 1").unwrap();
+    assert_eq!(board.status(), GameStatus::Ongoing);
+    make_move(&mut board, "a3a4");
+    assert_eq!(board.status(), GameStatus::DeadPosition);
+
+    for fen in [
+        // The b-pawn can still capture
+        "8/8/1k6/p1p1p1p1/PPP1P1P1/8/5K2/8 w - - 0 1",
+        // The white king reaches the a5 pawn
+        "8/8/1k6/p1p1p1p1/1PP1P1P1/8/5K2/8 w - - 0 1",
+        // The h-pawn is free to advance
+        "8/8/1k6/p1p1p1p1/P1P1P1P1/7P/5K2/8 w - - 0 1",
+        // A bishop is left
+        "8/8/1k6/p1p1p1p1/P1P1P1P1/8/5K2/7B w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.status(), GameStatus::Ongoing, "{fen}");
+    }
+}
+
+#[test]
+fn test_draw_seventy_five_moves() {
+    let mut board = Board::from_fen("8/8/4k3/8/8/3K4/8/R7 w - - 149 100").unwrap();
+    assert_eq!(board.status(), GameStatus::Ongoing);
+    make_move(&mut board, "a1a2");
+    assert_eq!(board.status(), GameStatus::SeventyFiveMoves);
+    assert!(board.make_move(Move::from_lan("e6e5").unwrap()).is_err());
+}
+
+#[test]
+fn test_lan_roundtrip() {
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        "2r1kr2/8/8/8/8/8/8/1R2K1R1 w GBfc - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        for mov in board.color_moves(board.active()) {
+            let lan = mov.to_lan();
+            let mut expected = board.clone();
+            expected.make_move(mov).unwrap();
+            let mut actual = board.clone();
+            actual
+                .make_move(Move::from_lan(&lan).unwrap())
+                .unwrap_or_else(|err| panic!("{lan} failed: {err:?}"));
+            assert_eq!(actual.zobrist(), expected.zobrist(), "{fen} {lan}");
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use shax::board::Board;
+use shax::stop::{StopToken, Stopped};
+use std::thread;
+use std::time::Duration;
+
+fn assert_perft(fen: &str, expected: &[u64]) {
+    let board = Board::from_fen(fen).unwrap_or_else(|err| panic!("Parsing {fen} failed: {err}"));
+    for (depth, &nodes) in expected.iter().enumerate() {
+        assert_eq!(
+            board.perft(depth + 1),
+            nodes,
+            "{fen} at depth {}",
+            depth + 1
+        );
+    }
+}
+
+#[test]
+fn test_perft_initial() {
+    assert_perft(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        &[20, 400, 8902, 197281],
+    );
+}
+
+#[test]
+fn test_perft_kiwipete() {
+    assert_perft(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[48, 2039, 97862, 4085603],
+    );
+}
+
+#[test]
+fn test_perft_position_3() {
+    assert_perft(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[14, 191, 2812, 43238],
+    );
+}
+
+#[test]
+fn test_perft_position_4() {
+    assert_perft(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        &[6, 264, 9467],
+    );
+}
+
+#[test]
+fn test_perft_position_5() {
+    assert_perft(
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        &[44, 1486, 62379],
+    );
+}
+
+#[test]
+fn test_perft_position_6() {
+    assert_perft(
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        &[46, 2079, 89890],
+    );
+}
+
+#[test]
+fn test_perft_chess960() {
+    assert_perft(
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        &[21, 528, 12189],
+    );
+    assert_perft(
+        "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        &[20, 479, 10471],
+    );
+    assert_perft(
+        "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+        &[22, 593, 13440],
+    );
+    assert_perft(
+        "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+        &[28, 1120, 31058],
+    );
+}
+
+#[test]
+fn test_perft_divide() {
+    let board =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+    let divide = board.perft_divide(2);
+    assert_eq!(divide.len(), 48);
+    assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+}
+
+#[test]
+fn test_perft_stopped_from_another_thread() {
+    let token = StopToken::new();
+    let stopper = {
+        let token = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            token.stop();
+        })
+    };
+    assert_eq!(Board::default().try_perft(8, &token), Err(Stopped));
+    stopper.join().unwrap();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_perft_parallel_matches_sequential() {
+    let board =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+    assert_eq!(board.perft_divide_parallel(3), board.perft_divide(3));
+    assert_eq!(board.perft_parallel(3), 97862);
+    assert_eq!(board.perft_parallel(1), 48);
+    assert_eq!(board.perft_parallel(0), 1);
+}
@@ -0,0 +1,40 @@
+use shax::notation::Move;
+use shax::pgn::Pgn;
+
+#[test]
+fn test_pgn_anderssen_vs_kieseritzky() {
+    let mut pgn = Pgn::new();
+    pgn.set_tag("White", "Adolf Anderssen");
+    pgn.set_tag("Black", "Lionel Kieseritzky");
+    for mov in [
+        "e2e4", "e7e5", "f2f4", "e5f4", "f1c4", "d8h4", "e1f1", "b7b5", "c4b5", "g8f6", "g1f3",
+        "h4h6", "d2d3", "f6h5", "f3h4", "h6g5", "h4f5", "c7c6", "g2g4", "h5f6", "h1g1", "c6b5",
+        "h2h4", "g5g6", "h4h5", "g6g5", "d1f3", "f6g8", "c1f4", "g5f6", "b1c3", "f8c5", "c3d5",
+        "f6b2", "f4d6", "c5g1", "e4e5", "b2a1", "f1e2", "b8a6", "f5g7", "e8d8", "f3f6", "g8f6",
+        "d6e7",
+    ] {
+        pgn.push(Move::from_lan(mov).unwrap());
+    }
+
+    let text = pgn.write().unwrap();
+    assert!(text.starts_with("[Event \"?\"]\n"));
+    assert!(text.contains("[Result \"1-0\"]\n\n1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 b5 5. Bxb5"));
+    assert!(text.contains("23. Be7# 1-0\n"));
+    assert!(text.lines().all(|line| line.len() <= 80));
+}
+
+#[test]
+fn test_pgn_castling_and_disambiguation() {
+    let mut pgn = Pgn::new();
+    for mov in [
+        "g1f3", "g8f6", "b1c3", "b8c6", "c3e4", "c6e5", "f3g5", "e5g4", "e4d6", "c7d6", "e2e4",
+        "e7e6", "f1e2", "f8e7", "e1g1",
+    ] {
+        pgn.push(Move::from_lan(mov).unwrap());
+    }
+
+    let text = pgn.write().unwrap();
+    assert!(text.ends_with(
+        "1. Nf3 Nf6 2. Nc3 Nc6 3. Ne4 Ne5 4. Nfg5 Neg4 5. Nd6+ cxd6 6. e4 e6 7. Be2 Be7\n8. O-O *\n"
+    ));
+}
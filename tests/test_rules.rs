@@ -0,0 +1,220 @@
+//! Rules edge cases, each checked against the exact set of legal moves in a small position.
+
+use shax::board::Board;
+use shax::notation::{CastlingSide, Color, Move, Square};
+
+fn board(fen: &str) -> Board {
+    Board::from_fen(fen).unwrap_or_else(|err| panic!("Parsing {fen} failed: {err}"))
+}
+
+fn sorted_lan(moves: impl IntoIterator<Item = Move>) -> Vec<String> {
+    let mut moves: Vec<String> = moves.into_iter().map(|mov| mov.to_lan()).collect();
+    moves.sort();
+    moves
+}
+
+fn sorted(expected: &[&str]) -> Vec<String> {
+    let mut expected: Vec<String> = expected.iter().map(|mov| mov.to_string()).collect();
+    expected.sort();
+    expected
+}
+
+/// Asserts the legal moves of the side to move.
+fn assert_moves(fen: &str, expected: &[&str]) {
+    let board = board(fen);
+    assert_eq!(
+        sorted_lan(board.color_moves(board.active())),
+        sorted(expected),
+        "{fen}"
+    );
+}
+
+/// Asserts the legal moves of the piece on `square`.
+fn assert_square_moves(fen: &str, square: Square, expected: &[&str]) {
+    let mut board = board(fen);
+    assert_eq!(
+        sorted_lan(board.selection(square).moves().iter().copied()),
+        sorted(expected),
+        "{fen} from {square}"
+    );
+}
+
+#[test]
+fn test_promotion_with_capture() {
+    assert_square_moves(
+        "1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        Square::A7,
+        &[
+            "a7a8q", "a7a8r", "a7a8b", "a7a8n", "a7b8q", "a7b8r", "a7b8b", "a7b8n",
+        ],
+    );
+    // Blocked pawn can only promote by capturing
+    assert_square_moves(
+        "rn2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        Square::A7,
+        &["a7b8q", "a7b8r", "a7b8b", "a7b8n"],
+    );
+    // Capturing the checking rook is the only way out of check besides moving the king
+    assert_moves(
+        "1r2k3/P7/8/8/8/8/8/1K6 w - - 0 1",
+        &[
+            "a7b8q", "a7b8r", "a7b8b", "a7b8n", "b1a1", "b1a2", "b1c1", "b1c2",
+        ],
+    );
+}
+
+#[test]
+fn test_promotion_with_capture_pinned() {
+    // A knight on c8 does not pin the pawn, so it may push or capture
+    assert_square_moves(
+        "2n1k3/1P6/K7/8/8/8/8/8 w - - 0 1",
+        Square::B7,
+        &[
+            "b7b8q", "b7b8r", "b7b8b", "b7b8n", "b7c8q", "b7c8r", "b7c8b", "b7c8n",
+        ],
+    );
+    // A bishop on c8 pins it along the diagonal, so it may only capture the pinner
+    assert_square_moves(
+        "2b1k3/1P6/K7/8/8/8/8/8 w - - 0 1",
+        Square::B7,
+        &["b7c8q", "b7c8r", "b7c8b", "b7c8n"],
+    );
+}
+
+#[test]
+fn test_double_check_allows_only_king_moves() {
+    // Rook e8 and bishop b4 both check, the rook on a4 cannot take or block
+    assert_moves(
+        "4r1k1/8/8/8/Rb6/8/8/4K3 w - - 0 1",
+        &["e1d1", "e1f1", "e1f2"],
+    );
+    // Knight f6 and rook e1 both check, the queen cannot capture the knight and d7 is covered
+    // by the knight
+    assert_moves("3qk3/8/5N2/8/8/8/8/4RK2 b - - 0 1", &["e8f7", "e8f8"]);
+}
+
+#[test]
+fn test_king_cannot_retreat_along_checking_ray() {
+    assert_moves(
+        "4r1k1/8/8/8/8/8/4K3/8 w - - 0 1",
+        &["e2d1", "e2d2", "e2d3", "e2f1", "e2f2", "e2f3"],
+    );
+}
+
+#[test]
+fn test_discovered_check() {
+    // Every knight move uncovers the rook on the king
+    let mut board = board("4k3/8/8/8/4N3/8/8/4R1K1 w - - 0 1");
+    let moves = board.selection(Square::E4).moves().to_vec();
+    assert_eq!(moves.len(), 8);
+    for mov in moves {
+        assert!(board.gives_check(mov), "{mov}");
+        let mut after = board.clone();
+        after.make_move(mov).unwrap();
+        assert!(after.is_check(Color::Black), "{mov}");
+    }
+}
+
+#[test]
+fn test_en_passant_discovering_check_on_rank() {
+    // Capturing en passant removes both pawns from the fifth rank, exposing the king
+    assert_moves(
+        "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+        &["a5a4", "a5a6", "a5b4", "a5b5", "a5b6", "e5e6"],
+    );
+}
+
+#[test]
+fn test_impossible_en_passant_square() {
+    // The square must be behind a pawn of the side not to move, which has just pushed it
+    for fen in [
+        "4k3/8/8/8/8/8/3P4/4K3 w - e3 0 1",
+        "4k3/8/8/3P4/8/8/8/4K3 w - e6 0 1",
+        "4k3/8/8/3Pp3/8/8/8/4K3 b - e6 0 1",
+        "4k3/8/4n3/3Pp3/8/8/8/4K3 w - e6 0 1",
+    ] {
+        assert!(Board::from_fen(fen).is_err(), "{fen}");
+    }
+    assert_square_moves(
+        "4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1",
+        Square::D5,
+        &["d5d6", "d5e6"],
+    );
+}
+
+#[test]
+fn test_pinned_pieces() {
+    // A pinned knight cannot move at all
+    assert_square_moves("4k3/4r3/8/8/4N3/8/8/4K3 w - - 0 1", Square::E4, &[]);
+    // A pinned bishop can move along the pin, including capturing the pinner
+    assert_square_moves(
+        "4k3/8/8/1b6/8/3B4/8/5K2 w - - 0 1",
+        Square::D3,
+        &["d3b5", "d3c4", "d3e2"],
+    );
+    // A pinned pawn can push along a file pin but not capture off it
+    assert_square_moves("4r1k1/8/8/3p4/4P3/8/8/4K3 w - - 0 1", Square::E4, &["e4e5"]);
+}
+
+#[test]
+fn test_castling_out_of_check() {
+    assert_moves(
+        "4k3/4r3/8/8/8/8/8/R3K2R w KQ - 0 1",
+        &["e1d1", "e1d2", "e1f1", "e1f2"],
+    );
+}
+
+#[test]
+fn test_castling_through_and_into_check() {
+    let castling = |fen| sorted_lan(board(fen).castling_moves(Color::White, Square::E1));
+    // f1 attacked: the king would pass through check
+    assert_eq!(castling("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1"), ["e1c1"]);
+    // g1 attacked: the king would land in check
+    assert_eq!(castling("4k3/8/8/8/8/8/6r1/R3K2R w KQ - 0 1"), ["e1c1"]);
+    // b1 attacked: only the rook passes it, so both sides are allowed
+    assert_eq!(
+        castling("4k3/8/8/8/8/8/1r6/R3K2R w KQ - 0 1"),
+        ["e1c1", "e1g1"]
+    );
+    // d1 attacked
+    assert_eq!(castling("4k3/8/8/8/8/8/3r4/R3K2R w KQ - 0 1"), ["e1g1"]);
+}
+
+#[test]
+fn test_can_castle() {
+    let can_castle = |fen, color, side| board(fen).can_castle(color, side);
+    // In check: neither side
+    for side in [CastlingSide::Kingside, CastlingSide::Queenside] {
+        assert!(!can_castle(
+            "4k3/4r3/8/8/8/8/8/R3K2R w KQ - 0 1",
+            Color::White,
+            side
+        ));
+    }
+    // Blocked path: the knight on b1 is in the way of the queenside rook only
+    let fen = "4k3/8/8/8/8/8/8/RN2K2R w KQ - 0 1";
+    assert!(can_castle(fen, Color::White, CastlingSide::Kingside));
+    assert!(!can_castle(fen, Color::White, CastlingSide::Queenside));
+    // Chess960: the king on b1 castles queenside with the rook on a1, while the knight on e1
+    // blocks the way of the king to g1
+    let fen = "1r3kr1/pppppppp/8/8/8/8/PPPPPPPP/RK2N1R1 w GAgb - 0 1";
+    assert!(can_castle(fen, Color::White, CastlingSide::Queenside));
+    assert!(!can_castle(fen, Color::White, CastlingSide::Kingside));
+}
+
+#[test]
+fn test_mate_queries_for_either_color() {
+    // Black is mated and stalemated in turn, with each side to move
+    for active in ["w", "b"] {
+        let mated = board(&format!("R5k1/5ppp/8/8/8/8/8/6K1 {active} - - 0 1"));
+        assert!(mated.is_checkmated(Color::Black));
+        assert!(!mated.is_stalemated(Color::Black));
+        assert!(!mated.is_checkmated(Color::White));
+
+        // The rook covers the b-file and the pinned bishop cannot move
+        let stalemated = board(&format!("k7/b7/8/8/8/8/8/QRK5 {active} - - 0 1"));
+        assert!(stalemated.is_stalemated(Color::Black), "{active}");
+        assert!(!stalemated.is_checkmated(Color::Black));
+        assert!(!stalemated.is_stalemated(Color::White));
+    }
+}
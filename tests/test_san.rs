@@ -0,0 +1,185 @@
+use shax::board::Board;
+use shax::notation::Move;
+use shax::pgn::{parse_san, san, ParseSanError, SanMode};
+
+/// Deterministic xorshift generator, so that failures are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Positions reached by random games from a few starting points rich in captures,
+/// promotions, castling and disambiguation.
+fn random_positions(rng: &mut Rng) -> Vec<Board> {
+    let mut positions = Vec::new();
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        "1N2k1N1/3P1P2/8/8/8/8/1p1p4/R3K2R w KQ - 0 1",
+    ] {
+        for _ in 0..2 {
+            let mut board = Board::from_fen(fen).unwrap();
+            for _ in 0..30 {
+                let moves: Vec<Move> = board.color_moves(board.active()).collect();
+                if moves.is_empty() || board.winner().is_some() {
+                    break;
+                }
+                positions.push(board.clone());
+                let mov = moves[rng.below(moves.len())];
+                board
+                    .make_move(mov)
+                    .unwrap_or_else(|err| panic!("{err} {mov:?} {board:?}\n{board:#?}"));
+            }
+        }
+    }
+    positions
+}
+
+/// Sloppy ways of writing the move that lenient mode accepts.
+fn sloppy_variants(mov: Move, san: &str) -> Vec<String> {
+    let mut variants = vec![
+        san.replace('x', ""),
+        san.trim_end_matches(['+', '#']).to_string(),
+        san.replace('=', ""),
+        format!("{san}+"),
+        format!("{san}!?"),
+        san.replace('O', "0"),
+    ];
+    if let Some(piece @ ('N' | 'R' | 'Q' | 'K')) = san.chars().next() {
+        variants.push(san.replacen(piece, &piece.to_ascii_lowercase().to_string(), 1));
+    }
+    match mov {
+        Move::Regular(regular) => {
+            variants.push(mov.to_lan());
+            variants.push(format!("{}-{}", regular.src, regular.dst));
+        }
+        Move::Promotion(_) => variants.push(mov.to_lan()),
+        Move::Castling(_) => (),
+    }
+    variants
+}
+
+#[test]
+fn test_san_roundtrip_fuzz() {
+    let mut rng = Rng(0x5eed);
+    for board in random_positions(&mut rng) {
+        for mov in board.color_moves(board.active()) {
+            let text = san(&board, mov).unwrap();
+            assert_eq!(parse_san(&board, &text, SanMode::Strict), Ok(mov), "{text}");
+            assert_eq!(
+                parse_san(&board, &text, SanMode::Lenient),
+                Ok(mov),
+                "{text}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_san_sloppy_variants_fuzz() {
+    let mut rng = Rng(0xfeed);
+    for board in random_positions(&mut rng) {
+        let moves: Vec<Move> = board.color_moves(board.active()).collect();
+        for mov in (0..4).map(|_| moves[rng.below(moves.len())]) {
+            let text = san(&board, mov).unwrap();
+            for variant in sloppy_variants(mov, &text) {
+                assert_eq!(
+                    parse_san(&board, &variant, SanMode::Lenient),
+                    Ok(mov),
+                    "{variant} for {text}"
+                );
+                if variant.trim_end_matches(['!', '?']) != text {
+                    assert!(
+                        parse_san(&board, &variant, SanMode::Strict).is_err(),
+                        "{variant} for {text}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_san_garbage_fuzz() {
+    let mut rng = Rng(0xbad);
+    let alphabet: Vec<char> = "abcdefgh12345678xKQRBNPOo0-=+#!? é".chars().collect();
+    let positions = random_positions(&mut rng);
+    for _ in 0..5000 {
+        let board = &positions[rng.below(positions.len())];
+        let len = rng.below(9);
+        let text: String = (0..len)
+            .map(|_| alphabet[rng.below(alphabet.len())])
+            .collect();
+        for mode in [SanMode::Strict, SanMode::Lenient] {
+            if let Ok(mov) = parse_san(board, &text, mode) {
+                assert!(board.color_moves(board.active()).any(|legal| legal == mov));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_san_ambiguity() {
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+    let nbd2 = Move::from_lan("b1d2").unwrap();
+
+    for mode in [SanMode::Strict, SanMode::Lenient] {
+        assert_eq!(
+            parse_san(&board, "Nd2", mode),
+            Err(ParseSanError::AmbiguousMove("Nd2".to_string()))
+        );
+        assert_eq!(parse_san(&board, "Nbd2", mode), Ok(nbd2));
+        assert_eq!(
+            parse_san(&board, "Nc4", mode),
+            Err(ParseSanError::IllegalMove("Nc4".to_string()))
+        );
+        assert_eq!(
+            parse_san(&board, "Zz9", mode),
+            Err(ParseSanError::Malformed("Zz9".to_string()))
+        );
+    }
+
+    assert_eq!(parse_san(&board, "Nb1d2", SanMode::Lenient), Ok(nbd2));
+    assert_eq!(
+        parse_san(&board, "Nb1d2", SanMode::Strict),
+        Err(ParseSanError::NonCanonical {
+            text: "Nb1d2".to_string(),
+            expected: "Nbd2".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_lenient_lan_castling() {
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    for (lan, san_text) in [
+        ("e1g1", "O-O"),
+        ("e1c1", "O-O-O"),
+        ("e1h1", "O-O"),
+        ("Ke1g1", "O-O"),
+    ] {
+        let castling = parse_san(&board, san_text, SanMode::Strict).unwrap();
+        assert_eq!(
+            parse_san(&board, lan, SanMode::Lenient),
+            Ok(castling),
+            "{lan}"
+        );
+    }
+
+    // The rook on h1 moving to g1 is not castling
+    let rook = parse_san(&board, "Rg1", SanMode::Strict).unwrap();
+    assert_eq!(parse_san(&board, "h1g1", SanMode::Lenient), Ok(rook));
+    assert!(parse_san(&board, "e1g1", SanMode::Strict).is_err());
+}
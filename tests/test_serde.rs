@@ -0,0 +1,87 @@
+#![cfg(feature = "serde")]
+
+use shax::board::Board;
+#[cfg(not(feature = "serde-structural"))]
+use shax::notation::Square;
+use shax::notation::{CastlingRights, Color, Move, Piece};
+
+#[test]
+fn test_serde_board() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let board = Board::from_fen(fen).unwrap();
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(json, format!("\"{fen}\""));
+
+    let restored: Board = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.zobrist(), board.zobrist());
+    assert!(serde_json::from_str::<Board>("\"8/8/8 w - -\"").is_err());
+}
+
+#[test]
+#[cfg(not(feature = "serde-structural"))]
+fn test_serde_moves() {
+    let moves: Vec<Move> = ["e2e4", "e7e8q", "a7b8n"]
+        .iter()
+        .map(|lan| Move::from_lan(lan).unwrap())
+        .collect();
+    let json = serde_json::to_string(&moves).unwrap();
+    assert_eq!(json, r#"["e2e4","e7e8q","a7b8n"]"#);
+    assert_eq!(serde_json::from_str::<Vec<Move>>(&json).unwrap(), moves);
+
+    for bad in [r#""e2""#, r#""e2e4 ""#, r#""e2e9""#, "42"] {
+        assert!(serde_json::from_str::<Move>(bad).is_err(), "{bad}");
+    }
+}
+
+#[test]
+#[cfg(feature = "serde-structural")]
+fn test_serde_structural() {
+    let moves = [
+        Move::from_lan("e2e4").unwrap(),
+        Move::from_lan("e7e8q").unwrap(),
+    ];
+    let json = serde_json::to_string(&moves).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"Regular":{"src":"E2","dst":"E4"}},{"Promotion":{"src":"E7","dst":"E8","piece":"Queen"}}]"#
+    );
+    assert_eq!(serde_json::from_str::<[Move; 2]>(&json).unwrap(), moves);
+    assert!(serde_json::from_str::<Move>(r#""e2e4""#).is_err());
+}
+
+#[test]
+#[cfg(not(feature = "serde-structural"))]
+fn test_serde_squares() {
+    let json = serde_json::to_string(&[Square::E4, Square::H8]).unwrap();
+    assert_eq!(json, r#"["e4","h8"]"#);
+    assert_eq!(
+        serde_json::from_str::<[Square; 2]>(&json).unwrap(),
+        [Square::E4, Square::H8]
+    );
+    assert!(serde_json::from_str::<Square>(r#""i1""#).is_err());
+}
+
+#[test]
+fn test_serde_notation() {
+    let value = (
+        Color::Black,
+        Piece::Knight,
+        CastlingRights::WHITE_KINGSIDE | CastlingRights::BLACK_QUEENSIDE,
+    );
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(json, r#"["b","n","Kq"]"#);
+    assert_eq!(
+        serde_json::from_str::<(Color, Piece, CastlingRights)>(&json).unwrap(),
+        value
+    );
+
+    assert_eq!(
+        serde_json::to_string(&CastlingRights::empty()).unwrap(),
+        r#""-""#
+    );
+    assert_eq!(
+        serde_json::from_str::<CastlingRights>(r#""-""#).unwrap(),
+        CastlingRights::empty()
+    );
+    assert!(serde_json::from_str::<CastlingRights>(r#""KK""#).is_err());
+}